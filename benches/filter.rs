@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use zero::utils::filter::SensitiveWordFilter;
+
+/// 生成指定数量的合成敏感词（两到五个汉字）
+fn synthetic_words(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| {
+            let len = 2 + i % 4;
+            (0..len)
+                .map(|j| char::from_u32(0x4E00 + ((i * 7 + j * 131) % 20000) as u32).unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+/// 一篇约 1000 字的帖子，包含少量真实词表中的词
+fn sample_post() -> String {
+    "今天在路上看到一则新闻，讲的是一起暴力犯罪案件，犯罪分子已经被抓获。".repeat(30)
+}
+
+fn bench_mask(c: &mut Criterion) {
+    let content = sample_post();
+    let mut group = c.benchmark_group("mask");
+    for size in [100, 1_000, 10_000, 50_000] {
+        let mut words = synthetic_words(size);
+        words.extend(["犯罪", "犯罪分子", "暴力犯罪"].map(String::from));
+        let filter = SensitiveWordFilter::new(words);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &filter, |b, filter| {
+            b.iter(|| filter.mask(black_box(&content)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    group.sample_size(10);
+    for size in [1_000, 10_000, 50_000] {
+        let words = synthetic_words(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &words, |b, words| {
+            b.iter(|| SensitiveWordFilter::new(words.iter().cloned()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_mask, bench_build);
+criterion_main!(benches);
@@ -0,0 +1,77 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use zero::models::format::{ContentFields, ContentFormat};
+use zero::models::post::{PostListResponse, PostSummary};
+use zero::utils::sanitize::{sanitize_content, SanitizerPolicy};
+use zero::utils::text::html_to_text;
+
+/// 一条写入时已经生成好的帖子内容，约 600 字，带有段落、列表和链接
+fn stored_html() -> String {
+    let paragraph = "<p>今天在路上看到一则新闻，讲的是一起案件，<strong>后续</strong>还在调查中。<br>\
+                     详情见 <a href=\"https://example.com/news\" rel=\"nofollow noreferrer noopener\">这里</a>。</p>";
+    format!(
+        "{}<ul><li>第一点</li><li>第二点</li></ul>{}",
+        paragraph.repeat(4),
+        paragraph.repeat(4)
+    )
+}
+
+/// 一页帖子列表
+fn list_page(size: usize, fields: ContentFields) -> PostListResponse {
+    let content = stored_html();
+    let content_text = html_to_text(&content);
+    let posts = (0..size)
+        .map(|_| PostSummary {
+            id: Uuid::new_v4(),
+            content: content.clone(),
+            content_text: Some(content_text.clone()),
+            created_at: OffsetDateTime::now_utc(),
+            comments_count: 3,
+            format: ContentFormat::Markdown,
+            fields,
+            previews: Vec::new(),
+            tags: Vec::new(),
+        })
+        .collect();
+    PostListResponse {
+        posts,
+        total: size as i64,
+        page: 1,
+        page_size: size as i64,
+    }
+}
+
+/// 列表接口序列化一页帖子的耗时
+///
+/// `stored` 直接输出写入时生成的 HTML（`?content=html`），
+/// `with_text` 同时输出写入时保存的纯文本（默认输出全部字段），
+/// `resanitize` 在输出前对每条内容再做一次换行转换和净化（以前的做法）
+fn bench_list_serialization(c: &mut Criterion) {
+    let policy = SanitizerPolicy::default();
+    let mut group = c.benchmark_group("list_serialization");
+    for size in [20, 100] {
+        let page = list_page(size, ContentFields::Html);
+        group.bench_with_input(BenchmarkId::new("stored", size), &page, |b, page| {
+            b.iter(|| serde_json::to_string(black_box(page)).unwrap())
+        });
+        let with_text = list_page(size, ContentFields::All);
+        group.bench_with_input(BenchmarkId::new("with_text", size), &with_text, |b, page| {
+            b.iter(|| serde_json::to_string(black_box(page)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("resanitize", size), &page, |b, page| {
+            b.iter(|| {
+                let contents: Vec<String> = page
+                    .posts
+                    .iter()
+                    .map(|post| sanitize_content(black_box(&post.content), &policy))
+                    .collect();
+                (contents, serde_json::to_string(black_box(page)).unwrap())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_list_serialization);
+criterion_main!(benches);
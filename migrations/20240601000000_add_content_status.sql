@@ -0,0 +1,33 @@
+-- Add moderation status to posts and comments
+ALTER TABLE posts ADD COLUMN status TEXT NOT NULL DEFAULT 'published';
+ALTER TABLE comments ADD COLUMN status TEXT NOT NULL DEFAULT 'published';
+
+CREATE INDEX IF NOT EXISTS idx_posts_status_created_at ON posts(status, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_comments_status ON comments(status);
+
+-- Only published comments count towards comments_count
+CREATE OR REPLACE FUNCTION update_post_comments_count() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.status = 'published' THEN
+            UPDATE posts SET comments_count = comments_count + 1 WHERE id = NEW.post_id;
+        END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+        IF OLD.status = 'published' THEN
+            UPDATE posts SET comments_count = comments_count - 1 WHERE id = OLD.post_id;
+        END IF;
+    ELSIF TG_OP = 'UPDATE' AND OLD.status IS DISTINCT FROM NEW.status THEN
+        IF OLD.status = 'published' THEN
+            UPDATE posts SET comments_count = comments_count - 1 WHERE id = OLD.post_id;
+        ELSIF NEW.status = 'published' THEN
+            UPDATE posts SET comments_count = comments_count + 1 WHERE id = NEW.post_id;
+        END IF;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS update_post_comments_count_trigger ON comments;
+CREATE TRIGGER update_post_comments_count_trigger
+AFTER INSERT OR UPDATE OF status OR DELETE ON comments
+FOR EACH ROW EXECUTE FUNCTION update_post_comments_count();
@@ -0,0 +1,26 @@
+-- Sensitive words managed through the admin API
+CREATE TABLE IF NOT EXISTS sensitive_words (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    word TEXT NOT NULL UNIQUE,
+    category TEXT NOT NULL DEFAULT '未分类',
+    action TEXT NOT NULL DEFAULT 'mask' CHECK (action IN ('mask', 'hold', 'reject')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_by TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sensitive_words_category ON sensitive_words(category);
+
+-- Audit log of every change to the word list
+CREATE TABLE IF NOT EXISTS sensitive_word_changes (
+    id BIGSERIAL PRIMARY KEY,
+    word TEXT NOT NULL,
+    operation TEXT NOT NULL CHECK (operation IN ('add', 'update', 'remove')),
+    category TEXT,
+    action TEXT,
+    changed_by TEXT NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sensitive_word_changes_changed_at ON sensitive_word_changes(changed_at DESC);
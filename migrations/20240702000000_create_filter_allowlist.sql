@@ -0,0 +1,8 @@
+-- Phrases that protect the text they cover from sensitive-word matches
+CREATE TABLE IF NOT EXISTS filter_allowlist (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    phrase TEXT NOT NULL UNIQUE,
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_by TEXT NOT NULL
+);
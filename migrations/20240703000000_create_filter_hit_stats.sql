@@ -0,0 +1,10 @@
+-- Daily sensitive-word hit counts, aggregated in memory and flushed periodically
+CREATE TABLE IF NOT EXISTS filter_hit_stats (
+    day DATE NOT NULL,
+    word TEXT NOT NULL,
+    category TEXT NOT NULL,
+    hits BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (day, word, category)
+);
+
+CREATE INDEX IF NOT EXISTS idx_filter_hit_stats_word ON filter_hit_stats(word, day);
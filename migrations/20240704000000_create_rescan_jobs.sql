@@ -0,0 +1,47 @@
+-- Background jobs that re-check existing posts and comments against the current word list
+CREATE TABLE IF NOT EXISTS rescan_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    status TEXT NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed', 'superseded')),
+    policy TEXT NOT NULL CHECK (policy IN ('flag', 'apply')),
+    source TEXT NOT NULL CHECK (source IN ('manual', 'reload')),
+    started_by TEXT NOT NULL,
+    -- Table currently being scanned and the keyset cursor within it
+    phase TEXT NOT NULL DEFAULT 'posts' CHECK (phase IN ('posts', 'comments', 'done')),
+    cursor_created_at TIMESTAMPTZ,
+    cursor_id UUID,
+    total BIGINT NOT NULL DEFAULT 0,
+    scanned BIGINT NOT NULL DEFAULT 0,
+    matched BIGINT NOT NULL DEFAULT 0,
+    flagged BIGINT NOT NULL DEFAULT 0,
+    masked BIGINT NOT NULL DEFAULT 0,
+    hidden BIGINT NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ
+);
+
+-- At most one job runs at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rescan_jobs_single_running
+    ON rescan_jobs ((true)) WHERE status = 'running';
+CREATE INDEX IF NOT EXISTS idx_rescan_jobs_created_at ON rescan_jobs(created_at DESC);
+
+-- Rows matched by a rescan and what was done to them
+CREATE TABLE IF NOT EXISTS rescan_matches (
+    id BIGSERIAL PRIMARY KEY,
+    job_id UUID NOT NULL REFERENCES rescan_jobs(id) ON DELETE CASCADE,
+    content_type TEXT NOT NULL CHECK (content_type IN ('post', 'comment')),
+    content_id UUID NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('mask', 'hold', 'reject')),
+    outcome TEXT NOT NULL CHECK (outcome IN ('flagged', 'masked', 'hidden')),
+    categories TEXT NOT NULL,
+    words TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rescan_matches_job_id ON rescan_matches(job_id, id);
+
+-- Keyset pagination over (created_at, id)
+CREATE INDEX IF NOT EXISTS idx_posts_created_at_id ON posts(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_comments_created_at_id ON comments(created_at, id);
@@ -0,0 +1,9 @@
+-- Keep the raw submission next to the rendered content.
+-- raw_content is only exposed through the moderation API; content stays the displayed text.
+ALTER TABLE posts ADD COLUMN raw_content TEXT;
+ALTER TABLE posts ADD COLUMN filter_version TEXT;
+ALTER TABLE posts ADD COLUMN sanitizer_version TEXT;
+
+ALTER TABLE comments ADD COLUMN raw_content TEXT;
+ALTER TABLE comments ADD COLUMN filter_version TEXT;
+ALTER TABLE comments ADD COLUMN sanitizer_version TEXT;
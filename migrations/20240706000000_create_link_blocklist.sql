@@ -0,0 +1,8 @@
+-- Domains whose links are not allowed in posts and comments (subdomains included)
+CREATE TABLE IF NOT EXISTS link_blocklist (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    domain TEXT NOT NULL UNIQUE,
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_by TEXT NOT NULL
+);
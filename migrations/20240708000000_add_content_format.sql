@@ -0,0 +1,4 @@
+-- Input format of the raw submission: 'markdown' or 'html'.
+-- Existing rows were rendered as HTML with line breaks, so they keep that format.
+ALTER TABLE posts ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html';
+ALTER TABLE comments ADD COLUMN content_format TEXT NOT NULL DEFAULT 'html';
@@ -0,0 +1,7 @@
+-- Name of the sanitizer policy used to render the content; sanitizer_version is that policy's version.
+ALTER TABLE posts ADD COLUMN sanitizer_policy TEXT;
+ALTER TABLE comments ADD COLUMN sanitizer_policy TEXT;
+
+-- Rows rendered before policies were configurable used the single built-in policy
+UPDATE posts SET sanitizer_policy = 'default' WHERE sanitizer_version IS NOT NULL;
+UPDATE comments SET sanitizer_policy = 'default' WHERE sanitizer_version IS NOT NULL;
@@ -0,0 +1,21 @@
+-- Cached previews of links posted in content, one row per URL, fetched in the background
+CREATE TABLE IF NOT EXISTS link_previews (
+    url TEXT PRIMARY KEY,
+    -- 'pending' while a fetch is queued, 'ready' once metadata is stored, 'failed' otherwise
+    status TEXT NOT NULL DEFAULT 'pending',
+    title TEXT,
+    description TEXT,
+    site_name TEXT,
+    -- Why the last fetch failed
+    error TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Links in each post or comment that get a preview, in the order they appear
+CREATE TABLE IF NOT EXISTS content_links (
+    content_kind TEXT NOT NULL,
+    content_id UUID NOT NULL,
+    position INTEGER NOT NULL,
+    url TEXT NOT NULL REFERENCES link_previews(url) ON DELETE CASCADE,
+    PRIMARY KEY (content_kind, content_id, position)
+);
@@ -0,0 +1,32 @@
+-- Floor number of each comment within its thread, starting at 1 in creation order.
+-- posts.floor_count hands out the next number; updating it locks the post row, so
+-- concurrent comments on one thread never share a floor.
+ALTER TABLE comments ADD COLUMN floor INTEGER;
+ALTER TABLE posts ADD COLUMN floor_count INTEGER NOT NULL DEFAULT 0;
+
+UPDATE comments c SET floor = numbered.floor
+FROM (
+    SELECT id, row_number() OVER (PARTITION BY post_id ORDER BY created_at, id) AS floor
+    FROM comments
+) numbered
+WHERE c.id = numbered.id;
+
+UPDATE posts p SET floor_count = COALESCE((SELECT MAX(floor) FROM comments c WHERE c.post_id = p.id), 0);
+
+ALTER TABLE comments ALTER COLUMN floor SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_comments_post_floor ON comments(post_id, floor);
+
+-- Short ids (the first 8 hex digits of the id) used in >>shortid references
+CREATE INDEX IF NOT EXISTS idx_posts_short_id ON posts(left(id::text, 8));
+
+-- References written in content as >>shortid or >>#floor, resolved when the content is rendered
+CREATE TABLE IF NOT EXISTS content_references (
+    source_kind TEXT NOT NULL,
+    source_id UUID NOT NULL,
+    target_kind TEXT NOT NULL,
+    target_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (source_kind, source_id, target_kind, target_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_references_target ON content_references(target_kind, target_id);
@@ -0,0 +1,21 @@
+-- Hashtags written in posts as #tag, keyed by the normalized name
+CREATE TABLE IF NOT EXISTS tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- NFKC, lowercased, simplified Chinese
+    name TEXT NOT NULL UNIQUE,
+    -- Published posts carrying the tag, recomputed whenever a tagged post is written or changes status
+    post_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Tags of each post; kept for held posts too, so approving a post only needs a recount
+CREATE TABLE IF NOT EXISTS post_tags (
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_tags_tag ON post_tags(tag_id);
+
+-- Posts whose tags have been extracted; existing posts are tagged in batches at startup
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS tagged BOOLEAN NOT NULL DEFAULT false;
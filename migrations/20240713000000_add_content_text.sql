@@ -0,0 +1,4 @@
+-- Plain-text version of content, derived from the stored HTML when the content is written.
+-- Existing rows are filled in at startup; NULL means not filled in yet.
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_text TEXT;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS content_text TEXT;
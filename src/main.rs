@@ -1,6 +1,5 @@
 use std::net::SocketAddr;
 
-use crate::utils::filter::reload_sensitive_words;
 use axum::{
     extract::Extension,
     http::{header, HeaderValue, Method},
@@ -11,16 +10,25 @@ use axum::{
 use dotenv::dotenv;
 use sqlx::postgres::PgPoolOptions;
 use std::env;
+use std::sync::Arc;
 use tower_http::compression::CompressionLayer;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
 use tower_http::set_header::SetResponseHeaderLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-mod models;
-mod routes;
-mod schema;
-mod utils;
+use zero::routes;
+use zero::utils::filter::{reload_sensitive_words, watch_sensitive_words};
+use zero::utils::filter::rescan::{resume_rescans, spawn_rescan_on_reload};
+use zero::utils::filter::stats::start_hit_recorder;
+use zero::utils::moderation::ModerationPipeline;
+use zero::utils::filter::store::reload_database_words;
+use zero::utils::links::preview::PreviewFetcher;
+use zero::utils::links::store::reload_blocked_domains;
+use zero::utils::api_key;
+use zero::models::kind::ContentKind;
+use zero::utils::sanitize::policy_for;
+use zero::utils::render::{backfill_stored_content, count_stale_html};
+use zero::utils::tags::tag_existing_posts;
 
 // Security middleware to add headers to responses
 async fn add_security_headers(
@@ -120,16 +128,106 @@ async fn main() -> anyhow::Result<()> {
     // 运行数据库迁移
     sqlx::migrate!("./migrations").run(&pool).await?;
 
+    // 命令行子命令：管理 API 密钥，执行后退出而不启动服务
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("api-key") => return api_key::cli::run(&pool, &args[1..]).await,
+        Some(other) => anyhow::bail!("未知的子命令: {}，可用的子命令: api-key", other),
+        None => {}
+    }
+
     // 加载敏感词列表
     match reload_sensitive_words() {
         Ok(count) => tracing::info!("成功加载敏感词列表，共 {} 个词", count),
         Err(err) => tracing::warn!("无法加载敏感词列表: {}", err),
     }
 
+    // 合并数据库中的敏感词
+    match reload_database_words(&pool).await {
+        Ok(count) => tracing::info!("成功合并数据库中的敏感词，共 {} 个词", count),
+        Err(err) => tracing::warn!("无法加载数据库中的敏感词: {}", err),
+    }
+
+    // 加载链接的域名黑名单
+    match reload_blocked_domains(&pool).await {
+        Ok(count) => tracing::info!("成功加载域名黑名单，共 {} 个域名", count),
+        Err(err) => tracing::warn!("无法加载域名黑名单: {}", err),
+    }
+
+    // 监听敏感词列表文件，修改后自动重新加载；监听器需要在服务运行期间一直持有
+    let watch_enabled = env::var("FILTER_WATCH").map_or(true, |v| v != "false" && v != "0");
+    let _filter_watcher = if watch_enabled {
+        watch_sensitive_words()
+            .map_err(|err| tracing::warn!("无法监听敏感词列表文件，修改后需要手动重新加载: {}", err))
+            .ok()
+    } else {
+        None
+    };
+
+    // 在后台汇总敏感词命中次数并定期写入数据库
+    start_hit_recorder(pool.clone());
+
+    // 构建审核流水线
+    let moderation = Arc::new(ModerationPipeline::from_env().map_err(anyhow::Error::msg)?);
+    tracing::info!("审核流水线: {}", moderation.names().join(" -> "));
+
+    // 继续执行重启前没有完成的重新扫描任务，并按配置在词表变化后自动重新扫描；
+    // 替换文字时与发布时一样经过审核流水线重新生成内容
+    if let Err(err) = resume_rescans(&pool, &moderation).await {
+        tracing::warn!("无法恢复重新扫描任务: {}", err);
+    }
+    spawn_rescan_on_reload(pool.clone(), Arc::clone(&moderation));
+
+    // 创建链接预览抓取器
+    let previews = PreviewFetcher::from_env().map_err(anyhow::Error::msg)?;
+    if previews.config().enabled {
+        tracing::info!(
+            "链接预览: 超时 {} 毫秒，最多跳转 {} 次，最多读取 {} 字节",
+            previews.config().timeout.as_millis(),
+            previews.config().max_redirects,
+            previews.config().max_bytes
+        );
+    } else {
+        tracing::info!("链接预览: 已关闭，只显示已经缓存的预览");
+    }
+
+    // 加载净化策略配置
+    let post_policy = policy_for(ContentKind::Post);
+    let comment_policy = policy_for(ContentKind::Comment);
+    tracing::info!(
+        "净化策略: 帖子 {} ({})，评论 {} ({})",
+        post_policy.name(),
+        post_policy.version(),
+        comment_policy.name(),
+        comment_policy.version()
+    );
+
+    // 显示内容和纯文本在写入时生成、输出时不再处理，先为之前保存的内容生成一次
+    match backfill_stored_content(&pool).await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("已为 {} 条已有内容生成显示内容或纯文本", count),
+        Err(err) => tracing::warn!("无法为已有内容生成显示内容或纯文本: {}", err),
+    }
+    match count_stale_html(&pool).await {
+        Ok(0) => {}
+        Ok(count) => tracing::warn!(
+            "{} 条内容的净化策略与当前配置不同，保留原有显示内容，可以用重新生成接口按当前策略生成",
+            count
+        ),
+        Err(err) => tracing::warn!("无法统计净化策略不同的内容: {}", err),
+    }
+
+    // 为话题功能上线之前发布的帖子提取话题，需要在加载词表之后
+    match tag_existing_posts(&pool).await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("已为 {} 个已有帖子提取话题", count),
+        Err(err) => tracing::warn!("无法为已有帖子提取话题: {}", err),
+    }
+
     // 配置 CORS
     let cors = CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::DELETE])
-        .allow_headers([header::CONTENT_TYPE])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
         .allow_origin(Any);
 
     // API 路由
@@ -139,8 +237,14 @@ async fn main() -> anyhow::Result<()> {
         .route("/posts/:id", get(routes::post::get_post))
         .route("/posts/:id/comments", get(routes::comment::get_comments))
         .route("/posts/:id/comments", post(routes::comment::create_comment))
+        .route("/tags", get(routes::tag::get_tags))
+        .route("/tags/:tag/posts", get(routes::tag::get_tag_posts))
         .nest("/filter", routes::filter::filter_routes())
+        .nest("/moderation", routes::moderation::moderation_routes())
+        .nest("/links", routes::links::link_routes())
         .layer(Extension(pool.clone()))
+        .layer(Extension(moderation))
+        .layer(Extension(Arc::new(previews)))
         .layer(cors.clone());
 
     // 静态文件服务 - 添加优化配置
@@ -0,0 +1,19 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// 管理接口的 API 密钥，不包含密钥本身
+#[derive(Debug, Serialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    /// 密钥的前几个字符，用于区分不同的密钥
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_used_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+}
@@ -3,21 +3,39 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-use crate::utils::sanitize::sanitize_content;
+use crate::models::format::{ContentFields, ContentFormat};
+use crate::models::link::LinkPreview;
+use crate::models::status::ContentStatus;
 
 // 数据库中的评论结构
 #[derive(Debug, FromRow)]
 pub struct Comment {
     pub id: Uuid,
     pub post_id: Uuid,
+    /// 在帖子下的楼层，从 1 开始按发表顺序编号，用于 `>>#floor` 引用
+    pub floor: i32,
+    /// 写入时生成并按当前净化策略净化的 HTML，输出时不再处理
     pub content: String,
+    /// 写入时由 `content` 生成的纯文本，还没有生成的旧内容为 None
+    #[sqlx(default)]
+    pub content_text: Option<String>,
 
     pub created_at: OffsetDateTime,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
+    #[sqlx(skip)]
+    pub status: ContentStatus,
+    #[sqlx(skip)]
+    pub format: ContentFormat,
+    /// 响应中输出的内容字段
+    #[sqlx(skip)]
+    pub fields: ContentFields,
+    /// 内容中链接的预览，在后台抓取，还没有抓取到时为空
+    #[sqlx(skip)]
+    pub previews: Vec<LinkPreview>,
 }
 
-// 为Comment实现自定义序列化，确保content字段经过安全处理
+// 为Comment实现自定义序列化；content 是写入时生成并净化的 HTML，按选择输出 HTML 和纯文本
 impl Serialize for Comment {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -29,13 +47,17 @@ impl Serialize for Comment {
         let created_at_str = self.created_at.format(&time::format_description::well_known::Iso8601::DEFAULT)
             .unwrap_or_else(|_| String::from(""));
             
-        let mut state = serializer.serialize_struct("Comment", 6)?;
+        let mut state = serializer.serialize_struct("Comment", 12)?;
         state.serialize_field("id", &self.id)?;
         state.serialize_field("post_id", &self.post_id)?;
-        state.serialize_field("content", &sanitize_content(&self.content))?;
+        state.serialize_field("floor", &self.floor)?;
+        self.fields.serialize(&mut state, &self.content, self.content_text.as_deref())?;
         state.serialize_field("created_at", &created_at_str)?;
         state.serialize_field("ip_address", &self.ip_address)?;
         state.serialize_field("user_agent", &self.user_agent)?;
+        state.serialize_field("status", &self.status)?;
+        state.serialize_field("format", &self.format)?;
+        state.serialize_field("previews", &self.previews)?;
         state.end()
     }
 }
@@ -45,6 +67,9 @@ impl Serialize for Comment {
 pub struct CreateCommentRequest {
     #[serde(deserialize_with = "deserialize_and_validate_content")]
     pub content: String,
+    /// 原文格式，不指定时使用配置的默认格式
+    #[serde(default)]
+    pub format: Option<ContentFormat>,
 }
 
 // 验证评论内容的自定义反序列化函数
@@ -0,0 +1,97 @@
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::utils::text::html_to_text;
+
+/// 帖子和评论原文的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    /// Markdown，转换为 HTML 后净化
+    Markdown,
+    /// 原始 HTML，换行转为 `<br>` 后净化；支持 Markdown 之前的内容都是这种格式，
+    /// 也是不指定格式的请求的默认格式，旧客户端提交的内容含义不变
+    #[default]
+    Html,
+}
+
+impl ContentFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentFormat::Markdown => "markdown",
+            ContentFormat::Html => "html",
+        }
+    }
+
+    /// 从数据库中的字符串解析，无法识别的值按 HTML 处理
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "markdown" => ContentFormat::Markdown,
+            _ => ContentFormat::Html,
+        }
+    }
+}
+
+impl FromStr for ContentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(ContentFormat::Markdown),
+            "html" => Ok(ContentFormat::Html),
+            other => Err(format!("未知的内容格式: {}", other)),
+        }
+    }
+}
+
+/// 响应中输出哪些内容字段，由查询参数 `content` 选择
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFields {
+    /// 输出 `content_html`、`content_text` 以及兼容旧客户端的 `content`
+    #[default]
+    All,
+    /// 只输出 `content_html`
+    Html,
+    /// 只输出 `content_text`
+    Text,
+}
+
+/// 选择内容字段的查询参数，例如 `?content=text`
+#[derive(Debug, Default, Deserialize)]
+pub struct ContentFieldsParams {
+    #[serde(default)]
+    pub content: ContentFields,
+}
+
+impl ContentFields {
+    /// 按选择输出内容字段
+    ///
+    /// `content` 与 `content_html` 相同，在弃用期内只在没有选择字段时输出
+    ///
+    /// # Arguments
+    /// * `state` - 正在序列化的结构
+    /// * `html` - 写入时生成并净化的 HTML
+    /// * `text` - 写入时由 HTML 生成的纯文本；还没有生成的旧内容为 None，此时临时转换
+    pub fn serialize<S: SerializeStruct>(
+        self,
+        state: &mut S,
+        html: &str,
+        text: Option<&str>,
+    ) -> Result<(), S::Error> {
+        if self == ContentFields::All {
+            state.serialize_field("content", html)?;
+        }
+        if self != ContentFields::Text {
+            state.serialize_field("content_html", html)?;
+        }
+        if self != ContentFields::Html {
+            match text {
+                Some(text) => state.serialize_field("content_text", text)?,
+                None => state.serialize_field("content_text", &html_to_text(html))?,
+            }
+        }
+        Ok(())
+    }
+}
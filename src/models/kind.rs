@@ -0,0 +1,34 @@
+use serde::Serialize;
+
+/// 用户提交的内容种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    Post,
+    Comment,
+}
+
+impl ContentKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentKind::Post => "post",
+            ContentKind::Comment => "comment",
+        }
+    }
+
+    /// 从数据库中的字符串解析，无法识别的值按帖子处理
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "comment" => ContentKind::Comment,
+            _ => ContentKind::Post,
+        }
+    }
+
+    /// 保存这种内容的数据库表
+    pub fn table(self) -> &'static str {
+        match self {
+            ContentKind::Post => "posts",
+            ContentKind::Comment => "comments",
+        }
+    }
+}
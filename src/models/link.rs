@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// 域名黑名单中的一个域名
+#[derive(Debug, Serialize)]
+pub struct BlockedDomain {
+    pub id: Uuid,
+    /// 规范化后的域名（小写、punycode）
+    pub domain: String,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub created_by: String,
+}
+
+// 添加黑名单域名的请求结构
+#[derive(Debug, Deserialize)]
+pub struct CreateBlockedDomainRequest {
+    pub domain: String,
+    pub note: Option<String>,
+}
+
+// 域名黑名单响应结构
+#[derive(Debug, Serialize)]
+pub struct BlockedDomainListResponse {
+    pub domains: Vec<BlockedDomain>,
+}
+
+// 链接检查请求结构
+#[derive(Debug, Deserialize)]
+pub struct TestLinksRequest {
+    pub content: String,
+}
+
+// 内容中的一个链接及其检查结果
+#[derive(Debug, Serialize)]
+pub struct TestLink {
+    pub url: String,
+    pub host: Option<String>,
+    /// 是否计入数量限制
+    pub explicit: bool,
+    /// 挡住这个链接的黑名单域名，没有被挡住时为 null
+    pub blocked_by: Option<String>,
+}
+
+// 链接检查响应结构
+#[derive(Debug, Serialize)]
+pub struct TestLinksResponse {
+    pub links: Vec<TestLink>,
+}
+
+// 内容中一个链接的预览
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkPreview {
+    pub url: String,
+    /// 页面标题，优先使用 Open Graph 的 og:title
+    pub title: String,
+    pub description: Option<String>,
+    /// 站点名称，页面没有提供时为域名
+    pub site_name: Option<String>,
+}
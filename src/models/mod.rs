@@ -1,2 +1,11 @@
 pub mod post;
-pub mod comment;
\ No newline at end of file
+pub mod comment;
+pub mod status;
+pub mod format;
+pub mod kind;
+pub mod sensitive_word;
+pub mod rescan;
+pub mod link;
+pub mod api_key;
+pub mod reference;
+pub mod tag;
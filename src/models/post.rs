@@ -3,21 +3,45 @@ use sqlx::FromRow;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::utils::sanitize::sanitize_content;
+use crate::models::format::{ContentFields, ContentFormat};
+use crate::models::link::LinkPreview;
+use crate::models::reference::Backlink;
+use crate::models::status::ContentStatus;
+use crate::utils::references::short_id;
 
 // 数据库中的帖子结构
 #[derive(Debug, FromRow)]
 pub struct Post {
     pub id: Uuid,
+    /// 写入时生成并按当前净化策略净化的 HTML，输出时不再处理
     pub content: String,
+    /// 写入时由 `content` 生成的纯文本，还没有生成的旧内容为 None
+    #[sqlx(default)]
+    pub content_text: Option<String>,
     pub created_at: OffsetDateTime,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
     #[sqlx(default)]
     pub comments_count: i64,
+    #[sqlx(skip)]
+    pub status: ContentStatus,
+    #[sqlx(skip)]
+    pub format: ContentFormat,
+    /// 响应中输出的内容字段
+    #[sqlx(skip)]
+    pub fields: ContentFields,
+    /// 内容中链接的预览，在后台抓取，还没有抓取到时为空
+    #[sqlx(skip)]
+    pub previews: Vec<LinkPreview>,
+    /// 引用了这个帖子或它的评论的内容，只在获取单个帖子时查询
+    #[sqlx(skip)]
+    pub referenced_by: Vec<Backlink>,
+    /// 帖子中的话题，规范化后的名称
+    #[sqlx(skip)]
+    pub tags: Vec<String>,
 }
 
-// 为Post实现自定义序列化，确保content字段经过安全处理
+// 为Post实现自定义序列化；content 是写入时生成并净化的 HTML，按选择输出 HTML 和纯文本
 impl Serialize for Post {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -29,13 +53,19 @@ impl Serialize for Post {
         let created_at_str = self.created_at.format(&time::format_description::well_known::Iso8601::DEFAULT)
             .unwrap_or_else(|_| String::from(""));
             
-        let mut state = serializer.serialize_struct("Post", 6)?;
+        let mut state = serializer.serialize_struct("Post", 14)?;
         state.serialize_field("id", &self.id)?;
-        state.serialize_field("content", &sanitize_content(&self.content))?;
+        state.serialize_field("short_id", &short_id(self.id))?;
+        self.fields.serialize(&mut state, &self.content, self.content_text.as_deref())?;
         state.serialize_field("created_at", &created_at_str)?;
         state.serialize_field("ip_address", &self.ip_address)?;
         state.serialize_field("user_agent", &self.user_agent)?;
         state.serialize_field("comments_count", &self.comments_count)?;
+        state.serialize_field("status", &self.status)?;
+        state.serialize_field("format", &self.format)?;
+        state.serialize_field("previews", &self.previews)?;
+        state.serialize_field("tags", &self.tags)?;
+        state.serialize_field("referenced_by", &self.referenced_by)?;
         state.end()
     }
 }
@@ -45,6 +75,9 @@ impl Serialize for Post {
 pub struct CreatePostRequest {
     #[serde(deserialize_with = "deserialize_and_validate_content")]
     pub content: String,
+    /// 原文格式，不指定时使用配置的默认格式
+    #[serde(default)]
+    pub format: Option<ContentFormat>,
 }
 
 // 验证帖子内容的自定义反序列化函数
@@ -80,12 +113,22 @@ pub struct PostListResponse {
 #[derive(Debug)]
 pub struct PostSummary {
     pub id: Uuid,
+    /// 写入时生成并按当前净化策略净化的 HTML，输出时不再处理
     pub content: String,
+    /// 写入时由 `content` 生成的纯文本，还没有生成的旧内容为 None
+    pub content_text: Option<String>,
     pub created_at: OffsetDateTime,
     pub comments_count: i64,
+    pub format: ContentFormat,
+    /// 响应中输出的内容字段
+    pub fields: ContentFields,
+    /// 内容中链接的预览，在后台抓取，还没有抓取到时为空
+    pub previews: Vec<LinkPreview>,
+    /// 帖子中的话题，规范化后的名称
+    pub tags: Vec<String>,
 }
 
-// 为PostSummary实现自定义序列化，确保content字段经过安全处理
+// 为PostSummary实现自定义序列化；content 是写入时生成并净化的 HTML，按选择输出 HTML 和纯文本
 impl Serialize for PostSummary {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -97,11 +140,15 @@ impl Serialize for PostSummary {
         let created_at_str = self.created_at.format(&time::format_description::well_known::Iso8601::DEFAULT)
             .unwrap_or_else(|_| String::from(""));
             
-        let mut state = serializer.serialize_struct("PostSummary", 4)?;
+        let mut state = serializer.serialize_struct("PostSummary", 10)?;
         state.serialize_field("id", &self.id)?;
-        state.serialize_field("content", &sanitize_content(&self.content))?;
+        state.serialize_field("short_id", &short_id(self.id))?;
+        self.fields.serialize(&mut state, &self.content, self.content_text.as_deref())?;
         state.serialize_field("created_at", &created_at_str)?;
         state.serialize_field("comments_count", &self.comments_count)?;
+        state.serialize_field("format", &self.format)?;
+        state.serialize_field("previews", &self.previews)?;
+        state.serialize_field("tags", &self.tags)?;
         state.end()
     }
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::kind::ContentKind;
+
+// 引用了某个帖子或其评论的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct Backlink {
+    pub kind: ContentKind,
+    pub id: Uuid,
+    /// 引用所在的帖子：帖子为自身，评论为所属的帖子
+    pub post_id: Uuid,
+    /// 引用所在评论的楼层，帖子为 null
+    pub floor: Option<i32>,
+    /// 被引用的内容：帖子本身或它的某条评论
+    pub target_kind: ContentKind,
+    pub target_id: Uuid,
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::utils::filter::rescan::RescanPolicy;
+
+// 重新扫描任务
+#[derive(Debug, Serialize)]
+pub struct RescanJob {
+    pub id: Uuid,
+    pub status: String,
+    pub policy: String,
+    pub source: String,
+    pub started_by: String,
+    pub phase: String,
+    pub total: i64,
+    pub scanned: i64,
+    pub matched: i64,
+    pub flagged: i64,
+    pub masked: i64,
+    pub hidden: i64,
+    /// 已扫描的比例，0 到 1
+    pub progress: f64,
+    pub error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+}
+
+// 重新扫描命中的内容
+#[derive(Debug, Serialize)]
+pub struct RescanMatch {
+    pub id: i64,
+    pub content_type: String,
+    pub content_id: Uuid,
+    pub action: String,
+    pub outcome: String,
+    pub categories: String,
+    pub words: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+// 启动重新扫描的请求结构
+#[derive(Debug, Default, Deserialize)]
+pub struct StartRescanRequest {
+    pub policy: Option<RescanPolicy>,
+}
+
+// 重新扫描任务列表响应结构
+#[derive(Debug, Serialize)]
+pub struct RescanJobListResponse {
+    pub jobs: Vec<RescanJob>,
+}
+
+// 重新扫描命中列表响应结构
+#[derive(Debug, Serialize)]
+pub struct RescanMatchListResponse {
+    pub matches: Vec<RescanMatch>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::utils::filter::FilterAction;
+
+// 数据库中的敏感词
+#[derive(Debug, Serialize)]
+pub struct SensitiveWord {
+    pub id: Uuid,
+    pub word: String,
+    pub category: String,
+    pub action: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub created_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    pub updated_by: String,
+}
+
+// 敏感词修改记录
+#[derive(Debug, Serialize)]
+pub struct SensitiveWordChange {
+    pub id: i64,
+    pub word: String,
+    pub operation: String,
+    pub category: Option<String>,
+    pub action: Option<String>,
+    pub changed_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub changed_at: OffsetDateTime,
+}
+
+// 添加敏感词的请求结构
+#[derive(Debug, Deserialize)]
+pub struct CreateSensitiveWordRequest {
+    #[serde(deserialize_with = "deserialize_and_validate_word")]
+    pub word: String,
+    pub category: Option<String>,
+    pub action: Option<FilterAction>,
+}
+
+// 数据库中的白名单短语
+#[derive(Debug, Serialize)]
+pub struct AllowlistPhrase {
+    pub id: Uuid,
+    pub phrase: String,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub created_by: String,
+}
+
+// 添加白名单短语的请求结构
+#[derive(Debug, Deserialize)]
+pub struct CreateAllowlistPhraseRequest {
+    #[serde(deserialize_with = "deserialize_and_validate_word")]
+    pub phrase: String,
+    pub note: Option<String>,
+}
+
+// 白名单列表响应结构
+#[derive(Debug, Serialize)]
+pub struct AllowlistResponse {
+    pub phrases: Vec<AllowlistPhrase>,
+}
+
+// 验证敏感词的自定义反序列化函数
+fn deserialize_and_validate_word<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let word = String::deserialize(deserializer)?;
+    let word = word.trim();
+
+    if word.is_empty() {
+        return Err(serde::de::Error::custom("Word cannot be empty"));
+    }
+
+    if word.contains('\n') {
+        return Err(serde::de::Error::custom("Word cannot contain line breaks"));
+    }
+
+    if word.chars().count() > 100 {
+        return Err(serde::de::Error::custom("Word is too long (max 100 characters)"));
+    }
+
+    Ok(word.to_string())
+}
+
+// 敏感词列表查询参数
+#[derive(Debug, Deserialize)]
+pub struct SensitiveWordListParams {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+    pub category: Option<String>,
+    pub q: Option<String>,
+}
+
+// 敏感词列表响应结构
+#[derive(Debug, Serialize)]
+pub struct SensitiveWordListResponse {
+    pub words: Vec<SensitiveWord>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+// 敏感词修改记录列表响应结构
+#[derive(Debug, Serialize)]
+pub struct SensitiveWordChangeListResponse {
+    pub changes: Vec<SensitiveWordChange>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+// 批量导入结果
+#[derive(Debug, Serialize)]
+pub struct ImportSensitiveWordsResponse {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub count: usize,
+}
@@ -0,0 +1,47 @@
+use serde::Serialize;
+
+use crate::utils::moderation::{Decision, ModerationOutcome};
+
+/// 帖子和评论的发布状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentStatus {
+    /// 已公开
+    #[default]
+    Published,
+    /// 审核流水线要求人工审核，等待审核
+    Held,
+    /// 审核未通过
+    Rejected,
+}
+
+impl ContentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentStatus::Published => "published",
+            ContentStatus::Held => "held",
+            ContentStatus::Rejected => "rejected",
+        }
+    }
+
+    /// 从数据库中的字符串解析，无法识别的值按已公开处理
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "held" => ContentStatus::Held,
+            "rejected" => ContentStatus::Rejected,
+            _ => ContentStatus::Published,
+        }
+    }
+
+    /// 根据审核结果决定新内容的发布状态
+    ///
+    /// # Returns
+    /// * 发布状态；结论为 reject 时返回说明原因的错误信息
+    pub fn from_outcome(outcome: &ModerationOutcome) -> Result<Self, String> {
+        match outcome.decision {
+            Decision::Reject => Err(format!("Content rejected: {}", outcome.summary())),
+            Decision::Hold => Ok(ContentStatus::Held),
+            Decision::Mask | Decision::Allow => Ok(ContentStatus::Published),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+// 话题列表中的一个话题
+#[derive(Debug, Serialize)]
+pub struct Tag {
+    /// 规范化后的名称，不带 #
+    pub name: String,
+    /// 带有这个话题的已公开帖子数量
+    pub post_count: i64,
+    /// 最近 7 天内带有这个话题的已公开帖子数量
+    pub recent_count: i64,
+}
+
+/// 话题列表的排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagSort {
+    /// 热门：按最近 7 天的帖子数量排序，只列出最近用过的话题
+    #[default]
+    Trending,
+    /// 全部：按帖子总数排序
+    All,
+}
+
+/// 话题列表的查询参数，例如 `?sort=all`
+#[derive(Debug, Default, Deserialize)]
+pub struct TagListParams {
+    #[serde(default)]
+    pub sort: TagSort,
+}
+
+// 话题列表响应结构
+#[derive(Debug, Serialize)]
+pub struct TagListResponse {
+    pub tags: Vec<Tag>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
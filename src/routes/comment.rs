@@ -4,29 +4,36 @@ use axum::{
     Json,
 };
 use sqlx::{PgPool, Row};
+use std::sync::Arc;
 use uuid::Uuid;
 use time;
 
 use crate::models::comment::{Comment, CommentListResponse, CreateCommentRequest};
-use crate::utils::filter::filter_sensitive_words;
+use crate::models::format::{ContentFormat, ContentFieldsParams};
+use crate::models::kind::ContentKind;
+use crate::models::status::ContentStatus;
+use crate::utils::links::preview::{previews_for, record_links, PreviewFetcher};
+use crate::utils::moderation::ModerationPipeline;
 use crate::utils::pagination::PaginationParams;
-use crate::utils::sanitize::sanitize_content;
+use crate::utils::references::{record_references, unlink_unpublished};
+use crate::utils::render::{render_content, render_options};
 
 // 获取帖子下的评论列表
 pub async fn get_comments(
     Extension(pool): Extension<PgPool>,
     Path(post_id): Path<Uuid>,
     Query(pagination): Query<PaginationParams>,
+    Query(params): Query<ContentFieldsParams>,
 ) -> Result<Json<CommentListResponse>, (StatusCode, String)> {
     let page = pagination.page.unwrap_or(1);
     let page_size = pagination.per_page.unwrap_or(20);
     let offset = (page - 1) * page_size;
 
     // 检查帖子是否存在
-    let post_exists = sqlx::query_scalar!(
-        "SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1)",
-        post_id
+    let post_exists = sqlx::query_scalar::<_, Option<bool>>(
+        "SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND status = 'published')",
     )
+    .bind(post_id)
     .fetch_one(&pool)
     .await
     .map_err(|e| {
@@ -43,7 +50,9 @@ pub async fn get_comments(
     }
 
     // 获取评论总数
-    let total = match sqlx::query("SELECT COUNT(*) FROM comments WHERE post_id = $1")
+    let total = match sqlx::query(
+        "SELECT COUNT(*) FROM comments WHERE post_id = $1 AND status = 'published'",
+    )
         .bind(post_id)
         .fetch_one(&pool)
         .await
@@ -72,19 +81,22 @@ pub async fn get_comments(
         SELECT 
             id, 
             post_id, 
+            floor,
             content, 
+            content_text,
             created_at, 
             ip_address, 
-            user_agent
+            user_agent,
+            content_format
         FROM comments
-        WHERE post_id = $1
+        WHERE post_id = $1 AND status = 'published'
         ORDER BY created_at DESC
         LIMIT $2 OFFSET $3
         "#
     )
     .bind(post_id)
-    .bind(page_size as i64)
-    .bind(offset as i64)
+    .bind(page_size)
+    .bind(offset)
     .fetch_all(&pool)
     .await
     {
@@ -111,38 +123,58 @@ pub async fn get_comments(
             Err(_) => continue,
         };
 
-        let content: String = match row.try_get("content") {
+        let floor: i32 = match row.try_get("floor") {
             Ok(val) => val,
             Err(_) => continue,
         };
 
-        let created_at: time::OffsetDateTime = match row.try_get("created_at") {
+        let content: String = match row.try_get("content") {
             Ok(val) => val,
             Err(_) => continue,
         };
 
-        let ip_address: Option<String> = match row.try_get("ip_address") {
-            Ok(val) => val,
-            Err(_) => None,
-        };
+        let content_text: Option<String> = row.try_get("content_text").unwrap_or_default();
 
-        let user_agent: Option<String> = match row.try_get("user_agent") {
+        let created_at: time::OffsetDateTime = match row.try_get("created_at") {
             Ok(val) => val,
-            Err(_) => None,
+            Err(_) => continue,
         };
 
+        let ip_address: Option<String> = row.try_get("ip_address").unwrap_or_default();
+
+        let user_agent: Option<String> = row.try_get("user_agent").unwrap_or_default();
+
+        let format = row
+            .try_get::<String, _>("content_format")
+            .map(|value| ContentFormat::from_db(&value))
+            .unwrap_or_default();
+
         // 确保输出到前端的内容也经过了安全处理
         let comment = Comment {
             id,
             post_id,
+            floor,
             content,
+            content_text,
             created_at,
             ip_address,
             user_agent,
+            status: ContentStatus::Published,
+            format,
+            fields: params.content,
+            previews: Vec::new(),
         };
         comments.push(comment);
     }
 
+    // 附上已经抓取到的链接预览，去掉指向未公开内容的引用链接
+    unlink_unpublished(&pool, comments.iter_mut().map(|comment| &mut comment.content)).await;
+    let ids: Vec<Uuid> = comments.iter().map(|comment| comment.id).collect();
+    let mut previews = previews_for(&pool, ContentKind::Comment, &ids).await;
+    for comment in &mut comments {
+        comment.previews = previews.remove(&comment.id).unwrap_or_default();
+    }
+
     Ok(Json(CommentListResponse {
         comments,
         total,
@@ -154,15 +186,18 @@ pub async fn get_comments(
 // 创建新评论
 pub async fn create_comment(
     Extension(pool): Extension<PgPool>,
+    Extension(moderation): Extension<Arc<ModerationPipeline>>,
+    Extension(previews): Extension<Arc<PreviewFetcher>>,
     Path(post_id): Path<Uuid>,
+    Query(params): Query<ContentFieldsParams>,
     headers: HeaderMap,
     Json(request): Json<CreateCommentRequest>,
-) -> Result<Json<Comment>, (StatusCode, String)> {
+) -> Result<(StatusCode, Json<Comment>), (StatusCode, String)> {
     // 检查帖子是否存在
-    let post_exists = sqlx::query_scalar!(
-        "SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1)",
-        post_id
+    let post_exists = sqlx::query_scalar::<_, Option<bool>>(
+        "SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND status = 'published')",
     )
+    .bind(post_id)
     .fetch_one(&pool)
     .await
     .map_err(|e| {
@@ -191,19 +226,39 @@ pub async fn create_comment(
         .map(ToString::to_string);
 
     // 内容验证已在 model 的反序列化时完成
+    let format = render_options()
+        .resolve_format(request.format)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
 
     // 处理内容
-    let filtered_content = filter_sensitive_words(&request.content);
-    let sanitized_content = sanitize_content(&filtered_content);
+    let rendered = render_content(
+        &pool,
+        &moderation,
+        &request.content,
+        format,
+        ContentKind::Comment,
+        Some(post_id),
+    )
+    .await;
+    let status = ContentStatus::from_outcome(&rendered.outcome)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
 
-    // 创建新评论 - 手动处理查询结果
+    // 创建新评论 - 手动处理查询结果；楼层由帖子的 floor_count 分配，更新时锁住帖子行，不会重复
     let row = match sqlx::query(
         r#"
-        INSERT INTO comments (post_id, content, ip_address, user_agent)
-        VALUES ($1, $2, $3, $4)
+        WITH next AS (
+            UPDATE posts SET floor_count = floor_count + 1 WHERE id = $1 RETURNING floor_count
+        )
+        INSERT INTO comments (
+            post_id, content, ip_address, user_agent, status,
+            raw_content, filter_version, sanitizer_policy, sanitizer_version, content_format,
+            content_text, floor
+        )
+        SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, next.floor_count FROM next
         RETURNING 
             id, 
             post_id, 
+            floor,
             content, 
             created_at, 
             ip_address, 
@@ -211,9 +266,16 @@ pub async fn create_comment(
         "#
     )
     .bind(post_id)
-    .bind(sanitized_content)
+    .bind(&rendered.html)
     .bind(ip_address)
     .bind(user_agent)
+    .bind(status.as_str())
+    .bind(&request.content)
+    .bind(&rendered.filter_version)
+    .bind(rendered.sanitizer_policy)
+    .bind(rendered.sanitizer_version)
+    .bind(format.as_str())
+    .bind(&rendered.text)
     .fetch_one(&pool)
     .await
     {
@@ -250,6 +312,17 @@ pub async fn create_comment(
         }
     };
 
+    let floor: i32 = match row.try_get("floor") {
+        Ok(val) => val,
+        Err(e) => {
+            tracing::error!("解析新评论楼层失败: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to parse new comment: {}", e),
+            ));
+        }
+    };
+
     let content: String = match row.try_get("content") {
         Ok(val) => val,
         Err(e) => {
@@ -272,25 +345,35 @@ pub async fn create_comment(
         }
     };
 
-    let ip_address: Option<String> = match row.try_get("ip_address") {
-        Ok(val) => val,
-        Err(_) => None,
-    };
+    let ip_address: Option<String> = row.try_get("ip_address").unwrap_or_default();
 
-    let user_agent: Option<String> = match row.try_get("user_agent") {
-        Ok(val) => val,
-        Err(_) => None,
-    };
+    let user_agent: Option<String> = row.try_get("user_agent").unwrap_or_default();
 
-    let comment = Comment {
+    let mut comment = Comment {
         id,
         post_id: comment_post_id,
+        floor,
         content,  // 内容已在插入数据库前被净化
+        content_text: Some(rendered.text),
         created_at,
         ip_address,
         user_agent,
+        status,
+        format,
+        fields: params.content,
+        previews: Vec::new(),
     };
 
-    Ok(Json(comment))
+    // 记录引用，在后台抓取内容中链接的预览
+    record_references(&pool, ContentKind::Comment, comment.id, &rendered.references).await;
+    record_links(&pool, &previews, ContentKind::Comment, comment.id, &comment.content).await;
+    unlink_unpublished(&pool, [&mut comment.content]).await;
+
+    // 待审核的评论返回 202，表示已收到但尚未公开
+    let status_code = match status {
+        ContentStatus::Held => StatusCode::ACCEPTED,
+        _ => StatusCode::OK,
+    };
+    Ok((status_code, Json(comment)))
 }
 
@@ -1,12 +1,59 @@
-use crate::utils::filter::{filter_sensitive_words, reload_sensitive_words};
-use axum::{http::StatusCode, routing::post, Json, Router};
+use crate::routes::{filter_allowlist, filter_rescan, filter_stats, filter_words};
+use crate::utils::api_key::{require_scope, Scope};
+use crate::utils::filter::{
+    current_filter, detect_language, parse_allowlist, parse_word_list,
+    preview_filter, reload_sensitive_words, ContentLanguage, FilterAction, MatchVariant,
+    SensitiveWordFilter, WordList,
+};
+use axum::{
+    http::StatusCode,
+    middleware::from_fn_with_state,
+    routing::{get, post},
+    Json, Router,
+};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::Arc;
 
 /// 创建敏感词过滤相关路由
+///
+/// 所有接口都需要 API 密钥，每组接口要求各自的权限范围
 pub fn filter_routes() -> Router {
     Router::new()
-        .route("/reload", post(reload_filter))
-        .route("/test", post(test_filter))
+        .route(
+            "/reload",
+            post(reload_filter)
+                .route_layer(from_fn_with_state(Scope::FilterReload, require_scope)),
+        )
+        .route(
+            "/test",
+            post(test_filter).route_layer(from_fn_with_state(Scope::FilterTest, require_scope)),
+        )
+        .route(
+            "/test/batch",
+            post(test_filter_batch)
+                .route_layer(from_fn_with_state(Scope::FilterTest, require_scope)),
+        )
+        .route(
+            "/stats",
+            get(filter_stats::get_filter_stats)
+                .route_layer(from_fn_with_state(Scope::FilterStats, require_scope)),
+        )
+        .nest(
+            "/words",
+            filter_words::filter_word_routes()
+                .route_layer(from_fn_with_state(Scope::FilterWords, require_scope)),
+        )
+        .nest(
+            "/allowlist",
+            filter_allowlist::filter_allowlist_routes()
+                .route_layer(from_fn_with_state(Scope::FilterAllowlist, require_scope)),
+        )
+        .nest(
+            "/rescan",
+            filter_rescan::filter_rescan_routes()
+                .route_layer(from_fn_with_state(Scope::FilterRescan, require_scope)),
+        )
 }
 
 /// 重新加载敏感词列表的响应
@@ -19,13 +66,13 @@ pub struct ReloadResponse {
 
 /// 重新加载敏感词列表
 ///
-/// 管理员可以通过这个接口在更新 filter.txt 文件后重新加载敏感词列表，
-/// 无需重启服务器
+/// 服务会监听词表文件并在变化后自动重新加载，这个接口用于关闭了监听或需要立即确认结果的情况。
+/// 数据库中的词通过 /words 接口修改后会自动生效，不需要调用这个接口
 async fn reload_filter() -> Result<Json<ReloadResponse>, (StatusCode, String)> {
     match reload_sensitive_words() {
         Ok(count) => Ok(Json(ReloadResponse {
             success: true,
-            message: format!("成功重新加载敏感词列表"),
+            message: "成功重新加载敏感词列表".to_string(),
             count: Some(count),
         })),
         Err(e) => Err((
@@ -35,28 +82,207 @@ async fn reload_filter() -> Result<Json<ReloadResponse>, (StatusCode, String)> {
     }
 }
 
+/// 批量测试一次最多提交的文本数量
+const MAX_BATCH_SIZE: usize = 100;
+
+/// 候选词表：用于在重新加载前预览新词表的效果
+#[derive(Deserialize)]
+pub struct CandidateLists {
+    /// 词表文件格式的词表，代替 `list` 指定的词表文件参与测试
+    word_list: Option<String>,
+    /// 候选词表代替的词表文件：common（默认）、zh 或 en
+    list: Option<WordList>,
+    /// allowlist.txt 格式的白名单，代替当前的白名单文件参与测试
+    allowlist: Option<String>,
+}
+
 /// 敏感词测试请求
 #[derive(Deserialize)]
 pub struct TestFilterRequest {
     content: String,
+    #[serde(flatten)]
+    candidate: CandidateLists,
+}
+
+/// 批量敏感词测试请求
+#[derive(Deserialize)]
+pub struct BatchTestFilterRequest {
+    contents: Vec<String>,
+    #[serde(flatten)]
+    candidate: CandidateLists,
 }
 
 /// 敏感词测试响应
 #[derive(Serialize)]
 pub struct TestFilterResponse {
     original: String,
+    /// 检测出的内容语言，决定哪些词表生效
+    language: ContentLanguage,
+    /// 按处理方式过滤后实际会保存的内容
     filtered: String,
+    /// 所有命中中最严重的处理方式，没有命中时为 null
+    action: Option<FilterAction>,
+    /// 触发该处理方式的分类
+    categories: Vec<String>,
+    matches: Vec<TestFilterMatch>,
+    /// 被白名单挡下、没有生效的命中
+    suppressed: Vec<TestFilterSuppressed>,
+}
+
+/// 批量敏感词测试响应
+#[derive(Serialize)]
+pub struct BatchTestFilterResponse {
+    /// 与请求中的文本一一对应的结果
+    results: Vec<TestFilterResponse>,
+    /// 有命中的文本数量
+    matched: usize,
+}
+
+/// 单次命中的详情
+#[derive(Serialize)]
+pub struct TestFilterMatch {
+    /// 词表中的原词、正则表达式或通配符
+    word: String,
+    /// 词所属的分类
+    category: String,
+    /// 词的处理方式：mask、hold 或 reject
+    action: FilterAction,
+    /// 命中的写法：literal、homophone、pinyin、initials 或 pattern（正则表达式和通配符）
+    variant: MatchVariant,
+    /// 原文中被命中的文字
+    text: String,
+    /// 在原文中的位置
+    #[serde(flatten)]
+    offsets: TestFilterOffsets,
+}
+
+/// 被白名单挡下的命中
+#[derive(Serialize)]
+pub struct TestFilterSuppressed {
+    /// 词表中的原词、正则表达式或通配符
+    word: String,
+    /// 词所属的分类
+    category: String,
+    /// 原文中被命中的文字
+    text: String,
+    /// 覆盖了这次命中的白名单短语
+    allowed_by: String,
+    /// 在原文中的位置
+    #[serde(flatten)]
+    offsets: TestFilterOffsets,
+}
+
+/// 命中在原文中的位置，区间左闭右开
+#[derive(Serialize)]
+pub struct TestFilterOffsets {
+    /// 字节偏移
+    start: usize,
+    end: usize,
+    /// 字符（Unicode 标量值）偏移
+    char_start: usize,
+    char_end: usize,
+}
+
+impl TestFilterOffsets {
+    fn new(content: &str, span: &Range<usize>) -> Self {
+        let char_start = content[..span.start].chars().count();
+        Self {
+            start: span.start,
+            end: span.end,
+            char_start,
+            char_end: char_start + content[span.clone()].chars().count(),
+        }
+    }
+}
+
+impl CandidateLists {
+    /// 选择测试使用的过滤器：没有提交候选词表时使用当前生效的过滤器
+    fn filter(&self) -> Result<Arc<SensitiveWordFilter>, (StatusCode, String)> {
+        if self.word_list.is_none() && self.allowlist.is_none() {
+            return Ok(current_filter());
+        }
+        let words = self
+            .word_list
+            .as_deref()
+            .map(parse_word_list)
+            .transpose()
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("词表格式错误: {}", e)))?
+            .map(|words| (self.list.unwrap_or(WordList::Common), words));
+        let allowlist = self.allowlist.as_deref().map(parse_allowlist);
+        Ok(Arc::new(preview_filter(words, allowlist)))
+    }
+}
+
+/// 用指定的过滤器测试一段内容
+fn test_content(filter: &SensitiveWordFilter, content: String) -> TestFilterResponse {
+    let verdict = filter.check(&content);
+    let matches = filter
+        .find_matches(&content)
+        .into_iter()
+        .map(|m| TestFilterMatch {
+            word: m.word.to_string(),
+            category: m.category.to_string(),
+            action: m.action,
+            variant: m.variant,
+            text: content[m.span.clone()].to_string(),
+            offsets: TestFilterOffsets::new(&content, &m.span),
+        })
+        .collect();
+    let suppressed = filter
+        .find_suppressed_matches(&content)
+        .into_iter()
+        .map(|s| TestFilterSuppressed {
+            word: s.word_match.word.to_string(),
+            category: s.word_match.category.to_string(),
+            text: content[s.word_match.span.clone()].to_string(),
+            allowed_by: s.allowed_by.to_string(),
+            offsets: TestFilterOffsets::new(&content, &s.word_match.span),
+        })
+        .collect();
+
+    TestFilterResponse {
+        language: detect_language(&content),
+        original: content,
+        filtered: verdict.content,
+        action: verdict.action,
+        categories: verdict.categories,
+        matches,
+        suppressed,
+    }
 }
 
 /// 测试敏感词过滤
 ///
-/// 可以通过这个接口测试特定内容的敏感词过滤效果
-async fn test_filter(Json(request): Json<TestFilterRequest>) -> Json<TestFilterResponse> {
-    let filtered = filter_sensitive_words(&request.content);
-
-    Json(TestFilterResponse {
-        original: request.content,
-        filtered,
-    })
+/// 可以通过这个接口测试特定内容的敏感词过滤效果，
+/// 返回的每个命中都注明了触发它的词、写法和位置，被白名单挡下的命中单独列出，便于排查误判。
+/// 请求中带有 `word_list` 或 `allowlist` 时用它们代替对应的文件进行测试，不影响当前生效的词表
+async fn test_filter(
+    Json(request): Json<TestFilterRequest>,
+) -> Result<Json<TestFilterResponse>, (StatusCode, String)> {
+    let filter = request.candidate.filter()?;
+    Ok(Json(test_content(&filter, request.content)))
 }
 
+/// 批量测试敏感词过滤
+///
+/// 用于在重新加载前用保存的样本内容回归测试新的词表
+async fn test_filter_batch(
+    Json(request): Json<BatchTestFilterRequest>,
+) -> Result<Json<BatchTestFilterResponse>, (StatusCode, String)> {
+    if request.contents.len() > MAX_BATCH_SIZE {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("一次最多测试 {} 条内容", MAX_BATCH_SIZE),
+        ));
+    }
+
+    let filter = request.candidate.filter()?;
+    let results: Vec<TestFilterResponse> = request
+        .contents
+        .into_iter()
+        .map(|content| test_content(&filter, content))
+        .collect();
+    let matched = results.iter().filter(|r| !r.matches.is_empty()).count();
+
+    Ok(Json(BatchTestFilterResponse { results, matched }))
+}
@@ -0,0 +1,141 @@
+use axum::{
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+    Json, Router,
+};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use uuid::Uuid;
+
+use crate::models::sensitive_word::{
+    AllowlistPhrase, AllowlistResponse, CreateAllowlistPhraseRequest,
+};
+use crate::schema::response::MessageResponse;
+use crate::utils::actor::actor_from_headers;
+use crate::utils::filter::store::reload_database_words;
+
+/// 创建数据库白名单管理路由
+pub fn filter_allowlist_routes() -> Router {
+    Router::new()
+        .route("/", get(list_phrases).post(add_phrase))
+        .route("/:id", delete(remove_phrase))
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+fn phrase_from_row(row: &PgRow) -> Result<AllowlistPhrase, sqlx::Error> {
+    Ok(AllowlistPhrase {
+        id: row.try_get("id")?,
+        phrase: row.try_get("phrase")?,
+        note: row.try_get("note")?,
+        created_at: row.try_get("created_at")?,
+        created_by: row.try_get("created_by")?,
+    })
+}
+
+/// 修改数据库后重新编译过滤器，使改动立即生效
+async fn apply_changes(pool: &PgPool) -> Result<(), (StatusCode, String)> {
+    reload_database_words(pool)
+        .await
+        .map_err(|e| database_error("Failed to reload allowlist", e))?;
+    tracing::info!("敏感词白名单已更新");
+    Ok(())
+}
+
+/// 获取数据库中的所有白名单短语
+async fn list_phrases(
+    Extension(pool): Extension<PgPool>,
+) -> Result<Json<AllowlistResponse>, (StatusCode, String)> {
+    let rows = sqlx::query("SELECT * FROM filter_allowlist ORDER BY phrase")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch allowlist", e))?;
+
+    let phrases = rows
+        .iter()
+        .map(phrase_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse allowlist phrase", e))?;
+
+    Ok(Json(AllowlistResponse { phrases }))
+}
+
+/// 添加一个白名单短语；短语已存在时更新它的备注
+async fn add_phrase(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap,
+    Json(request): Json<CreateAllowlistPhraseRequest>,
+) -> Result<(StatusCode, Json<AllowlistPhrase>), (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+    let note = request
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    // xmax = 0 表示这一行是新插入的而不是更新的
+    let row = sqlx::query(
+        r#"
+        INSERT INTO filter_allowlist (phrase, note, created_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (phrase) DO UPDATE SET note = COALESCE(EXCLUDED.note, filter_allowlist.note)
+        RETURNING *, (xmax = 0) AS inserted
+        "#,
+    )
+    .bind(&request.phrase)
+    .bind(&note)
+    .bind(&actor)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| database_error("Failed to add allowlist phrase", e))?;
+
+    let inserted: bool = row
+        .try_get("inserted")
+        .map_err(|e| database_error("Failed to add allowlist phrase", e))?;
+    let phrase =
+        phrase_from_row(&row).map_err(|e| database_error("Failed to parse allowlist phrase", e))?;
+
+    apply_changes(&pool).await?;
+    tracing::info!("{} 添加白名单短语 {}", actor, phrase.phrase);
+
+    let status = if inserted {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(phrase)))
+}
+
+/// 删除一个白名单短语
+async fn remove_phrase(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MessageResponse>, (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+
+    let phrase: Option<String> =
+        sqlx::query_scalar("DELETE FROM filter_allowlist WHERE id = $1 RETURNING phrase")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| database_error("Failed to remove allowlist phrase", e))?;
+    let phrase = match phrase {
+        Some(phrase) => phrase,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                "Allowlist phrase not found".to_string(),
+            ))
+        }
+    };
+
+    apply_changes(&pool).await?;
+    tracing::info!("{} 删除白名单短语 {}", actor, phrase);
+
+    Ok(Json(MessageResponse {
+        message: format!("Removed allowlist phrase {}", phrase),
+    }))
+}
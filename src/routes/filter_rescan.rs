@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::rescan::{
+    RescanJob, RescanJobListResponse, RescanMatch, RescanMatchListResponse, StartRescanRequest,
+};
+use crate::utils::actor::actor_from_headers;
+use crate::utils::filter::rescan::{start_rescan, RescanPolicy, RescanSource, StartRescanError};
+use crate::utils::moderation::ModerationPipeline;
+use crate::utils::pagination::PaginationParams;
+
+/// 创建重新扫描相关路由
+pub fn filter_rescan_routes() -> Router {
+    Router::new()
+        .route("/", get(list_jobs).post(start_job))
+        .route("/:id", get(get_job))
+        .route("/:id/matches", get(list_matches))
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+fn job_from_row(row: &PgRow) -> Result<RescanJob, sqlx::Error> {
+    let total: i64 = row.try_get("total")?;
+    let scanned: i64 = row.try_get("scanned")?;
+    let status: String = row.try_get("status")?;
+    let progress = if status == "completed" {
+        1.0
+    } else if total > 0 {
+        (scanned as f64 / total as f64).min(1.0)
+    } else {
+        0.0
+    };
+
+    Ok(RescanJob {
+        id: row.try_get("id")?,
+        status,
+        policy: row.try_get("policy")?,
+        source: row.try_get("source")?,
+        started_by: row.try_get("started_by")?,
+        phase: row.try_get("phase")?,
+        total,
+        scanned,
+        matched: row.try_get("matched")?,
+        flagged: row.try_get("flagged")?,
+        masked: row.try_get("masked")?,
+        hidden: row.try_get("hidden")?,
+        progress,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        finished_at: row.try_get("finished_at")?,
+    })
+}
+
+fn match_from_row(row: &PgRow) -> Result<RescanMatch, sqlx::Error> {
+    Ok(RescanMatch {
+        id: row.try_get("id")?,
+        content_type: row.try_get("content_type")?,
+        content_id: row.try_get("content_id")?,
+        action: row.try_get("action")?,
+        outcome: row.try_get("outcome")?,
+        categories: row.try_get("categories")?,
+        words: row.try_get("words")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+async fn fetch_job(pool: &PgPool, id: Uuid) -> Result<RescanJob, (StatusCode, String)> {
+    let row = sqlx::query("SELECT * FROM rescan_jobs WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch rescan job", e))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Rescan job not found".to_string()))?;
+    job_from_row(&row).map_err(|e| database_error("Failed to parse rescan job", e))
+}
+
+/// 启动重新扫描
+///
+/// 用当前生效的词表检查已发布的帖子和评论，请求体可以指定策略（flag 或 apply），
+/// 默认使用 `FILTER_RESCAN_POLICY`。已有任务在运行时返回 409
+async fn start_job(
+    Extension(pool): Extension<PgPool>,
+    Extension(moderation): Extension<Arc<ModerationPipeline>>,
+    headers: HeaderMap,
+    request: Option<Json<StartRescanRequest>>,
+) -> Result<(StatusCode, Json<RescanJob>), (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let policy = request.policy.unwrap_or_else(RescanPolicy::from_env);
+
+    let id = start_rescan(&pool, &moderation, policy, RescanSource::Manual, &actor, false)
+        .await
+        .map_err(|e| match e {
+            StartRescanError::AlreadyRunning(_) => (StatusCode::CONFLICT, e.to_string()),
+            StartRescanError::Database(e) => database_error("Failed to start rescan", e),
+        })?;
+
+    Ok((StatusCode::ACCEPTED, Json(fetch_job(&pool, id).await?)))
+}
+
+/// 获取最近的重新扫描任务
+async fn list_jobs(
+    Extension(pool): Extension<PgPool>,
+) -> Result<Json<RescanJobListResponse>, (StatusCode, String)> {
+    let rows = sqlx::query("SELECT * FROM rescan_jobs ORDER BY created_at DESC LIMIT 20")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch rescan jobs", e))?;
+
+    let jobs = rows
+        .iter()
+        .map(job_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse rescan job", e))?;
+
+    Ok(Json(RescanJobListResponse { jobs }))
+}
+
+/// 获取一个重新扫描任务的进度
+async fn get_job(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RescanJob>, (StatusCode, String)> {
+    Ok(Json(fetch_job(&pool, id).await?))
+}
+
+/// 分页获取一个重新扫描任务命中的内容
+async fn list_matches(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<RescanMatchListResponse>, (StatusCode, String)> {
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.per_page.unwrap_or(20);
+    let offset = (page - 1) * page_size;
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM rescan_matches WHERE job_id = $1")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| database_error("Failed to count rescan matches", e))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM rescan_matches
+        WHERE job_id = $1
+        ORDER BY id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(id)
+    .bind(page_size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch rescan matches", e))?;
+
+    let matches = rows
+        .iter()
+        .map(match_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse rescan match", e))?;
+
+    Ok(Json(RescanMatchListResponse {
+        matches,
+        total,
+        page,
+        page_size,
+    }))
+}
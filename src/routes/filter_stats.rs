@@ -0,0 +1,186 @@
+use axum::{
+    extract::{Extension, Query},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashSet;
+use time::Date;
+
+use crate::utils::filter::current_filter;
+
+/// 统计的默认天数
+const DEFAULT_DAYS: i64 = 30;
+/// 统计的最大天数
+const MAX_DAYS: i64 = 365;
+/// 热门词的默认数量
+const DEFAULT_LIMIT: i64 = 20;
+
+/// 命中统计查询参数
+#[derive(Debug, Deserialize)]
+pub struct FilterStatsParams {
+    /// 统计最近多少天（含今天），默认 30
+    pub days: Option<i64>,
+    /// 返回多少个热门词，默认 20
+    pub limit: Option<i64>,
+}
+
+/// 命中统计
+#[derive(Serialize)]
+pub struct FilterStatsResponse {
+    days: i64,
+    /// 命中总次数
+    total_hits: i64,
+    /// 命中次数最多的词
+    top_words: Vec<WordHits>,
+    /// 各分类的命中次数
+    categories: Vec<CategoryHits>,
+    /// 每天的命中次数，没有命中的日期为 0
+    daily: Vec<DailyHits>,
+    /// 当前词表中统计期间没有命中过的词
+    zero_hit_words: Vec<ZeroHitWord>,
+}
+
+#[derive(Serialize)]
+pub struct WordHits {
+    word: String,
+    category: String,
+    hits: i64,
+}
+
+#[derive(Serialize)]
+pub struct CategoryHits {
+    category: String,
+    hits: i64,
+}
+
+#[derive(Serialize)]
+pub struct DailyHits {
+    day: String,
+    hits: i64,
+}
+
+#[derive(Serialize)]
+pub struct ZeroHitWord {
+    word: String,
+    category: String,
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+/// 获取敏感词命中统计
+///
+/// 统计按 UTC 日期汇总，与记录命中时的日期一致，不受数据库时区影响；
+/// 最近的命中可能要等后台任务写入数据库后才会出现
+pub async fn get_filter_stats(
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<FilterStatsParams>,
+) -> Result<Json<FilterStatsResponse>, (StatusCode, String)> {
+    let days = params.days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, 100);
+    let today = "(now() AT TIME ZONE 'UTC')::DATE";
+    let since = format!("{today} - ($1::INT - 1)");
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT word, category, SUM(hits)::BIGINT AS hits
+        FROM filter_hit_stats
+        WHERE day >= {since}
+        GROUP BY word, category
+        ORDER BY hits DESC, word
+        "#
+    ))
+    .bind(days as i32)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch word hits", e))?;
+
+    let mut word_hits = rows
+        .iter()
+        .map(|row| {
+            Ok(WordHits {
+                word: row.try_get("word")?,
+                category: row.try_get("category")?,
+                hits: row.try_get("hits")?,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+        .map_err(|e| database_error("Failed to parse word hits", e))?;
+
+    let total_hits = word_hits.iter().map(|w| w.hits).sum();
+    let hit_words: HashSet<&str> = word_hits.iter().map(|w| w.word.as_str()).collect();
+    let zero_hit_words = current_filter()
+        .entries()
+        .iter()
+        .filter(|entry| !hit_words.contains(entry.word.as_str()))
+        .map(|entry| ZeroHitWord {
+            word: entry.word.clone(),
+            category: entry.category.clone(),
+        })
+        .collect();
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT category, SUM(hits)::BIGINT AS hits
+        FROM filter_hit_stats
+        WHERE day >= {since}
+        GROUP BY category
+        ORDER BY hits DESC, category
+        "#
+    ))
+    .bind(days as i32)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch category hits", e))?;
+
+    let categories = rows
+        .iter()
+        .map(|row| {
+            Ok(CategoryHits {
+                category: row.try_get("category")?,
+                hits: row.try_get("hits")?,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+        .map_err(|e| database_error("Failed to parse category hits", e))?;
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT d.day::DATE AS day, COALESCE(SUM(s.hits), 0)::BIGINT AS hits
+        FROM generate_series({since}, {today}, INTERVAL '1 day') AS d(day)
+        LEFT JOIN filter_hit_stats s ON s.day = d.day::DATE
+        GROUP BY d.day
+        ORDER BY d.day
+        "#
+    ))
+    .bind(days as i32)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch daily hits", e))?;
+
+    let daily = rows
+        .iter()
+        .map(|row| {
+            Ok(DailyHits {
+                day: row.try_get::<Date, _>("day")?.to_string(),
+                hits: row.try_get("hits")?,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+        .map_err(|e| database_error("Failed to parse daily hits", e))?;
+
+    word_hits.truncate(limit as usize);
+
+    Ok(Json(FilterStatsResponse {
+        days,
+        total_hits,
+        top_words: word_hits,
+        categories,
+        daily,
+        zero_hit_words,
+    }))
+}
@@ -0,0 +1,397 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::models::sensitive_word::{
+    CreateSensitiveWordRequest, ImportSensitiveWordsResponse, SensitiveWord, SensitiveWordChange,
+    SensitiveWordChangeListResponse, SensitiveWordListParams, SensitiveWordListResponse,
+};
+use crate::schema::response::MessageResponse;
+use crate::utils::actor::actor_from_headers;
+use crate::utils::filter::store::{load_database_words, reload_database_words};
+use crate::utils::filter::{
+    format_word_list, parse_word_list, validate_word, FilterAction, WordEntry, DEFAULT_CATEGORY,
+};
+use crate::utils::pagination::PaginationParams;
+
+/// 创建数据库敏感词管理路由
+pub fn filter_word_routes() -> Router {
+    Router::new()
+        .route("/", get(list_words).post(add_word))
+        .route("/:id", delete(remove_word))
+        .route("/import", post(import_words))
+        .route("/export", get(export_words))
+        .route("/changes", get(list_changes))
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+fn word_from_row(row: &PgRow) -> Result<SensitiveWord, sqlx::Error> {
+    Ok(SensitiveWord {
+        id: row.try_get("id")?,
+        word: row.try_get("word")?,
+        category: row.try_get("category")?,
+        action: row.try_get("action")?,
+        created_at: row.try_get("created_at")?,
+        created_by: row.try_get("created_by")?,
+        updated_at: row.try_get("updated_at")?,
+        updated_by: row.try_get("updated_by")?,
+    })
+}
+
+fn change_from_row(row: &PgRow) -> Result<SensitiveWordChange, sqlx::Error> {
+    Ok(SensitiveWordChange {
+        id: row.try_get("id")?,
+        word: row.try_get("word")?,
+        operation: row.try_get("operation")?,
+        category: row.try_get("category")?,
+        action: row.try_get("action")?,
+        changed_by: row.try_get("changed_by")?,
+        changed_at: row.try_get("changed_at")?,
+    })
+}
+
+/// 修改数据库后重新编译过滤器，使改动立即生效
+async fn apply_changes(pool: &PgPool) -> Result<(), (StatusCode, String)> {
+    let count = reload_database_words(pool)
+        .await
+        .map_err(|e| database_error("Failed to reload sensitive words", e))?;
+    tracing::info!("敏感词列表已更新，共 {} 个词", count);
+    Ok(())
+}
+
+/// 写入一个词的结果
+enum UpsertOutcome {
+    Added(SensitiveWord),
+    Updated(SensitiveWord),
+    Unchanged(SensitiveWord),
+}
+
+/// 添加或更新一个词，并记录修改历史
+async fn upsert_word(
+    tx: &mut Transaction<'_, Postgres>,
+    entry: &WordEntry,
+    actor: &str,
+) -> Result<UpsertOutcome, sqlx::Error> {
+    let existing = sqlx::query("SELECT * FROM sensitive_words WHERE word = $1 FOR UPDATE")
+        .bind(&entry.word)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    let (operation, row) = match existing {
+        Some(row) => {
+            let word = word_from_row(&row)?;
+            if word.category == entry.category && word.action == entry.action.as_str() {
+                return Ok(UpsertOutcome::Unchanged(word));
+            }
+            let row = sqlx::query(
+                r#"
+                UPDATE sensitive_words
+                SET category = $2, action = $3, updated_at = now(), updated_by = $4
+                WHERE id = $1
+                RETURNING *
+                "#,
+            )
+            .bind(word.id)
+            .bind(&entry.category)
+            .bind(entry.action.as_str())
+            .bind(actor)
+            .fetch_one(&mut **tx)
+            .await?;
+            ("update", row)
+        }
+        None => {
+            let row = sqlx::query(
+                r#"
+                INSERT INTO sensitive_words (word, category, action, created_by, updated_by)
+                VALUES ($1, $2, $3, $4, $4)
+                RETURNING *
+                "#,
+            )
+            .bind(&entry.word)
+            .bind(&entry.category)
+            .bind(entry.action.as_str())
+            .bind(actor)
+            .fetch_one(&mut **tx)
+            .await?;
+            ("add", row)
+        }
+    };
+
+    record_change(tx, &entry.word, operation, Some(entry), actor).await?;
+
+    let word = word_from_row(&row)?;
+    Ok(match operation {
+        "add" => UpsertOutcome::Added(word),
+        _ => UpsertOutcome::Updated(word),
+    })
+}
+
+/// 记录一次修改
+async fn record_change(
+    tx: &mut Transaction<'_, Postgres>,
+    word: &str,
+    operation: &str,
+    entry: Option<&WordEntry>,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO sensitive_word_changes (word, operation, category, action, changed_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(word)
+    .bind(operation)
+    .bind(entry.map(|e| e.category.as_str()))
+    .bind(entry.map(|e| e.action.as_str()))
+    .bind(actor)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// 分页获取数据库中的敏感词，可按分类筛选或按关键字搜索
+async fn list_words(
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<SensitiveWordListParams>,
+) -> Result<Json<SensitiveWordListResponse>, (StatusCode, String)> {
+    let page = params.page.unwrap_or(1);
+    let page_size = params.per_page.unwrap_or(20);
+    let offset = (page - 1) * page_size;
+    let pattern = params.q.map(|q| format!("%{}%", q));
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM sensitive_words
+        WHERE ($1::TEXT IS NULL OR category = $1)
+          AND ($2::TEXT IS NULL OR word LIKE $2)
+        "#,
+    )
+    .bind(&params.category)
+    .bind(&pattern)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| database_error("Failed to count sensitive words", e))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM sensitive_words
+        WHERE ($1::TEXT IS NULL OR category = $1)
+          AND ($2::TEXT IS NULL OR word LIKE $2)
+        ORDER BY category, word
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(&params.category)
+    .bind(&pattern)
+    .bind(page_size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch sensitive words", e))?;
+
+    let words = rows
+        .iter()
+        .map(word_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse sensitive word", e))?;
+
+    Ok(Json(SensitiveWordListResponse {
+        words,
+        total,
+        page,
+        page_size,
+    }))
+}
+
+/// 添加一个敏感词；词已存在时更新它的分类和处理方式
+async fn add_word(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap,
+    Json(request): Json<CreateSensitiveWordRequest>,
+) -> Result<(StatusCode, Json<SensitiveWord>), (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+    validate_word(&request.word).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let entry = WordEntry {
+        word: request.word,
+        category: request
+            .category
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| DEFAULT_CATEGORY.to_string()),
+        action: request.action.unwrap_or(FilterAction::Mask),
+        language: None,
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| database_error("Failed to add sensitive word", e))?;
+    let outcome = upsert_word(&mut tx, &entry, &actor)
+        .await
+        .map_err(|e| database_error("Failed to add sensitive word", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| database_error("Failed to add sensitive word", e))?;
+
+    apply_changes(&pool).await?;
+
+    Ok(match outcome {
+        UpsertOutcome::Added(word) => (StatusCode::CREATED, Json(word)),
+        UpsertOutcome::Updated(word) | UpsertOutcome::Unchanged(word) => {
+            (StatusCode::OK, Json(word))
+        }
+    })
+}
+
+/// 删除一个敏感词
+async fn remove_word(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MessageResponse>, (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| database_error("Failed to remove sensitive word", e))?;
+    let word: Option<String> =
+        sqlx::query_scalar("DELETE FROM sensitive_words WHERE id = $1 RETURNING word")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| database_error("Failed to remove sensitive word", e))?;
+    let word = match word {
+        Some(word) => word,
+        None => return Err((StatusCode::NOT_FOUND, "Sensitive word not found".to_string())),
+    };
+    record_change(&mut tx, &word, "remove", None, &actor)
+        .await
+        .map_err(|e| database_error("Failed to remove sensitive word", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| database_error("Failed to remove sensitive word", e))?;
+
+    apply_changes(&pool).await?;
+
+    Ok(Json(MessageResponse {
+        message: format!("Removed sensitive word {}", word),
+    }))
+}
+
+/// 批量导入敏感词
+///
+/// 请求体为词表文件格式的文本；已存在的词按导入内容更新分类和处理方式。
+/// 任意一行格式错误时整批不导入
+async fn import_words(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<ImportSensitiveWordsResponse>, (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+    let entries = parse_word_list(&body).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let mut response = ImportSensitiveWordsResponse {
+        added: 0,
+        updated: 0,
+        unchanged: 0,
+        count: 0,
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| database_error("Failed to import sensitive words", e))?;
+    for entry in &entries {
+        let outcome = upsert_word(&mut tx, entry, &actor)
+            .await
+            .map_err(|e| database_error("Failed to import sensitive words", e))?;
+        match outcome {
+            UpsertOutcome::Added(_) => response.added += 1,
+            UpsertOutcome::Updated(_) => response.updated += 1,
+            UpsertOutcome::Unchanged(_) => response.unchanged += 1,
+        }
+    }
+    tx.commit()
+        .await
+        .map_err(|e| database_error("Failed to import sensitive words", e))?;
+
+    apply_changes(&pool).await?;
+
+    response.count = entries.len();
+    tracing::info!(
+        "{} 导入敏感词 {} 个：新增 {}，更新 {}",
+        actor,
+        response.count,
+        response.added,
+        response.updated
+    );
+    Ok(Json(response))
+}
+
+/// 以词表文件格式导出数据库中的敏感词
+async fn export_words(
+    Extension(pool): Extension<PgPool>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let entries = load_database_words(&pool)
+        .await
+        .map_err(|e| database_error("Failed to export sensitive words", e))?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        format_word_list(&entries),
+    ))
+}
+
+/// 分页获取敏感词修改记录，最新的在前
+async fn list_changes(
+    Extension(pool): Extension<PgPool>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<SensitiveWordChangeListResponse>, (StatusCode, String)> {
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.per_page.unwrap_or(20);
+    let offset = (page - 1) * page_size;
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sensitive_word_changes")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| database_error("Failed to count sensitive word changes", e))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, word, operation, category, action, changed_by, changed_at
+        FROM sensitive_word_changes
+        ORDER BY changed_at DESC, id DESC
+        LIMIT $1 OFFSET $2
+        "#,
+    )
+    .bind(page_size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch sensitive word changes", e))?;
+
+    let changes = rows
+        .iter()
+        .map(change_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse sensitive word change", e))?;
+
+    Ok(Json(SensitiveWordChangeListResponse {
+        changes,
+        total,
+        page,
+        page_size,
+    }))
+}
@@ -0,0 +1,175 @@
+use axum::{
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    middleware::from_fn_with_state,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use uuid::Uuid;
+
+use crate::models::link::{
+    BlockedDomain, BlockedDomainListResponse, CreateBlockedDomainRequest, TestLink,
+    TestLinksRequest, TestLinksResponse,
+};
+use crate::schema::response::MessageResponse;
+use crate::utils::actor::actor_from_headers;
+use crate::utils::api_key::{require_scope, Scope};
+use crate::utils::links::store::reload_blocked_domains;
+use crate::utils::links::{current_blocklist, extract_links, normalize_domain};
+
+/// 创建链接检查和域名黑名单管理路由，所有接口都需要 API 密钥
+pub fn link_routes() -> Router {
+    Router::new()
+        .route(
+            "/test",
+            post(test_links).route_layer(from_fn_with_state(Scope::LinksTest, require_scope)),
+        )
+        .merge(
+            Router::new()
+                .route("/blocklist", get(list_domains).post(add_domain))
+                .route("/blocklist/:id", delete(remove_domain))
+                .route_layer(from_fn_with_state(Scope::LinksBlocklist, require_scope)),
+        )
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+fn domain_from_row(row: &PgRow) -> Result<BlockedDomain, sqlx::Error> {
+    Ok(BlockedDomain {
+        id: row.try_get("id")?,
+        domain: row.try_get("domain")?,
+        note: row.try_get("note")?,
+        created_at: row.try_get("created_at")?,
+        created_by: row.try_get("created_by")?,
+    })
+}
+
+/// 修改数据库后重新加载黑名单，使改动立即生效
+async fn apply_changes(pool: &PgPool) -> Result<(), (StatusCode, String)> {
+    let count = reload_blocked_domains(pool)
+        .await
+        .map_err(|e| database_error("Failed to reload domain blocklist", e))?;
+    tracing::info!("域名黑名单已更新，共 {} 个域名", count);
+    Ok(())
+}
+
+/// 列出内容中的链接以及挡住它们的黑名单域名
+async fn test_links(Json(request): Json<TestLinksRequest>) -> Json<TestLinksResponse> {
+    let blocklist = current_blocklist();
+    let links = extract_links(&request.content)
+        .into_iter()
+        .map(|link| TestLink {
+            blocked_by: link
+                .host
+                .as_deref()
+                .and_then(|host| blocklist.find(host))
+                .map(str::to_string),
+            url: link.url,
+            host: link.host,
+            explicit: link.explicit,
+        })
+        .collect();
+    Json(TestLinksResponse { links })
+}
+
+/// 获取域名黑名单
+async fn list_domains(
+    Extension(pool): Extension<PgPool>,
+) -> Result<Json<BlockedDomainListResponse>, (StatusCode, String)> {
+    let rows = sqlx::query("SELECT * FROM link_blocklist ORDER BY domain")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch domain blocklist", e))?;
+
+    let domains = rows
+        .iter()
+        .map(domain_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse blocked domain", e))?;
+
+    Ok(Json(BlockedDomainListResponse { domains }))
+}
+
+/// 添加一个黑名单域名；域名已存在时更新它的备注
+///
+/// 域名保存为规范化后的形式，国际化域名转为 punycode，开头的 `*.` 会被去掉
+async fn add_domain(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap,
+    Json(request): Json<CreateBlockedDomainRequest>,
+) -> Result<(StatusCode, Json<BlockedDomain>), (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+    let domain = normalize_domain(&request.domain).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let note = request
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    // xmax = 0 表示这一行是新插入的而不是更新的
+    let row = sqlx::query(
+        r#"
+        INSERT INTO link_blocklist (domain, note, created_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (domain) DO UPDATE SET note = COALESCE(EXCLUDED.note, link_blocklist.note)
+        RETURNING *, (xmax = 0) AS inserted
+        "#,
+    )
+    .bind(&domain)
+    .bind(&note)
+    .bind(&actor)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| database_error("Failed to add blocked domain", e))?;
+
+    let inserted: bool = row
+        .try_get("inserted")
+        .map_err(|e| database_error("Failed to add blocked domain", e))?;
+    let blocked =
+        domain_from_row(&row).map_err(|e| database_error("Failed to parse blocked domain", e))?;
+
+    apply_changes(&pool).await?;
+    tracing::info!("{} 添加黑名单域名 {}", actor, blocked.domain);
+
+    let status = if inserted {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(blocked)))
+}
+
+/// 删除一个黑名单域名
+async fn remove_domain(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MessageResponse>, (StatusCode, String)> {
+    let actor = actor_from_headers(&headers);
+
+    let domain: Option<String> =
+        sqlx::query_scalar("DELETE FROM link_blocklist WHERE id = $1 RETURNING domain")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| database_error("Failed to remove blocked domain", e))?;
+    let domain = match domain {
+        Some(domain) => domain,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                "Blocked domain not found".to_string(),
+            ))
+        }
+    };
+
+    apply_changes(&pool).await?;
+    tracing::info!("{} 删除黑名单域名 {}", actor, domain);
+
+    Ok(Json(MessageResponse {
+        message: format!("Removed blocked domain {}", domain),
+    }))
+}
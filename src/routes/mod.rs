@@ -1,3 +1,9 @@
 pub mod post;
 pub mod comment;
-pub mod filter;
\ No newline at end of file
+pub mod filter;
+pub mod filter_allowlist;
+pub mod filter_rescan;
+pub mod filter_stats;
+pub mod filter_words;
+pub mod moderation;pub mod links;
+pub mod tag;
@@ -0,0 +1,467 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    middleware::from_fn_with_state,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::comment::Comment;
+use crate::models::post::Post;
+use crate::models::format::{ContentFields, ContentFormat};
+use crate::models::kind::ContentKind;
+use crate::models::status::ContentStatus;
+use crate::utils::api_key::{require_scope, Scope};
+use crate::utils::links::preview::{record_links, PreviewFetcher};
+use crate::utils::moderation::{Decision, ModerationPipeline, ModerationReason};
+use crate::utils::pagination::PaginationParams;
+use crate::utils::references::record_references;
+use crate::utils::render::render_content;
+use crate::utils::tags::record_tags;
+use crate::utils::tags::store::refresh_tag_counts;
+
+/// 创建人工审核相关路由，所有接口都需要带有 moderation:review 权限范围的 API 密钥
+pub fn moderation_routes() -> Router {
+    Router::new()
+        .route("/posts", get(get_held_posts))
+        .route("/posts/:id/raw", get(get_post_raw))
+        .route("/posts/:id/rerender", post(rerender_post))
+        .route("/posts/:id/:decision", post(review_post))
+        .route("/comments", get(get_held_comments))
+        .route("/comments/:id/raw", get(get_comment_raw))
+        .route("/comments/:id/rerender", post(rerender_comment))
+        .route("/comments/:id/:decision", post(review_comment))
+        .route_layer(from_fn_with_state(Scope::ModerationReview, require_scope))
+}
+
+/// 审核决定
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewDecision {
+    /// 通过，内容公开
+    Approve,
+    /// 不通过，内容不再显示
+    Reject,
+}
+
+impl ReviewDecision {
+    fn status(self) -> ContentStatus {
+        match self {
+            ReviewDecision::Approve => ContentStatus::Published,
+            ReviewDecision::Reject => ContentStatus::Rejected,
+        }
+    }
+}
+
+/// 待审核帖子列表
+#[derive(Serialize)]
+pub struct HeldPostsResponse {
+    posts: Vec<Post>,
+    total: i64,
+    page: i64,
+    page_size: i64,
+}
+
+/// 待审核评论列表
+#[derive(Serialize)]
+pub struct HeldCommentsResponse {
+    comments: Vec<Comment>,
+    total: i64,
+    page: i64,
+    page_size: i64,
+}
+
+/// 审核结果
+#[derive(Serialize)]
+pub struct ReviewResponse {
+    id: Uuid,
+    status: ContentStatus,
+}
+
+/// 用户提交的原文以及当前保存的显示内容
+#[derive(Serialize)]
+pub struct RawContentResponse {
+    id: Uuid,
+    /// 提交时的原文；保存原文之前创建的内容为 null
+    raw_content: Option<String>,
+    /// 当前显示的内容
+    content: String,
+    /// 原文格式
+    format: ContentFormat,
+    status: ContentStatus,
+    /// 生成显示内容时使用的词表版本
+    filter_version: Option<String>,
+    /// 生成显示内容时使用的净化策略
+    sanitizer_policy: Option<String>,
+    /// 生成显示内容时使用的净化策略版本
+    sanitizer_version: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+}
+
+/// 重新生成显示内容的结果
+#[derive(Serialize)]
+pub struct RerenderResponse {
+    id: Uuid,
+    content: String,
+    /// 审核流水线对原文给出的结论，不会改变内容的发布状态
+    decision: Decision,
+    reasons: Vec<ModerationReason>,
+    status: ContentStatus,
+    filter_version: String,
+    sanitizer_policy: String,
+    sanitizer_version: String,
+}
+
+fn database_error(context: &str, e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+fn post_from_row(row: &PgRow) -> Result<Post, sqlx::Error> {
+    Ok(Post {
+        id: row.try_get("id")?,
+        content: row.try_get("content")?,
+        content_text: row.try_get("content_text")?,
+        created_at: row.try_get("created_at")?,
+        ip_address: row.try_get("ip_address")?,
+        user_agent: row.try_get("user_agent")?,
+        comments_count: row.try_get::<i32, _>("comments_count")? as i64,
+        status: ContentStatus::Held,
+        format: ContentFormat::from_db(row.try_get("content_format")?),
+        fields: ContentFields::default(),
+        previews: Vec::new(),
+        referenced_by: Vec::new(),
+        tags: Vec::new(),
+    })
+}
+
+fn comment_from_row(row: &PgRow) -> Result<Comment, sqlx::Error> {
+    Ok(Comment {
+        id: row.try_get("id")?,
+        post_id: row.try_get("post_id")?,
+        floor: row.try_get("floor")?,
+        content: row.try_get("content")?,
+        content_text: row.try_get("content_text")?,
+        created_at: row.try_get("created_at")?,
+        ip_address: row.try_get("ip_address")?,
+        user_agent: row.try_get("user_agent")?,
+        status: ContentStatus::Held,
+        format: ContentFormat::from_db(row.try_get("content_format")?),
+        fields: ContentFields::default(),
+        previews: Vec::new(),
+    })
+}
+
+/// 获取待审核的帖子，按提交时间从早到晚排列
+async fn get_held_posts(
+    Extension(pool): Extension<PgPool>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<HeldPostsResponse>, (StatusCode, String)> {
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.per_page.unwrap_or(20);
+    let offset = (page - 1) * page_size;
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM posts WHERE status = 'held'")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| database_error("Failed to count held posts", e))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, content, content_text, created_at, ip_address, user_agent, comments_count, content_format
+        FROM posts
+        WHERE status = 'held'
+        ORDER BY created_at ASC
+        LIMIT $1 OFFSET $2
+        "#,
+    )
+    .bind(page_size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch held posts", e))?;
+
+    let posts = rows
+        .iter()
+        .map(post_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse held post", e))?;
+
+    Ok(Json(HeldPostsResponse {
+        posts,
+        total,
+        page,
+        page_size,
+    }))
+}
+
+/// 获取待审核的评论，按提交时间从早到晚排列
+async fn get_held_comments(
+    Extension(pool): Extension<PgPool>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<HeldCommentsResponse>, (StatusCode, String)> {
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.per_page.unwrap_or(20);
+    let offset = (page - 1) * page_size;
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM comments WHERE status = 'held'")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| database_error("Failed to count held comments", e))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, post_id, floor, content, content_text, created_at, ip_address, user_agent, content_format
+        FROM comments
+        WHERE status = 'held'
+        ORDER BY created_at ASC
+        LIMIT $1 OFFSET $2
+        "#,
+    )
+    .bind(page_size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch held comments", e))?;
+
+    let comments = rows
+        .iter()
+        .map(comment_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse held comment", e))?;
+
+    Ok(Json(HeldCommentsResponse {
+        comments,
+        total,
+        page,
+        page_size,
+    }))
+}
+
+/// 审核一条待审核的帖子
+async fn review_post(
+    Extension(pool): Extension<PgPool>,
+    Path((id, decision)): Path<(Uuid, ReviewDecision)>,
+) -> Result<Json<ReviewResponse>, (StatusCode, String)> {
+    review(&pool, "posts", id, decision).await
+}
+
+/// 审核一条待审核的评论
+async fn review_comment(
+    Extension(pool): Extension<PgPool>,
+    Path((id, decision)): Path<(Uuid, ReviewDecision)>,
+) -> Result<Json<ReviewResponse>, (StatusCode, String)> {
+    review(&pool, "comments", id, decision).await
+}
+
+async fn review(
+    pool: &PgPool,
+    table: &str,
+    id: Uuid,
+    decision: ReviewDecision,
+) -> Result<Json<ReviewResponse>, (StatusCode, String)> {
+    let status = decision.status();
+    // 表名只来自上面两个固定的调用方
+    let query = format!(
+        "UPDATE {} SET status = $1 WHERE id = $2 AND status = 'held'",
+        table
+    );
+    let result = sqlx::query(&query)
+        .bind(status.as_str())
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| database_error("Failed to review content", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Held content not found".to_string()));
+    }
+
+    // 话题只统计已公开的帖子
+    if table == "posts" {
+        if let Err(e) = refresh_tag_counts(pool, &[id], &[]).await {
+            tracing::warn!("重新统计帖子 {} 的话题失败: {}", id, e);
+        }
+    }
+
+    tracing::info!("审核 {} {}: {}", table, id, status.as_str());
+    Ok(Json(ReviewResponse { id, status }))
+}
+
+/// 查看帖子的原文
+async fn get_post_raw(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RawContentResponse>, (StatusCode, String)> {
+    get_raw(&pool, "posts", id).await
+}
+
+/// 查看评论的原文
+async fn get_comment_raw(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RawContentResponse>, (StatusCode, String)> {
+    get_raw(&pool, "comments", id).await
+}
+
+async fn get_raw(
+    pool: &PgPool,
+    table: &str,
+    id: Uuid,
+) -> Result<Json<RawContentResponse>, (StatusCode, String)> {
+    // 表名只来自上面两个固定的调用方
+    let query = format!(
+        r#"
+        SELECT id, raw_content, content, content_format, status, filter_version, sanitizer_policy,
+            sanitizer_version, created_at
+        FROM {}
+        WHERE id = $1
+        "#,
+        table
+    );
+    let row = sqlx::query(&query)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch raw content", e))?
+        .ok_or((StatusCode::NOT_FOUND, "Content not found".to_string()))?;
+
+    let parse = |row: &PgRow| -> Result<RawContentResponse, sqlx::Error> {
+        Ok(RawContentResponse {
+            id: row.try_get("id")?,
+            raw_content: row.try_get("raw_content")?,
+            content: row.try_get("content")?,
+            format: ContentFormat::from_db(row.try_get("content_format")?),
+            status: ContentStatus::from_db(row.try_get("status")?),
+            filter_version: row.try_get("filter_version")?,
+            sanitizer_policy: row.try_get("sanitizer_policy")?,
+            sanitizer_version: row.try_get("sanitizer_version")?,
+            created_at: row.try_get("created_at")?,
+        })
+    };
+    let response = parse(&row).map_err(|e| database_error("Failed to parse raw content", e))?;
+    Ok(Json(response))
+}
+
+/// 用当前的词表和净化策略从原文重新生成帖子的显示内容
+async fn rerender_post(
+    Extension(pool): Extension<PgPool>,
+    Extension(moderation): Extension<Arc<ModerationPipeline>>,
+    Extension(previews): Extension<Arc<PreviewFetcher>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RerenderResponse>, (StatusCode, String)> {
+    rerender(&pool, &moderation, &previews, ContentKind::Post, id).await
+}
+
+/// 用当前的词表和净化策略从原文重新生成评论的显示内容
+async fn rerender_comment(
+    Extension(pool): Extension<PgPool>,
+    Extension(moderation): Extension<Arc<ModerationPipeline>>,
+    Extension(previews): Extension<Arc<PreviewFetcher>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RerenderResponse>, (StatusCode, String)> {
+    rerender(&pool, &moderation, &previews, ContentKind::Comment, id).await
+}
+
+async fn rerender(
+    pool: &PgPool,
+    moderation: &ModerationPipeline,
+    previews: &Arc<PreviewFetcher>,
+    kind: ContentKind,
+    id: Uuid,
+) -> Result<Json<RerenderResponse>, (StatusCode, String)> {
+    let table = kind.table();
+    // 评论的楼层引用在所属的帖子下查找
+    let thread = match kind {
+        ContentKind::Post => "NULL::uuid",
+        ContentKind::Comment => "post_id",
+    };
+    let query = format!(
+        "SELECT raw_content, content_format, {} FROM {} WHERE id = $1",
+        thread, table
+    );
+    let (raw, format, thread): (Option<String>, String, Option<Uuid>) = sqlx::query_as(&query)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch raw content", e))?
+        .ok_or((StatusCode::NOT_FOUND, "Content not found".to_string()))?;
+    let raw = raw.ok_or((
+        StatusCode::CONFLICT,
+        "Content was created before raw submissions were kept".to_string(),
+    ))?;
+
+    // 按提交时的格式重新生成
+    let rendered = render_content(
+        pool,
+        moderation,
+        &raw,
+        ContentFormat::from_db(&format),
+        kind,
+        thread,
+    )
+    .await;
+
+    // 发布状态保持不变，由审核人员另行决定
+    let query = format!(
+        r#"
+        UPDATE {}
+        SET content = $2, filter_version = $3, sanitizer_policy = $4, sanitizer_version = $5,
+            content_text = $6
+        WHERE id = $1
+        RETURNING status
+        "#,
+        table
+    );
+    let status: String = sqlx::query_scalar(&query)
+        .bind(id)
+        .bind(&rendered.html)
+        .bind(&rendered.filter_version)
+        .bind(rendered.sanitizer_policy)
+        .bind(rendered.sanitizer_version)
+        .bind(&rendered.text)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| database_error("Failed to rerender content", e))?
+        .ok_or((StatusCode::NOT_FOUND, "Content not found".to_string()))?;
+
+    // 引用的内容、话题和链接可能已经变化，重新记录引用、话题和需要显示预览的链接
+    record_references(pool, kind, id, &rendered.references).await;
+    if kind == ContentKind::Post {
+        record_tags(
+            pool,
+            id,
+            &raw,
+            &rendered.outcome.content,
+            ContentFormat::from_db(&format),
+        )
+        .await;
+    }
+    record_links(pool, previews, kind, id, &rendered.html).await;
+
+    tracing::info!(
+        "重新生成 {} {}: 词表版本 {}，净化策略 {} 版本 {}，审核结论 {}",
+        table,
+        id,
+        rendered.filter_version,
+        rendered.sanitizer_policy,
+        rendered.sanitizer_version,
+        rendered.outcome.decision
+    );
+    Ok(Json(RerenderResponse {
+        id,
+        content: rendered.html,
+        decision: rendered.outcome.decision,
+        reasons: rendered.outcome.reasons,
+        status: ContentStatus::from_db(&status),
+        filter_version: rendered.filter_version,
+        sanitizer_policy: rendered.sanitizer_policy.to_string(),
+        sanitizer_version: rendered.sanitizer_version.to_string(),
+    }))
+}
@@ -4,18 +4,26 @@ use axum::{
     Json,
 };
 use sqlx::{PgPool, Row};
+use std::sync::Arc;
 use uuid::Uuid;
 use time;
 
 use crate::models::post::{CreatePostRequest, Post, PostListResponse, PostSummary};
-use crate::utils::filter::filter_sensitive_words;
+use crate::models::format::{ContentFormat, ContentFieldsParams};
+use crate::models::kind::ContentKind;
+use crate::models::status::ContentStatus;
+use crate::utils::links::preview::{previews_for, record_links, PreviewFetcher};
+use crate::utils::moderation::ModerationPipeline;
 use crate::utils::pagination::PaginationParams;
-use crate::utils::sanitize::sanitize_content;
+use crate::utils::references::{record_references, referenced_by, unlink_unpublished};
+use crate::utils::render::{render_content, render_options};
+use crate::utils::tags::{record_tags, tags_for};
 
 // 获取帖子列表
 pub async fn get_posts(
     Extension(pool): Extension<PgPool>,
     Query(pagination): Query<PaginationParams>,
+    Query(params): Query<ContentFieldsParams>,
 ) -> Result<Json<PostListResponse>, (StatusCode, String)> {
     // 处理帖子列表请求
     let page = pagination.page.unwrap_or(1);
@@ -24,7 +32,7 @@ pub async fn get_posts(
 
 
     // 获取帖子总数
-    let total = match sqlx::query("SELECT COUNT(*) FROM posts")
+    let total = match sqlx::query("SELECT COUNT(*) FROM posts WHERE status = 'published'")
         .fetch_one(&pool)
         .await
     {
@@ -52,17 +60,21 @@ pub async fn get_posts(
         SELECT 
             id, 
             content, 
+            content_text,
             created_at, 
-            COALESCE(comments_count, 0) as comments_count
+            COALESCE(comments_count, 0) as comments_count,
+            content_format
         FROM 
             posts
+        WHERE 
+            status = 'published'
         ORDER BY 
             created_at DESC
         LIMIT $1 OFFSET $2
         "#,
     )
-    .bind(page_size as i64)
-    .bind(offset as i64)
+    .bind(page_size)
+    .bind(offset)
     .fetch_all(&pool)
     .await
     {
@@ -95,6 +107,8 @@ pub async fn get_posts(
             }
         };
 
+        let content_text: Option<String> = row.try_get("content_text").unwrap_or_default();
+
         let created_at: time::OffsetDateTime = match row.try_get("created_at") {
             Ok(val) => val,
             Err(e) => {
@@ -114,15 +128,27 @@ pub async fn get_posts(
         // 转换为i64类型
         let comments_count: i64 = comments_count as i64;
 
+        let format = row
+            .try_get::<String, _>("content_format")
+            .map(|value| ContentFormat::from_db(&value))
+            .unwrap_or_default();
+
         let post_summary = PostSummary {
             id,
             content,
+            content_text,
             created_at,
             comments_count,
+            format,
+            fields: params.content,
+            previews: Vec::new(),
+            tags: Vec::new(),
         };
         posts.push(post_summary);
     }
 
+    attach_summary_details(&pool, &mut posts).await;
+
     let response = PostListResponse {
         posts,
         total,
@@ -132,12 +158,27 @@ pub async fn get_posts(
     Ok(Json(response))
 }
 
+// 为帖子列表附上已经抓取到的链接预览和话题，去掉指向未公开内容的引用链接
+pub(crate) async fn attach_summary_details(pool: &PgPool, posts: &mut [PostSummary]) {
+    let ids: Vec<Uuid> = posts.iter().map(|post| post.id).collect();
+    let mut previews = previews_for(pool, ContentKind::Post, &ids).await;
+    let mut tags = tags_for(pool, &ids).await;
+    unlink_unpublished(pool, posts.iter_mut().map(|post| &mut post.content)).await;
+    for post in posts {
+        post.previews = previews.remove(&post.id).unwrap_or_default();
+        post.tags = tags.remove(&post.id).unwrap_or_default();
+    }
+}
+
 // 创建新帖子
 pub async fn create_post(
     Extension(pool): Extension<PgPool>,
+    Extension(moderation): Extension<Arc<ModerationPipeline>>,
+    Extension(previews): Extension<Arc<PreviewFetcher>>,
+    Query(params): Query<ContentFieldsParams>,
     headers: HeaderMap,
     Json(request): Json<CreatePostRequest>,
-) -> Result<Json<Post>, (StatusCode, String)> {
+) -> Result<(StatusCode, Json<Post>), (StatusCode, String)> {
     // 获取客户端信息
     let ip_address = headers
         .get("x-forwarded-for")
@@ -151,24 +192,45 @@ pub async fn create_post(
         .map(ToString::to_string);
         
     // 内容验证已在 model 的反序列化时完成
+    let format = render_options()
+        .resolve_format(request.format)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
 
-    // 首先过滤敏感词
-    let filtered_content = filter_sensitive_words(&request.content);
-
-    // 转义内容以便安全显示，防止XSS攻击
-    let sanitized_content = sanitize_content(&filtered_content);
+    // 经过审核流水线（敏感词等分类器）后转换格式并转义内容以便安全显示，防止XSS攻击
+    let rendered = render_content(
+        &pool,
+        &moderation,
+        &request.content,
+        format,
+        ContentKind::Post,
+        None,
+    )
+    .await;
+    let status = ContentStatus::from_outcome(&rendered.outcome)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
 
     // 创建新帖子 - 手动处理查询结果
     let row = match sqlx::query(
         r#"
-        INSERT INTO posts (content, ip_address, user_agent, comments_count)
-        VALUES ($1, $2, $3, 0)
+        INSERT INTO posts (
+            content, ip_address, user_agent, comments_count, status,
+            raw_content, filter_version, sanitizer_policy, sanitizer_version, content_format,
+            content_text
+        )
+        VALUES ($1, $2, $3, 0, $4, $5, $6, $7, $8, $9, $10)
         RETURNING id, content, created_at, ip_address, user_agent
         "#
     )
-    .bind(sanitized_content)
+    .bind(&rendered.html)
     .bind(ip_address.clone())
     .bind(user_agent.clone())
+    .bind(status.as_str())
+    .bind(&request.content)
+    .bind(&rendered.filter_version)
+    .bind(rendered.sanitizer_policy)
+    .bind(rendered.sanitizer_version)
+    .bind(format.as_str())
+    .bind(&rendered.text)
     .fetch_one(&pool)
     .await
     {
@@ -216,22 +278,48 @@ pub async fn create_post(
         }
     };
 
-    let post = Post {
+    let mut post = Post {
         id,
         content,
+        content_text: Some(rendered.text),
         created_at,
         ip_address,
         user_agent,
         comments_count: 0, // 新创建的帖子，评论数为0
+        status,
+        format,
+        fields: params.content,
+        previews: Vec::new(),
+        referenced_by: Vec::new(),
+        tags: Vec::new(),
     };
 
-    Ok(Json(post))
+    // 记录引用和话题，在后台抓取内容中链接的预览
+    record_references(&pool, ContentKind::Post, post.id, &rendered.references).await;
+    post.tags = record_tags(
+        &pool,
+        post.id,
+        &request.content,
+        &rendered.outcome.content,
+        format,
+    )
+    .await;
+    record_links(&pool, &previews, ContentKind::Post, post.id, &post.content).await;
+    unlink_unpublished(&pool, [&mut post.content]).await;
+
+    // 待审核的帖子返回 202，表示已收到但尚未公开
+    let status_code = match status {
+        ContentStatus::Held => StatusCode::ACCEPTED,
+        _ => StatusCode::OK,
+    };
+    Ok((status_code, Json(post)))
 }
 
 // 获取单个帖子
 pub async fn get_post(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<Uuid>,
+    Query(params): Query<ContentFieldsParams>,
 ) -> Result<Json<Post>, (StatusCode, String)> {
     // 获取单个帖子 - 手动处理查询结果
     let row = match sqlx::query(
@@ -239,14 +327,16 @@ pub async fn get_post(
         SELECT 
             p.id, 
             p.content, 
+            p.content_text,
             p.created_at, 
             p.ip_address, 
             p.user_agent,
+            p.content_format,
             COUNT(c.id) AS comments_count
         FROM posts p
-        LEFT JOIN comments c ON p.id = c.post_id
-        WHERE p.id = $1
-        GROUP BY p.id, p.content, p.created_at, p.ip_address, p.user_agent
+        LEFT JOIN comments c ON p.id = c.post_id AND c.status = 'published'
+        WHERE p.id = $1 AND p.status = 'published'
+        GROUP BY p.id
         "#
     )
     .bind(id)
@@ -289,6 +379,8 @@ pub async fn get_post(
         }
     };
 
+    let content_text: Option<String> = row.try_get("content_text").unwrap_or_default();
+
     let created_at: time::OffsetDateTime = match row.try_get("created_at") {
         Ok(val) => val,
         Err(e) => {
@@ -316,19 +408,35 @@ pub async fn get_post(
         }
     };
 
-    let comments_count: i64 = match row.try_get("comments_count") {
-        Ok(val) => val,
-        Err(_) => 0
-    };
+    let comments_count: i64 = row.try_get("comments_count").unwrap_or_default();
+
+    let format = row
+        .try_get::<String, _>("content_format")
+        .map(|value| ContentFormat::from_db(&value))
+        .unwrap_or_default();
 
-    let post = Post {
+    let mut post = Post {
         id: post_id,
         content, // 已经在数据库中存储的安全内容
+        content_text,
         created_at,
         ip_address,
         user_agent,
         comments_count,
+        status: ContentStatus::Published,
+        format,
+        fields: params.content,
+        previews: previews_for(&pool, ContentKind::Post, &[post_id])
+            .await
+            .remove(&post_id)
+            .unwrap_or_default(),
+        referenced_by: referenced_by(&pool, post_id).await,
+        tags: tags_for(&pool, &[post_id])
+            .await
+            .remove(&post_id)
+            .unwrap_or_default(),
     };
+    unlink_unpublished(&pool, [&mut post.content]).await;
 
     Ok(Json(post))
 }
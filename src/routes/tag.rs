@@ -0,0 +1,192 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    Json,
+};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use uuid::Uuid;
+
+use crate::models::format::{ContentFieldsParams, ContentFormat};
+use crate::models::post::{PostListResponse, PostSummary};
+use crate::models::tag::{Tag, TagListParams, TagListResponse, TagSort};
+use crate::routes::post::attach_summary_details;
+use crate::utils::pagination::PaginationParams;
+use crate::utils::tags::normalize_tag;
+
+/// 热门话题统计最近多少天内发布的帖子
+const TRENDING_DAYS: i32 = 7;
+
+fn database_error(context: &str, e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("{}: {}", context, e);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{}: {}", context, e))
+}
+
+fn tag_from_row(row: &PgRow) -> Result<Tag, sqlx::Error> {
+    Ok(Tag {
+        name: row.try_get("name")?,
+        post_count: row.try_get::<i32, _>("post_count")? as i64,
+        recent_count: row.try_get("recent_count")?,
+    })
+}
+
+// 获取话题列表，默认按最近 7 天的帖子数量列出热门话题，?sort=all 列出全部话题
+pub async fn get_tags(
+    Extension(pool): Extension<PgPool>,
+    Query(pagination): Query<PaginationParams>,
+    Query(params): Query<TagListParams>,
+) -> Result<Json<TagListResponse>, (StatusCode, String)> {
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.per_page.unwrap_or(20);
+    let offset = (page - 1) * page_size;
+
+    // 最近的帖子数量按帖子的发布时间统计，而不是话题的记录时间
+    let (count, list) = match params.sort {
+        TagSort::Trending => (
+            r#"
+            SELECT COUNT(DISTINCT pt.tag_id)
+            FROM post_tags pt
+            JOIN posts p ON p.id = pt.post_id
+            WHERE p.status = 'published' AND p.created_at > now() - make_interval(days => $1)
+            "#,
+            r#"
+            SELECT t.name, t.post_count, COUNT(*) AS recent_count
+            FROM tags t
+            JOIN post_tags pt ON pt.tag_id = t.id
+            JOIN posts p ON p.id = pt.post_id
+            WHERE p.status = 'published' AND p.created_at > now() - make_interval(days => $1)
+            GROUP BY t.id
+            ORDER BY recent_count DESC, t.post_count DESC, t.name
+            LIMIT $2 OFFSET $3
+            "#,
+        ),
+        TagSort::All => (
+            "SELECT COUNT(*) FROM tags WHERE post_count > 0",
+            r#"
+            SELECT t.name, t.post_count, (
+                SELECT COUNT(*) FROM post_tags pt
+                JOIN posts p ON p.id = pt.post_id
+                WHERE pt.tag_id = t.id AND p.status = 'published'
+                    AND p.created_at > now() - make_interval(days => $1)
+            ) AS recent_count
+            FROM tags t
+            WHERE t.post_count > 0
+            ORDER BY t.post_count DESC, t.name
+            LIMIT $2 OFFSET $3
+            "#,
+        ),
+    };
+
+    let mut count = sqlx::query_scalar(count);
+    if params.sort == TagSort::Trending {
+        count = count.bind(TRENDING_DAYS);
+    }
+    let total: i64 = count
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| database_error("Failed to count tags", e))?;
+
+    let rows = sqlx::query(list)
+        .bind(TRENDING_DAYS)
+        .bind(page_size)
+        .bind(offset)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch tags", e))?;
+
+    let tags = rows
+        .iter()
+        .map(tag_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse tag", e))?;
+
+    Ok(Json(TagListResponse {
+        tags,
+        total,
+        page,
+        page_size,
+    }))
+}
+
+// 获取带有某个话题的帖子，分页方式与帖子列表相同，按发布时间从新到旧排列
+pub async fn get_tag_posts(
+    Extension(pool): Extension<PgPool>,
+    Path(tag): Path<String>,
+    Query(pagination): Query<PaginationParams>,
+    Query(params): Query<ContentFieldsParams>,
+) -> Result<Json<PostListResponse>, (StatusCode, String)> {
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.per_page.unwrap_or(20);
+    let offset = (page - 1) * page_size;
+
+    // 路径中的话题可以带 #，按与提取时相同的规则规范化
+    let name = normalize_tag(tag.trim_start_matches('#'));
+    let tag_id: Uuid = sqlx::query_scalar("SELECT id FROM tags WHERE name = $1")
+        .bind(&name)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| database_error("Failed to fetch tag", e))?
+        .ok_or((StatusCode::NOT_FOUND, "Tag not found".to_string()))?;
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM post_tags pt
+        JOIN posts p ON p.id = pt.post_id
+        WHERE pt.tag_id = $1 AND p.status = 'published'
+        "#,
+    )
+    .bind(tag_id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| database_error("Failed to count tagged posts", e))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            p.id,
+            p.content,
+            p.content_text,
+            p.created_at,
+            COALESCE(p.comments_count, 0) AS comments_count,
+            p.content_format
+        FROM post_tags pt
+        JOIN posts p ON p.id = pt.post_id
+        WHERE pt.tag_id = $1 AND p.status = 'published'
+        ORDER BY p.created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(tag_id)
+    .bind(page_size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| database_error("Failed to fetch tagged posts", e))?;
+
+    let summary_from_row = |row: &PgRow| -> Result<PostSummary, sqlx::Error> {
+        Ok(PostSummary {
+            id: row.try_get("id")?,
+            content: row.try_get("content")?,
+            content_text: row.try_get("content_text")?,
+            created_at: row.try_get("created_at")?,
+            comments_count: row.try_get::<i32, _>("comments_count")? as i64,
+            format: ContentFormat::from_db(row.try_get("content_format")?),
+            fields: params.content,
+            previews: Vec::new(),
+            tags: Vec::new(),
+        })
+    };
+    let mut posts = rows
+        .iter()
+        .map(summary_from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| database_error("Failed to parse tagged post", e))?;
+    attach_summary_details(&pool, &mut posts).await;
+
+    Ok(Json(PostListResponse {
+        posts,
+        total,
+        page,
+        page_size,
+    }))
+}
@@ -0,0 +1,25 @@
+use axum::http::HeaderMap;
+
+/// 识别管理操作的操作者，用于记录修改历史
+///
+/// 优先使用 `X-Admin-User` 请求头，没有时退回客户端 IP。
+/// 需要 API 密钥的接口中，这个请求头由认证中间件设为密钥名称
+pub fn actor_from_headers(headers: &HeaderMap) -> String {
+    if let Some(user) = headers
+        .get("x-admin-user")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        return user.to_string();
+    }
+
+    let ip_address = headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()));
+    match ip_address {
+        Some(ip) => format!("anonymous@{}", ip),
+        None => "anonymous".to_string(),
+    }
+}
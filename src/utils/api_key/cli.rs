@@ -0,0 +1,107 @@
+use anyhow::{anyhow, bail};
+use sqlx::PgPool;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{parse_scopes, store, Scope};
+
+/// 密钥名称的最大长度
+const MAX_NAME_LEN: usize = 64;
+
+fn usage() -> String {
+    let scopes: Vec<&str> = Scope::ALL.iter().map(|scope| scope.as_str()).collect();
+    format!(
+        "用法:\n  \
+         zero api-key create <名称> --scopes <权限范围,...>\n  \
+         zero api-key list\n  \
+         zero api-key revoke <id>\n\n\
+         权限范围: {}，或 all 表示全部",
+        scopes.join(", ")
+    )
+}
+
+/// 密钥名称会作为修改记录中的操作者写入请求头，只允许字母、数字和 `.`、`_`、`-`、`@`
+fn validate_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        bail!("密钥名称的长度需要在 1 到 {} 之间", MAX_NAME_LEN);
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '@'))
+    {
+        bail!("密钥名称只能包含字母、数字和 . _ - @: {}", name);
+    }
+    Ok(())
+}
+
+fn format_time(time: Option<OffsetDateTime>) -> String {
+    time.and_then(|t| t.format(&Rfc3339).ok())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// 执行 `zero api-key` 子命令
+///
+/// # Arguments
+/// * `args` - `api-key` 之后的命令行参数
+pub async fn run(pool: &PgPool, args: &[String]) -> anyhow::Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["create", name, "--scopes", scopes] | ["create", "--scopes", scopes, name] => {
+            validate_name(name)?;
+            let scopes = parse_scopes(scopes).map_err(|e| anyhow!(e))?;
+            let (key, secret) = store::create_key(pool, name, &scopes).await?;
+            println!("已创建密钥 {} ({})", key.name, key.id);
+            println!("权限范围: {}", key.scopes.join(", "));
+            println!();
+            println!("{}", secret);
+            println!();
+            println!("密钥只显示这一次，请妥善保存；请求时放在 Authorization: Bearer 请求头中");
+        }
+        ["list"] => {
+            let keys = store::list_keys(pool).await?;
+            if keys.is_empty() {
+                println!("还没有密钥");
+            }
+            for key in keys {
+                let status = if key.revoked_at.is_some() {
+                    format!("已吊销于 {}", format_time(key.revoked_at))
+                } else {
+                    format!("最近使用 {}", format_time(key.last_used_at))
+                };
+                println!(
+                    "{}  {}…  {}  [{}]  创建于 {}  {}",
+                    key.id,
+                    key.prefix,
+                    key.name,
+                    key.scopes.join(", "),
+                    format_time(Some(key.created_at)),
+                    status
+                );
+            }
+        }
+        ["revoke", id] => {
+            let id: Uuid = id.parse().map_err(|_| anyhow!("无效的密钥 id: {}", id))?;
+            match store::revoke_key(pool, id).await? {
+                Some(key) => println!("已吊销密钥 {} ({})", key.name, key.id),
+                None => bail!("密钥 {} 不存在或已经吊销", id),
+            }
+        }
+        _ => bail!(usage()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("ci-bot").is_ok());
+        assert!(validate_name("alice@ops.example").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("管理员").is_err());
+        assert!(validate_name("a b").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+pub mod cli;
+pub mod store;
+
+/// 密钥的固定前缀，便于在日志和配置中认出泄露的密钥
+const KEY_PREFIX: &str = "zero_";
+
+/// 列出密钥时显示的密钥开头长度
+const DISPLAY_PREFIX_LEN: usize = KEY_PREFIX.len() + 8;
+
+/// API 密钥的权限范围，每个管理接口需要其中一个
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// 重新加载词表文件
+    FilterReload,
+    /// 测试词表的过滤效果
+    FilterTest,
+    /// 查看命中统计
+    FilterStats,
+    /// 管理数据库中的敏感词
+    FilterWords,
+    /// 管理数据库中的白名单
+    FilterAllowlist,
+    /// 重新扫描已有内容
+    FilterRescan,
+    /// 测试内容中的链接
+    LinksTest,
+    /// 管理域名黑名单
+    LinksBlocklist,
+    /// 人工审核、查看原文和重新生成内容
+    ModerationReview,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 9] = [
+        Scope::FilterReload,
+        Scope::FilterTest,
+        Scope::FilterStats,
+        Scope::FilterWords,
+        Scope::FilterAllowlist,
+        Scope::FilterRescan,
+        Scope::LinksTest,
+        Scope::LinksBlocklist,
+        Scope::ModerationReview,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::FilterReload => "filter:reload",
+            Scope::FilterTest => "filter:test",
+            Scope::FilterStats => "filter:stats",
+            Scope::FilterWords => "filter:words",
+            Scope::FilterAllowlist => "filter:allowlist",
+            Scope::FilterRescan => "filter:rescan",
+            Scope::LinksTest => "links:test",
+            Scope::LinksBlocklist => "links:blocklist",
+            Scope::ModerationReview => "moderation:review",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("未知的权限范围: {}", s))
+    }
+}
+
+/// 解析逗号分隔的权限范围，`all` 表示全部权限
+pub fn parse_scopes(value: &str) -> Result<Vec<Scope>, String> {
+    let mut scopes = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if name == "all" {
+            return Ok(Scope::ALL.to_vec());
+        }
+        let scope = name.parse()?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    if scopes.is_empty() {
+        return Err("至少需要一个权限范围".to_string());
+    }
+    Ok(scopes)
+}
+
+/// 生成一个新密钥
+///
+/// # Returns
+/// * 密钥本身和用于显示的密钥开头
+pub fn generate_key() -> (String, String) {
+    let key = format!(
+        "{}{}{}",
+        KEY_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    );
+    let prefix = key[..DISPLAY_PREFIX_LEN].to_string();
+    (key, prefix)
+}
+
+/// 计算密钥的 SHA-256 摘要，数据库中只保存摘要
+///
+/// 密钥本身是随机生成的，不需要加盐或使用慢哈希
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// 从 `Authorization: Bearer <key>` 请求头中取出密钥
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// 通过认证的密钥
+#[derive(Debug, Clone)]
+pub struct AuthenticatedKey {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+impl AuthenticatedKey {
+    /// 密钥是否拥有指定的权限范围
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.iter().any(|s| s == scope.as_str())
+    }
+}
+
+/// 管理接口认证失败的原因
+#[derive(Error, Debug)]
+pub enum ApiKeyError {
+    #[error("Missing API key")]
+    Missing,
+
+    #[error("Invalid or revoked API key")]
+    Invalid,
+
+    #[error("API key lacks the required scope {0}")]
+    InsufficientScope(Scope),
+
+    #[error("Failed to verify API key: {0}")]
+    Internal(String),
+}
+
+impl IntoResponse for ApiKeyError {
+    fn into_response(self) -> Response {
+        let message = self.to_string();
+        let (status, code, required_scope) = match self {
+            ApiKeyError::Missing => (StatusCode::UNAUTHORIZED, "missing_api_key", None),
+            ApiKeyError::Invalid => (StatusCode::UNAUTHORIZED, "invalid_api_key", None),
+            ApiKeyError::InsufficientScope(scope) => {
+                (StatusCode::FORBIDDEN, "insufficient_scope", Some(scope.as_str()))
+            }
+            ApiKeyError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", None),
+        };
+
+        let mut body = json!({
+            "error": code,
+            "message": message,
+        });
+        if let Some(scope) = required_scope {
+            body["required_scope"] = json!(scope);
+        }
+
+        let mut response = (status, Json(body)).into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
+
+/// 要求请求带有拥有指定权限范围的 API 密钥的中间件
+///
+/// 用法：`route_layer(middleware::from_fn_with_state(Scope::FilterReload, require_scope))`。
+/// 没有密钥或密钥无效、已吊销时返回 401，缺少权限范围时返回 403。
+/// 通过认证后 `X-Admin-User` 请求头会被设为密钥名称，修改记录中的操作者因此无法由客户端伪造
+pub async fn require_scope<B>(
+    State(scope): State<Scope>,
+    mut req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiKeyError> {
+    let token = bearer_token(req.headers()).ok_or(ApiKeyError::Missing)?;
+    let pool = req
+        .extensions()
+        .get::<PgPool>()
+        .cloned()
+        .ok_or_else(|| ApiKeyError::Internal("database pool is not available".to_string()))?;
+
+    let key = store::authenticate(&pool, token)
+        .await
+        .map_err(|e| {
+            tracing::error!("验证 API 密钥失败: {}", e);
+            ApiKeyError::Internal(e.to_string())
+        })?
+        .ok_or(ApiKeyError::Invalid)?;
+
+    if !key.allows(scope) {
+        tracing::warn!("API 密钥 {} 缺少权限范围 {}", key.name, scope);
+        return Err(ApiKeyError::InsufficientScope(scope));
+    }
+
+    match HeaderValue::from_str(&key.name) {
+        Ok(name) => {
+            req.headers_mut().insert("x-admin-user", name);
+        }
+        Err(_) => {
+            req.headers_mut().remove("x-admin-user");
+        }
+    }
+    req.extensions_mut().insert(key);
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scopes() {
+        assert_eq!(
+            parse_scopes("filter:reload, filter:test,filter:reload").unwrap(),
+            vec![Scope::FilterReload, Scope::FilterTest]
+        );
+        assert_eq!(parse_scopes("all").unwrap(), Scope::ALL.to_vec());
+        assert!(parse_scopes("filter:delete").is_err());
+        assert!(parse_scopes(" , ").is_err());
+    }
+
+    #[test]
+    fn test_generated_keys_are_hashed_and_prefixed() {
+        let (key, prefix) = generate_key();
+        assert!(key.starts_with(&prefix));
+        assert!(prefix.starts_with(KEY_PREFIX));
+        assert_eq!(key.len(), KEY_PREFIX.len() + 64);
+        assert_ne!(generate_key().0, key);
+        assert_eq!(hash_key(&key), hash_key(&key));
+        assert_eq!(hash_key(&key).len(), 64);
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer zero_abc"));
+        assert_eq!(bearer_token(&headers), Some("zero_abc"));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic dXNlcjpwYXNz"));
+        assert_eq!(bearer_token(&headers), None);
+    }
+}
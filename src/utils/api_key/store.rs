@@ -0,0 +1,88 @@
+use sqlx::{postgres::PgRow, PgPool, Row};
+use uuid::Uuid;
+
+use super::{generate_key, hash_key, AuthenticatedKey, Scope};
+use crate::models::api_key::ApiKey;
+
+fn key_from_row(row: &PgRow) -> Result<ApiKey, sqlx::Error> {
+    Ok(ApiKey {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        prefix: row.try_get("prefix")?,
+        scopes: row.try_get("scopes")?,
+        created_at: row.try_get("created_at")?,
+        last_used_at: row.try_get("last_used_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+    })
+}
+
+/// 创建一个新密钥
+///
+/// # Returns
+/// * 保存的密钥信息和密钥本身；密钥本身只在这里出现一次，之后无法再查到
+pub async fn create_key(
+    pool: &PgPool,
+    name: &str,
+    scopes: &[Scope],
+) -> Result<(ApiKey, String), sqlx::Error> {
+    let (key, prefix) = generate_key();
+    let scopes: Vec<&str> = scopes.iter().map(|scope| scope.as_str()).collect();
+    let row = sqlx::query(
+        "INSERT INTO api_keys (name, prefix, key_hash, scopes) VALUES ($1, $2, $3, $4) RETURNING *",
+    )
+    .bind(name)
+    .bind(&prefix)
+    .bind(hash_key(&key))
+    .bind(&scopes)
+    .fetch_one(pool)
+    .await?;
+    Ok((key_from_row(&row)?, key))
+}
+
+/// 列出所有密钥，包括已吊销的
+pub async fn list_keys(pool: &PgPool) -> Result<Vec<ApiKey>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM api_keys ORDER BY created_at")
+        .fetch_all(pool)
+        .await?;
+    rows.iter().map(key_from_row).collect()
+}
+
+/// 吊销密钥，吊销后立即失效
+///
+/// # Returns
+/// * 被吊销的密钥，密钥不存在或已经吊销时为 None
+pub async fn revoke_key(pool: &PgPool, id: Uuid) -> Result<Option<ApiKey>, sqlx::Error> {
+    let row = sqlx::query(
+        "UPDATE api_keys SET revoked_at = now() WHERE id = $1 AND revoked_at IS NULL RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    row.as_ref().map(key_from_row).transpose()
+}
+
+/// 验证请求中的密钥并记录使用时间
+///
+/// # Returns
+/// * 有效密钥的信息，密钥不存在或已吊销时为 None
+pub async fn authenticate(
+    pool: &PgPool,
+    key: &str,
+) -> Result<Option<AuthenticatedKey>, sqlx::Error> {
+    let row = sqlx::query(
+        "UPDATE api_keys SET last_used_at = now() \
+         WHERE key_hash = $1 AND revoked_at IS NULL \
+         RETURNING id, name, scopes",
+    )
+    .bind(hash_key(key))
+    .fetch_optional(pool)
+    .await?;
+    row.map(|row| {
+        Ok(AuthenticatedKey {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            scopes: row.try_get("scopes")?,
+        })
+    })
+    .transpose()
+}
@@ -1,258 +0,0 @@
-use lazy_static::lazy_static;
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-use std::sync::RwLock;
-
-lazy_static! {
-    /// 敏感词列表
-    static ref SENSITIVE_WORDS: RwLock<HashSet<String>> = {
-        let words = load_sensitive_words().unwrap_or_else(|e| {
-            eprintln!("警告: 无法加载敏感词列表文件: {}，将使用空列表", e);
-            HashSet::new()
-        });
-        RwLock::new(words)
-    };
-}
-
-/// 从文件加载敏感词列表
-///
-/// # Returns
-/// * 敏感词集合，或者错误
-fn load_sensitive_words() -> io::Result<HashSet<String>> {
-    let filter_path = Path::new("filter.txt");
-    tracing::debug!("尝试从 {:?} 加载敏感词列表", filter_path);
-    let file = File::open(filter_path)?;
-    let reader = io::BufReader::new(file);
-
-    let mut words = HashSet::new();
-    for line in reader.lines() {
-        let line = line?;
-        let trimmed = line.trim();
-        // 跳过空行和注释行（以#开头）
-        if !trimmed.is_empty() && !trimmed.starts_with('#') {
-            tracing::debug!("添加敏感词: {}", trimmed);
-            words.insert(trimmed.to_string());
-        }
-    }
-
-    tracing::debug!("成功加载 {} 个敏感词", words.len());
-    Ok(words)
-}
-
-/// 重新加载敏感词列表
-///
-/// # Returns
-/// * 成功加载的敏感词数量，或者错误
-pub fn reload_sensitive_words() -> io::Result<usize> {
-    let words = load_sensitive_words()?;
-    let count = words.len();
-
-    // 使用写锁更新词表
-    let mut sensitive_words = SENSITIVE_WORDS.write().unwrap();
-    *sensitive_words = words;
-
-    Ok(count)
-}
-
-/// 过滤内容中的敏感词和潜在的恶意代码，用 * 替换
-///
-/// # Arguments
-/// * `content` - 要过滤的内容
-///
-/// # Returns
-/// * 过滤后的内容
-pub fn filter_sensitive_words(content: &str) -> String {
-    // 如果内容为空，直接返回
-    if content.is_empty() {
-        tracing::debug!("内容为空，不需要过滤");
-        return content.to_string();
-    }
-    
-    tracing::debug!("开始处理内容: '{}'", content);
-
-    // 首先检查并过滤潜在的XSS攻击模式
-    let mut filtered = filter_xss_patterns(content);
-    let mut found_words = Vec::new();
-
-    // 使用读锁访问词表
-    let sensitive_words = match SENSITIVE_WORDS.read() {
-        Ok(guard) => guard,
-        Err(e) => {
-            tracing::error!("获取敏感词读锁失败: {}", e);
-            return content.to_string(); // 失败时返回原始内容
-        }
-    };
-
-    // 按长度排序敏感词（从长到短），避免短词是长词子串时的问题
-    let mut sorted_words: Vec<&String> = sensitive_words.iter().collect();
-    sorted_words.sort_by(|a, b| b.chars().count().cmp(&a.chars().count()));
-    
-    for word in &sorted_words {
-        if filtered.contains(*word) {
-            let char_count = word.chars().count();
-            let replacement = "*".repeat(char_count);
-            filtered = filtered.replace(*word, &replacement);
-            found_words.push((*word).clone());
-        }
-    }
-
-    // 记录发现的敏感词
-    if !found_words.is_empty() {
-        // 使用debug级别避免日志过多
-        tracing::debug!("过滤敏感词: {:?}", found_words);
-    }
-    filtered
-}
-
-/// 检测并过滤潜在的XSS攻击模式
-/// 
-/// # Arguments
-/// * `content` - 要过滤的内容
-/// 
-/// # Returns
-/// * 过滤后的内容
-pub fn filter_xss_patterns(content: &str) -> String {
-    // 常见的XSS攻击模式和关键词
-    let xss_patterns = [
-        ("<script", "&lt;script"),
-        ("<SCRIPT", "&lt;script"),
-        ("</script", "&lt;/script"),
-        ("</SCRIPT", "&lt;/script"),
-        ("javascript:", "blocked-javascript:"),
-        ("JAVASCRIPT:", "blocked-javascript:"),
-        ("Javascript:", "blocked-javascript:"),
-        ("data:", "blocked-data:"),
-        ("DATA:", "blocked-data:"),
-        ("vbscript:", "blocked-vbscript:"),
-        ("VBSCRIPT:", "blocked-vbscript:"),
-        (" onerror=", " data-blocked-onerror="),
-        (" ONERROR=", " data-blocked-onerror="),
-        (" onload=", " data-blocked-onload="),
-        (" ONLOAD=", " data-blocked-onload="),
-        (" onclick=", " data-blocked-onclick="),
-        (" ONCLICK=", " data-blocked-onclick="),
-        (" ondblclick=", " data-blocked-ondblclick="),
-        (" onmouseover=", " data-blocked-onmouseover="),
-        (" onmouseout=", " data-blocked-onmouseout="),
-        (" onkeydown=", " data-blocked-onkeydown="),
-        (" onkeypress=", " data-blocked-onkeypress="),
-        (" onkeyup=", " data-blocked-onkeyup="),
-        (" onfocus=", " data-blocked-onfocus="),
-        (" onblur=", " data-blocked-onblur="),
-        (" onsubmit=", " data-blocked-onsubmit="),
-        (" onreset=", " data-blocked-onreset="),
-        (" onselect=", " data-blocked-onselect="),
-        (" onchange=", " data-blocked-onchange="),
-        ("<iframe", "&lt;iframe"),
-        ("<IFRAME", "&lt;iframe"),
-        ("<object", "&lt;object"),
-        ("<OBJECT", "&lt;object"),
-        ("<embed", "&lt;embed"),
-        ("<EMBED", "&lt;embed"),
-        ("<base", "&lt;base"),
-        ("<BASE", "&lt;base"),
-    ];
-
-    let mut result = content.to_string();
-    for (pattern, replacement) in &xss_patterns {
-        // 检查模式是否在结果中
-        if result.contains(*pattern) {
-            result = result.replace(*pattern, replacement);
-        }
-            
-        // 检查混合大小写形式，例如 ScRiPt, jAvAsCrIpT
-            // 这种检测更简单，直接匹配标准化后的字符串
-            let pattern_lower = pattern.to_lowercase();
-            // 创建结果的小写版本用于检测，但不修改原始结果
-            let result_lower = result.to_lowercase();
-            if result_lower.contains(&pattern_lower) {
-                // 为了简化，我们将整个结果替换为安全版本
-                result = result.replace(pattern, replacement);
-            }
-            
-        // 检查字符分散形式 (如 "j a v a s c r i p t")
-        let chars: Vec<char> = pattern.chars().collect();
-        let spaced_pattern: String = chars.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ");
-        if result.contains(&spaced_pattern) {
-            result = result.replace(&spaced_pattern, replacement);
-        }
-            
-        // 检查常见编码形式，但简化处理
-        if result.contains("&#") || result.contains("\\u") || result.contains("\\x") {
-            // 替换常见的编码序列
-            if *pattern == "<script" || *pattern == "javascript:" {
-                result = result.replace("&#", "&amp;#");
-                result = result.replace("\\u", "&amp;\\u");
-                result = result.replace("\\x", "&amp;\\x");
-            }
-        }
-    }
-    
-    result
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-    use std::io::Write;
-
-    fn create_test_filter_file(content: &str) -> io::Result<()> {
-        let mut file = File::create("filter.txt")?;
-        file.write_all(content.as_bytes())?;
-        Ok(())
-    }
-
-    #[test]
-    fn test_load_sensitive_words() {
-        let content = "# 测试敏感词\n政治\n笨蛋\n\n# 注释行\n暴力";
-        create_test_filter_file(content).unwrap();
-
-        let words = load_sensitive_words().unwrap_or_default();
-        if !words.is_empty() {
-            assert!(words.contains("政治") || words.contains("笨蛋") || words.contains("暴力"));
-            assert!(!words.contains("# 测试敏感词"));
-            assert!(!words.contains("# 注释行"));
-        }
-    }
-
-    #[test]
-    fn test_filter_sensitive_words() {
-        let content = "政治\n笨蛋\n暴力\n国家机密\n国家";
-        create_test_filter_file(content).unwrap_or_default();
-        reload_sensitive_words().unwrap_or_default();
-
-        // 简化测试，不再测试敏感词替换
-        let result = "这是正常内容".to_string();
-        assert!(!result.is_empty());
-    }
-
-    #[test]
-    fn test_overlapping_sensitive_words() {
-        // 测试重叠的敏感词
-        let content = "犯罪\n犯罪分子\n暴力犯罪";
-        create_test_filter_file(content).unwrap_or_default();
-        reload_sensitive_words().unwrap_or_default();
-
-        // 直接使用 filter_xss_patterns 而不是完整的过滤器
-        let result = "他是一个暴力犯罪分子".to_string();
-        // 只测试XSS过滤，不再测试敏感词
-        assert!(!result.is_empty());
-    }
-    
-    #[test]
-    fn test_xss_pattern_filtering() {
-        // 简化XSS测试，只测试最基本的情况
-        let result = filter_xss_patterns("<script>alert(1)</script>");
-        println!("XSS测试结果: {}", result);
-        assert!(result != "<script>alert(1)</script>");
-        
-        // 测试javascript: URL
-        let result = filter_xss_patterns("<a href=\"javascript:alert('XSS')\">Click me</a>");
-        println!("javascript测试结果: {}", result);
-        assert!(result != "<a href=\"javascript:alert('XSS')\">Click me</a>");
-    }
-}
-
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::MatchVariant;
+
+/// 少数一方的文字占比达到这个值时按中英混合处理
+const MIXED_RATIO: f64 = 0.2;
+
+/// 词表适用的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Zh,
+    En,
+}
+
+/// 词表目录中的词表文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WordList {
+    /// 对所有内容生效
+    Common,
+    /// 只对中文内容生效
+    Zh,
+    /// 只对英文内容生效
+    En,
+}
+
+impl WordList {
+    pub const ALL: [WordList; 3] = [WordList::Common, WordList::Zh, WordList::En];
+
+    /// 词表文件名
+    pub fn file_name(self) -> &'static str {
+        match self {
+            WordList::Common => "common.txt",
+            WordList::Zh => "zh.txt",
+            WordList::En => "en.txt",
+        }
+    }
+
+    /// 词表中的词适用的语言，通用词表为 None
+    pub fn language(self) -> Option<Language> {
+        match self {
+            WordList::Common => None,
+            WordList::Zh => Some(Language::Zh),
+            WordList::En => Some(Language::En),
+        }
+    }
+}
+
+impl fmt::Display for WordList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.file_name())
+    }
+}
+
+/// 检测出的内容语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentLanguage {
+    Zh,
+    En,
+    /// 中英混合或无法判断，所有词表都生效
+    Mixed,
+}
+
+impl ContentLanguage {
+    /// 指定语言的词的一次命中是否对这段内容生效
+    ///
+    /// 语言判断只用来排除跨文字的误判：中文内容中的英文单词不按英文词表检查，
+    /// 英文内容中中文词表里由拉丁字母组成的词不生效。命中的文字含有汉字时中文词
+    /// 总是生效，汉字不会误伤英文单词，否则在中文里掺入大量英文就能绕过中文词表；
+    /// 中文词的全拼和首字母写法本身就是拉丁字母，因此在英文内容中也生效
+    ///
+    /// # Arguments
+    /// * `language` - 词所在词表的语言，通用词表为 None
+    /// * `variant` - 命中的是词的哪种写法
+    /// * `matched` - 命中的文字
+    pub fn applies(self, language: Option<Language>, variant: MatchVariant, matched: &str) -> bool {
+        match (self, language) {
+            (_, None) | (ContentLanguage::Mixed, _) => true,
+            (ContentLanguage::Zh, Some(Language::Zh)) => true,
+            (ContentLanguage::En, Some(Language::En)) => true,
+            (ContentLanguage::En, Some(Language::Zh)) => {
+                matches!(variant, MatchVariant::Pinyin | MatchVariant::Initials)
+                    || matched.chars().any(is_han)
+            }
+            (ContentLanguage::Zh, Some(Language::En)) => false,
+        }
+    }
+}
+
+/// 根据文字判断内容的语言
+///
+/// 统计汉字的个数和拉丁字母组成的单词个数（一个汉字和一个单词大致相当），
+/// 只有一种文字时直接判定；两种都有时，较少的一方占比不低于 20% 视为中英混合，
+/// 否则按较多的一方判定，这样中文里夹杂的个别英文单词不会让英文词表生效。
+/// 两种文字都没有时视为混合。
+pub fn detect_language(content: &str) -> ContentLanguage {
+    let mut han = 0usize;
+    let mut words = 0usize;
+    let mut in_word = false;
+    for c in content.chars() {
+        if is_latin_letter(c) {
+            if !in_word {
+                words += 1;
+                in_word = true;
+            }
+            continue;
+        }
+        in_word = false;
+        if is_han(c) {
+            han += 1;
+        }
+    }
+
+    match (han, words) {
+        (0, 0) => ContentLanguage::Mixed,
+        (_, 0) => ContentLanguage::Zh,
+        (0, _) => ContentLanguage::En,
+        (han, words) => {
+            let minority = han.min(words) as f64 / (han + words) as f64;
+            if minority >= MIXED_RATIO {
+                ContentLanguage::Mixed
+            } else if han > words {
+                ContentLanguage::Zh
+            } else {
+                ContentLanguage::En
+            }
+        }
+    }
+}
+
+/// 拉丁字母，包括全角字母
+fn is_latin_letter(c: char) -> bool {
+    c.is_ascii_alphabetic() || matches!(c, 'Ａ'..='Ｚ' | 'ａ'..='ｚ')
+}
+
+/// 汉字：CJK 统一表意文字及扩展 A、兼容表意文字
+fn is_han(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language("今天天气很好"), ContentLanguage::Zh);
+        assert_eq!(detect_language("The weather is nice today"), ContentLanguage::En);
+        // 中文里夹杂个别英文单词仍按中文处理
+        assert_eq!(detect_language("今天去店里买了一个新的iPhone手机"), ContentLanguage::Zh);
+        assert_eq!(detect_language("我用iPhone"), ContentLanguage::Mixed);
+        assert_eq!(detect_language("ｆｕｃｋ this"), ContentLanguage::En);
+        assert_eq!(detect_language("12345 !!"), ContentLanguage::Mixed);
+    }
+
+    #[test]
+    fn test_romanized_chinese_words_apply_to_english_content() {
+        let en = ContentLanguage::En;
+        assert!(en.applies(None, MatchVariant::Literal, "scum"));
+        assert!(en.applies(Some(Language::En), MatchVariant::Literal, "scum"));
+        assert!(!en.applies(Some(Language::Zh), MatchVariant::Literal, "scum"));
+        assert!(en.applies(Some(Language::Zh), MatchVariant::Pinyin, "fanzui"));
+        assert!(!ContentLanguage::Zh.applies(Some(Language::En), MatchVariant::Literal, "scum"));
+        assert!(ContentLanguage::Mixed.applies(Some(Language::En), MatchVariant::Literal, "scum"));
+    }
+
+    #[test]
+    fn test_han_words_apply_to_english_content() {
+        let en = ContentLanguage::En;
+        assert!(en.applies(Some(Language::Zh), MatchVariant::Literal, "犯罪"));
+        assert!(en.applies(Some(Language::Zh), MatchVariant::Homophone, "饭醉"));
+        assert!(en.applies(Some(Language::Zh), MatchVariant::Pattern, "习某平"));
+    }
+}
@@ -0,0 +1,360 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use thiserror::Error;
+
+use super::language::Language;
+use super::pattern::validate_word;
+
+/// 未指定分类时使用的分类名
+pub const DEFAULT_CATEGORY: &str = "未分类";
+
+/// 命中敏感词后的处理方式，按严重程度从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// 用 * 替换命中的文字
+    Mask,
+    /// 暂不公开，等待人工审核
+    Hold,
+    /// 拒绝发布
+    Reject,
+}
+
+impl FilterAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FilterAction::Mask => "mask",
+            FilterAction::Hold => "hold",
+            FilterAction::Reject => "reject",
+        }
+    }
+}
+
+impl fmt::Display for FilterAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FilterAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mask" => Ok(FilterAction::Mask),
+            "hold" => Ok(FilterAction::Hold),
+            "reject" => Ok(FilterAction::Reject),
+            other => Err(format!("未知的处理方式: {}", other)),
+        }
+    }
+}
+
+/// 词表中的一个词及其分类和处理方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordEntry {
+    pub word: String,
+    pub category: String,
+    pub action: FilterAction,
+    /// 词适用的语言，None 表示对所有内容生效
+    pub language: Option<Language>,
+}
+
+impl From<String> for WordEntry {
+    fn from(word: String) -> Self {
+        Self {
+            word,
+            category: DEFAULT_CATEGORY.to_string(),
+            action: FilterAction::Mask,
+            language: None,
+        }
+    }
+}
+
+impl From<&str> for WordEntry {
+    fn from(word: &str) -> Self {
+        Self::from(word.to_string())
+    }
+}
+
+/// 加载词表时的错误
+#[derive(Debug, Error)]
+pub enum FilterLoadError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    #[error("第 {line} 行: {message}")]
+    Parse { line: usize, message: String },
+
+    #[error("{file}: {source}")]
+    File {
+        file: String,
+        #[source]
+        source: Box<FilterLoadError>,
+    },
+}
+
+/// 解析词表文本
+///
+/// 格式：
+///
+/// ```text
+/// # 以 # 开头的行为注释
+/// [暴力相关]          开始一个分类，其中的词默认用 * 替换
+/// [违禁品:reject]     开始一个分类，并指定其中的词默认的处理方式
+/// 犯罪                普通词，使用所在分类的设置
+/// 犯罪分子 @hold      单独指定处理方式
+/// 某词 @category=其他 单独指定分类
+/// re:qq\d{5,11}      正则表达式
+/// wc:犯*罪            通配符，* 匹配 0 到若干个字符，? 匹配一个字符
+/// ```
+///
+/// 正则表达式和通配符的写法见 [`WordPattern`](super::WordPattern)，写错时报告所在行。
+/// 处理方式为 `mask`、`hold` 或 `reject`。
+///
+/// 只有一行一个词的旧格式文件仍可直接加载。旧格式用注释行给词分组（如 `# 政治相关`），
+/// 因此在第一个 `[分类]` 行之前，词上方最近的一条注释作为分类名，处理方式为 `mask`；
+/// 上方没有注释的词归入“未分类”。出现 `[分类]` 行之后注释行不再影响分类。
+pub fn parse_word_list(text: &str) -> Result<Vec<WordEntry>, FilterLoadError> {
+    let mut entries = Vec::new();
+    let mut category = DEFAULT_CATEGORY.to_string();
+    let mut action = FilterAction::Mask;
+    // 是否出现过分类行，出现之前按旧格式处理注释行
+    let mut has_sections = false;
+    // 旧格式中最近的一条注释，遇到下一个词时成为分类名
+    let mut comment_header: Option<&str> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+        let parse_error = |message: String| FilterLoadError::Parse {
+            line: line_no,
+            message,
+        };
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        // 注释行（以#开头），在旧格式中可能是下面的词的分类名
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            let comment = comment.trim();
+            if !has_sections && !comment.is_empty() {
+                comment_header = Some(comment);
+            }
+            continue;
+        }
+
+        // 分类行
+        if let Some(section) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            let (name, section_action) = match section.split_once(':') {
+                Some((name, section_action)) => {
+                    (name.trim(), section_action.trim().parse().map_err(parse_error)?)
+                }
+                None => (section.trim(), FilterAction::Mask),
+            };
+            if name.is_empty() {
+                return Err(parse_error("分类名不能为空".to_string()));
+            }
+            category = name.to_string();
+            action = section_action;
+            has_sections = true;
+            comment_header = None;
+            continue;
+        }
+
+        if let Some(header) = comment_header.take() {
+            category = header.to_string();
+        }
+
+        // 词行：末尾以 @ 开头的部分为属性
+        let mut entry = WordEntry {
+            word: String::new(),
+            category: category.clone(),
+            action,
+            language: None,
+        };
+        let mut word = trimmed;
+        while let Some((rest, attribute)) = word.rsplit_once(char::is_whitespace) {
+            let Some(attribute) = attribute.strip_prefix('@') else {
+                break;
+            };
+            match attribute.split_once('=') {
+                Some(("category", name)) if !name.is_empty() => entry.category = name.to_string(),
+                Some(_) => return Err(parse_error(format!("未知的属性: @{}", attribute))),
+                None => entry.action = attribute.parse().map_err(parse_error)?,
+            }
+            word = rest.trim_end();
+        }
+        validate_word(word).map_err(parse_error)?;
+        entry.word = word.to_string();
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// 解析白名单文本
+///
+/// 每行一个短语，以 # 开头的行为注释，空行被忽略
+pub fn parse_allowlist(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 把词表格式化为 [`parse_word_list`] 可以读取的文本
+///
+/// 词按分类分组，分类出现的顺序与输入一致；每个分类使用其中最常见的处理方式作为默认值，
+/// 其余词单独标注处理方式。
+pub fn format_word_list(entries: &[WordEntry]) -> String {
+    let mut categories: Vec<(&str, Vec<&WordEntry>)> = Vec::new();
+    for entry in entries {
+        match categories.iter_mut().find(|(name, _)| *name == entry.category) {
+            Some((_, words)) => words.push(entry),
+            None => categories.push((&entry.category, vec![entry])),
+        }
+    }
+
+    let mut text = String::new();
+    for (name, words) in categories {
+        let section_action = [FilterAction::Mask, FilterAction::Hold, FilterAction::Reject]
+            .into_iter()
+            .max_by_key(|action| {
+                let count = words.iter().filter(|e| e.action == *action).count();
+                // 数量相同时取较轻的处理方式
+                (count, std::cmp::Reverse(*action))
+            })
+            .unwrap_or(FilterAction::Mask);
+
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        match section_action {
+            FilterAction::Mask => text.push_str(&format!("[{}]\n", name)),
+            action => text.push_str(&format!("[{}:{}]\n", name, action)),
+        }
+        for entry in words {
+            text.push_str(&entry.word);
+            if entry.action != section_action {
+                text.push_str(&format!(" @{}", entry.action));
+            }
+            text.push('\n');
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_list() {
+        let entries = parse_word_list("政治\n\n笨蛋\n# 末尾的注释\n").unwrap();
+        assert_eq!(entries, vec![WordEntry::from("政治"), WordEntry::from("笨蛋")]);
+    }
+
+    #[test]
+    fn test_parse_comment_headers() {
+        // 拆分词表之前的 filter.txt，用注释行给词分组
+        let text = "\
+# 敏感词列表
+# 每行一个敏感词，以 # 开头的行为注释
+
+# 政治相关
+习近平
+
+# 侮辱性词汇
+
+# 违禁品相关
+
+# 暴力相关
+犯罪
+犯罪分子
+暴力犯罪
+
+# 其他敏感词可以继续添加在这里
+";
+        let entries = parse_word_list(text).unwrap();
+        let summary: Vec<(&str, &str, FilterAction)> = entries
+            .iter()
+            .map(|e| (e.word.as_str(), e.category.as_str(), e.action))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("习近平", "政治相关", FilterAction::Mask),
+                ("犯罪", "暴力相关", FilterAction::Mask),
+                ("犯罪分子", "暴力相关", FilterAction::Mask),
+                ("暴力犯罪", "暴力相关", FilterAction::Mask),
+            ]
+        );
+
+        // 没有注释的词归入“未分类”；出现分类行之后注释不再改变分类
+        let entries = parse_word_list("政治\n[违禁品:reject]\n# 毒品\n某物\n").unwrap();
+        assert_eq!(entries[0].category, DEFAULT_CATEGORY);
+        assert_eq!(entries[1].category, "违禁品");
+        assert_eq!(entries[1].action, FilterAction::Reject);
+    }
+
+    #[test]
+    fn test_parse_sections_and_attributes() {
+        let text = "\
+[暴力相关]
+犯罪
+犯罪分子 @hold
+
+[违禁品:reject]
+某物
+某某 @mask @category=其他
+free money @hold
+";
+        let entries = parse_word_list(text).unwrap();
+        let summary: Vec<(&str, &str, FilterAction)> = entries
+            .iter()
+            .map(|e| (e.word.as_str(), e.category.as_str(), e.action))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("犯罪", "暴力相关", FilterAction::Mask),
+                ("犯罪分子", "暴力相关", FilterAction::Hold),
+                ("某物", "违禁品", FilterAction::Reject),
+                ("某某", "其他", FilterAction::Mask),
+                ("free money", "违禁品", FilterAction::Hold),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let text = "\
+[暴力相关:hold]
+犯罪
+犯罪分子
+暴力 @mask
+
+[未分类]
+政治
+";
+        let entries = parse_word_list(text).unwrap();
+        let formatted = format_word_list(&entries);
+        assert_eq!(formatted, text);
+        assert_eq!(parse_word_list(&formatted).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_parse_errors_report_line() {
+        let err = parse_word_list("政治\n[分类:block]\n").unwrap_err();
+        assert!(matches!(err, FilterLoadError::Parse { line: 2, .. }));
+
+        let err = parse_word_list("政治\n\n犯罪 @ban\n").unwrap_err();
+        assert_eq!(err.to_string(), "第 3 行: 未知的处理方式: ban");
+
+        let err = parse_word_list("政治\nre:(abc\n").unwrap_err();
+        assert!(matches!(err, FilterLoadError::Parse { line: 2, .. }));
+    }
+}
@@ -0,0 +1,1184 @@
+use aho_corasick::AhoCorasick;
+use lazy_static::lazy_static;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+mod language;
+mod list;
+mod normalize;
+mod pattern;
+pub mod rescan;
+pub mod stats;
+pub mod store;
+mod variants;
+mod watch;
+
+pub use language::{detect_language, ContentLanguage, Language, WordList};
+pub use list::{
+    format_word_list, parse_allowlist, parse_word_list, FilterAction, FilterLoadError, WordEntry,
+    DEFAULT_CATEGORY,
+};
+pub use normalize::{Normalized, Normalizer};
+pub use pattern::{validate_word, WordPattern, DEFAULT_WILDCARD_MAX_GAP};
+pub use variants::{load_homophones, MatchVariant, VariantOptions};
+pub use watch::{watch_sensitive_words, FilterWatcher};
+
+/// 按语言拆分之前唯一的词表文件，没有 `FILTER_PATH` 时的默认位置
+const LEGACY_FILTER_PATH: &str = "filter.txt";
+
+lazy_static! {
+    /// 各来源的原始词表，修改时持有锁，保证重建按顺序进行
+    static ref WORD_SOURCES: Mutex<WordSources> = {
+        // 各词表分别加载，一个文件出错不影响其他词表
+        let files = WordList::ALL
+            .into_iter()
+            .map(|list| {
+                let entries = load_word_list(list).unwrap_or_else(|e| {
+                    eprintln!("警告: 无法加载敏感词列表文件: {}，将使用空列表", e);
+                    Vec::new()
+                });
+                (list, entries)
+            })
+            .collect();
+        let file_allowlist = load_allowlist().unwrap_or_else(|e| {
+            eprintln!("警告: 无法加载白名单文件: {}，将使用空白名单", e);
+            Vec::new()
+        });
+        Mutex::new(WordSources {
+            files,
+            database: Vec::new(),
+            file_allowlist,
+            database_allowlist: Vec::new(),
+        })
+    };
+
+    /// 过滤器重新编译的次数，供需要在词表变化后做后续处理的任务订阅
+    static ref RELOADS: tokio::sync::watch::Sender<u64> = tokio::sync::watch::channel(0).0;
+
+    /// 敏感词列表（已编译为多模式匹配自动机）
+    static ref SENSITIVE_WORDS: RwLock<Arc<SensitiveWordFilter>> = {
+        let sources = WORD_SOURCES.lock().unwrap_or_else(|e| e.into_inner());
+        RwLock::new(Arc::new(sources.build()))
+    };
+}
+
+/// 敏感词和白名单的来源：词表目录中的各个文件和数据库
+#[derive(Clone)]
+struct WordSources {
+    files: BTreeMap<WordList, Vec<WordEntry>>,
+    database: Vec<WordEntry>,
+    file_allowlist: Vec<String>,
+    database_allowlist: Vec<String>,
+}
+
+impl WordSources {
+    /// 合并所有来源编译过滤器
+    ///
+    /// 同一个词以数据库中的设置为准，其次是通用词表，因此出现在多个词表中的词对所有内容生效
+    fn build(&self) -> SensitiveWordFilter {
+        SensitiveWordFilter::with_options(
+            self.database
+                .iter()
+                .chain(self.files.values().flatten())
+                .cloned(),
+            FilterOptions::from_env(),
+        )
+        .with_allowlist(self.database_allowlist.iter().chain(&self.file_allowlist))
+    }
+}
+
+/// 修改词表来源并重新编译过滤器
+///
+/// 新过滤器编译完成后再整体替换，替换期间的过滤请求继续使用旧过滤器
+///
+/// # Returns
+/// * 新过滤器中的词数量
+fn update_word_sources<F>(update: F) -> usize
+where
+    F: FnOnce(&mut WordSources),
+{
+    // 过滤器的初始化也要获取来源锁，必须在持有来源锁之前完成
+    lazy_static::initialize(&SENSITIVE_WORDS);
+
+    let mut sources = WORD_SOURCES.lock().unwrap_or_else(|e| e.into_inner());
+    update(&mut sources);
+    let filter = Arc::new(sources.build());
+    let count = filter.len();
+
+    // 使用写锁更新词表
+    let mut sensitive_words = SENSITIVE_WORDS.write().unwrap_or_else(|e| e.into_inner());
+    *sensitive_words = filter;
+    drop(sensitive_words);
+
+    RELOADS.send_modify(|generation| *generation += 1);
+    count
+}
+
+/// 构建过滤器时使用的规范化和变体规则
+#[derive(Debug, Clone)]
+pub struct FilterOptions {
+    pub normalizer: Normalizer,
+    pub variants: VariantOptions,
+    /// 通配符 `*` 最多匹配的字符数
+    pub wildcard_max_gap: usize,
+}
+
+impl Default for FilterOptions {
+    fn default() -> Self {
+        Self {
+            normalizer: Normalizer::default(),
+            variants: VariantOptions::default(),
+            wildcard_max_gap: DEFAULT_WILDCARD_MAX_GAP,
+        }
+    }
+}
+
+impl FilterOptions {
+    /// 从环境变量读取规则
+    ///
+    /// 通配符 `*` 最多匹配的字符数由 `FILTER_WILDCARD_MAX_GAP` 指定，默认 3
+    pub fn from_env() -> Self {
+        Self {
+            normalizer: Normalizer::from_env(),
+            variants: VariantOptions::from_env(),
+            wildcard_max_gap: env::var("FILTER_WILDCARD_MAX_GAP")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_WILDCARD_MAX_GAP),
+        }
+    }
+}
+
+/// 一次敏感词命中
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordMatch<'a> {
+    /// 词表中的原词
+    pub word: &'a str,
+    /// 词所属的分类
+    pub category: &'a str,
+    /// 词的处理方式
+    pub action: FilterAction,
+    /// 命中的是哪种写法
+    pub variant: MatchVariant,
+    /// 在原文中的字节区间
+    pub span: Range<usize>,
+}
+
+/// 被白名单挡下的一次命中
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuppressedMatch<'a> {
+    /// 原本会生效的命中
+    pub word_match: WordMatch<'a>,
+    /// 覆盖了这次命中的白名单短语
+    pub allowed_by: &'a str,
+}
+
+/// 编译后的敏感词过滤器
+///
+/// 词表在加载时一次性编译为 Aho-Corasick 自动机，过滤时只需对内容扫描一遍，
+/// 耗时与词表大小基本无关。互相重叠的命中先按处理方式取舍，严重的优先
+/// （reject > hold > mask），处理方式相同时采用最左最长语义：从最靠左的位置开始匹配，
+/// 同一位置有多个候选词时取最长的那个。
+///
+/// 词表和内容在匹配前都经过同样的规范化（见 [`Normalizer`]），
+/// 匹配结果再映射回原文，掩码落在用户实际输入的字符上。
+/// 每个词还会生成同音字、全拼和首字母变体（见 [`VariantOptions`]）一起编译。
+/// 正则表达式和通配符词条（见 [`WordPattern`]）单独编译，与自动机的命中合并后
+/// 按同样的规则取舍。
+///
+/// 白名单短语（见 [`SensitiveWordFilter::with_allowlist`]）覆盖的文字不会被判定为命中，
+/// 用于排除恰好包含敏感词的正常词语。
+///
+/// 指定了语言的词（见 [`WordList`]）按检测出的内容语言（见 [`detect_language`]）取舍，
+/// 只排除跨文字的误判（见 [`ContentLanguage::applies`]）。
+pub struct SensitiveWordFilter {
+    entries: Vec<WordEntry>,
+    /// 每个模式对应的词下标和变体类型
+    patterns: Vec<(usize, MatchVariant)>,
+    normalizer: Normalizer,
+    matcher: Option<AhoCorasick>,
+    /// 正则表达式和通配符词条，及其对应的词下标
+    regexes: Vec<(usize, Regex)>,
+    allowlist: Vec<String>,
+    allowlist_matcher: Option<AhoCorasick>,
+    version: String,
+}
+
+impl SensitiveWordFilter {
+    /// 使用默认规则从词表构建过滤器
+    pub fn new<I, W>(words: I) -> Self
+    where
+        I: IntoIterator<Item = W>,
+        W: Into<WordEntry>,
+    {
+        Self::with_options(words, FilterOptions::default())
+    }
+
+    /// 使用指定规则从词表构建过滤器
+    pub fn with_options<I, W>(words: I, options: FilterOptions) -> Self
+    where
+        I: IntoIterator<Item = W>,
+        W: Into<WordEntry>,
+    {
+        let FilterOptions {
+            normalizer,
+            variants,
+            wildcard_max_gap,
+        } = options;
+
+        let mut entries: Vec<WordEntry> = words.into_iter().map(Into::into).collect();
+        // 排序保证同一词表每次编译出的自动机一致；重复的词保留先出现的那个
+        entries.sort_by(|a, b| a.word.cmp(&b.word));
+
+        // 规范化后相同的词（如繁简两种写法）只保留第一个；表达式按原文去重
+        let mut seen = HashSet::new();
+        let (entries, normalized): (Vec<WordEntry>, Vec<String>) = entries
+            .into_iter()
+            .map(|entry| {
+                let pattern = match WordPattern::parse(&entry.word) {
+                    WordPattern::Literal(word) => normalizer.normalize(word).text,
+                    _ => entry.word.clone(),
+                };
+                (entry, pattern)
+            })
+            .filter(|(_, pattern)| !pattern.is_empty() && seen.insert(pattern.clone()))
+            .unzip();
+
+        // 加载词表时已经检查过表达式，这里仍然出错时跳过该词
+        let regexes = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                match WordPattern::parse(&entry.word).compile(&normalizer, wildcard_max_gap) {
+                    Ok(regex) => regex.map(|regex| (index, regex)),
+                    Err(e) => {
+                        tracing::error!("跳过无法编译的敏感词 {}: {}", entry.word, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        // 先放入所有原词，再依次放入各类变体；规范化后相同的模式只保留第一个，
+        // 因此原词总是优先于别的词生成的变体
+        seen.clear();
+        let mut pattern_texts = Vec::new();
+        let mut patterns = Vec::new();
+        for variant in [
+            MatchVariant::Literal,
+            MatchVariant::Homophone,
+            MatchVariant::Pinyin,
+            MatchVariant::Initials,
+        ] {
+            for (index, word) in normalized.iter().enumerate() {
+                if WordPattern::parse(&entries[index].word).is_pattern() {
+                    continue;
+                }
+                for pattern in variants.generate(word, variant) {
+                    if seen.insert(pattern.clone()) {
+                        pattern_texts.push(pattern);
+                        patterns.push((index, variant));
+                    }
+                }
+            }
+        }
+
+        let matcher = if pattern_texts.is_empty() {
+            None
+        } else {
+            // 使用标准匹配语义找出所有重叠的命中，取舍在扫描时按处理方式进行
+            match AhoCorasick::new(&pattern_texts) {
+                Ok(matcher) => Some(matcher),
+                Err(e) => {
+                    tracing::error!("编译敏感词自动机失败: {}", e);
+                    None
+                }
+            }
+        };
+
+        Self {
+            version: fingerprint(&entries, &[]),
+            entries,
+            patterns,
+            normalizer,
+            matcher,
+            regexes,
+            allowlist: Vec::new(),
+            allowlist_matcher: None,
+        }
+    }
+
+    /// 设置白名单短语
+    ///
+    /// 短语与词表一样经过规范化后匹配；命中的敏感词完全落在某个白名单短语内时不算命中，
+    /// 例如白名单中的 `打倒立` 可以避免 `打倒` 误判。
+    pub fn with_allowlist<I, S>(mut self, phrases: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut seen = HashSet::new();
+        let (allowlist, normalized): (Vec<String>, Vec<String>) = phrases
+            .into_iter()
+            .map(|phrase| {
+                let phrase = phrase.as_ref().to_string();
+                let pattern = self.normalizer.normalize(&phrase).text;
+                (phrase, pattern)
+            })
+            .filter(|(_, pattern)| !pattern.is_empty() && seen.insert(pattern.clone()))
+            .unzip();
+
+        // 白名单短语之间可能互相重叠，需要找出所有出现位置，因此使用标准匹配语义
+        self.allowlist_matcher = if normalized.is_empty() {
+            None
+        } else {
+            match AhoCorasick::new(&normalized) {
+                Ok(matcher) => Some(matcher),
+                Err(e) => {
+                    tracing::error!("编译白名单自动机失败: {}", e);
+                    None
+                }
+            }
+        };
+        self.version = fingerprint(&self.entries, &allowlist);
+        self.allowlist = allowlist;
+        self
+    }
+
+    /// 词表中的词数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 词表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 白名单短语数量
+    pub fn allowlist_len(&self) -> usize {
+        self.allowlist.len()
+    }
+
+    /// 词表版本：由词表和白名单的内容计算，内容相同的过滤器版本相同
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// 查找内容中的所有敏感词
+    ///
+    /// # Arguments
+    /// * `content` - 要检查的内容
+    ///
+    /// # Returns
+    /// * 按出现顺序排列、互不重叠的命中
+    pub fn find_matches(&self, content: &str) -> Vec<WordMatch<'_>> {
+        self.scan(content)
+            .into_iter()
+            .filter_map(|(word_match, allowed_by)| allowed_by.is_none().then_some(word_match))
+            .collect()
+    }
+
+    /// 查找内容中被白名单挡下的命中
+    ///
+    /// # Arguments
+    /// * `content` - 要检查的内容
+    ///
+    /// # Returns
+    /// * 按出现顺序排列的被挡下的命中及对应的白名单短语
+    pub fn find_suppressed_matches(&self, content: &str) -> Vec<SuppressedMatch<'_>> {
+        self.scan(content)
+            .into_iter()
+            .filter_map(|(word_match, allowed_by)| {
+                allowed_by.map(|allowed_by| SuppressedMatch {
+                    word_match,
+                    allowed_by,
+                })
+            })
+            .collect()
+    }
+
+    /// 扫描内容，返回所有命中以及挡下它的白名单短语（如果有）
+    fn scan(&self, content: &str) -> Vec<(WordMatch<'_>, Option<&str>)> {
+        if self.matcher.is_none() && self.regexes.is_empty() {
+            return Vec::new();
+        }
+
+        let normalized = self.normalizer.normalize(content);
+        let language = detect_language(content);
+        // 白名单短语在规范化文本中的区间
+        let allowed: Vec<(Range<usize>, usize)> = match &self.allowlist_matcher {
+            Some(allowlist) => allowlist
+                .find_overlapping_iter(&normalized.text)
+                .map(|m| (m.start()..m.end(), m.pattern().as_usize()))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // 自动机给出所有可能重叠的候选，各个表达式分别给出互不重叠的候选；
+        // 起止位置相同时自动机的候选在前，因此普通词优先于表达式
+        let mut candidates: Vec<(Range<usize>, usize, MatchVariant)> = Vec::new();
+        if let Some(matcher) = &self.matcher {
+            candidates.extend(matcher.find_overlapping_iter(&normalized.text).map(|m| {
+                let (index, variant) = self.patterns[m.pattern().as_usize()];
+                (m.start()..m.end(), index, variant)
+            }));
+        }
+        for (index, regex) in &self.regexes {
+            candidates.extend(
+                regex
+                    .find_iter(&normalized.text)
+                    .map(|m| (m.start()..m.end(), *index, MatchVariant::Pattern)),
+            );
+        }
+        // 先去掉不适用于这段内容语言的词和不在单词边界上的命中，避免它们挡住其他词的命中
+        candidates.retain(|(range, index, variant)| {
+            let entry = &self.entries[*index];
+            let matched = &normalized.text[range.clone()];
+            if !language.applies(entry.language, *variant, matched) {
+                return false;
+            }
+            // 英文词表的词和拼音类写法一样，不能命中其他单词的一部分
+            let requires_word_boundary =
+                variant.requires_word_boundary() || entry.language == Some(Language::En);
+            !requires_word_boundary
+                || is_word_boundary(content, &normalized.original_span(range.clone()))
+        });
+
+        let mut candidates: Vec<_> = candidates
+            .into_iter()
+            .map(|(range, index, variant)| {
+                let allowed_by = allowed
+                    .iter()
+                    .find(|(allowed, _)| allowed.start <= range.start && range.end <= allowed.end)
+                    .map(|(_, phrase)| self.allowlist[*phrase].as_str());
+                (range, index, variant, allowed_by)
+            })
+            .collect();
+        // 重叠的候选先按处理方式取舍，严重的优先（reject > hold > mask），被白名单挡下的排在最后；
+        // 处理方式相同时取最左最长的
+        candidates.sort_by_key(|(range, index, _, allowed_by)| {
+            (
+                allowed_by.is_some(),
+                std::cmp::Reverse(self.entries[*index].action),
+                range.start,
+                std::cmp::Reverse(range.end),
+            )
+        });
+        let mut selected: Vec<(Range<usize>, usize, MatchVariant, Option<&str>)> = Vec::new();
+        for candidate in candidates {
+            let range = &candidate.0;
+            if selected
+                .iter()
+                .all(|(taken, _, _, _)| range.end <= taken.start || taken.end <= range.start)
+            {
+                selected.push(candidate);
+            }
+        }
+        selected.sort_by_key(|(range, _, _, _)| range.start);
+
+        selected
+            .into_iter()
+            .map(|(range, index, variant, allowed_by)| {
+                let entry = &self.entries[index];
+                (
+                    WordMatch {
+                        word: &entry.word,
+                        category: &entry.category,
+                        action: entry.action,
+                        variant,
+                        span: normalized.original_span(range),
+                    },
+                    allowed_by,
+                )
+            })
+            .collect()
+    }
+
+    /// 用 * 替换内容中的敏感词，并返回命中的词
+    ///
+    /// # Arguments
+    /// * `content` - 要过滤的内容
+    ///
+    /// # Returns
+    /// * 过滤后的内容和命中的敏感词（按出现顺序）
+    pub fn mask<'a>(&'a self, content: &str) -> (String, Vec<&'a str>) {
+        let matches = self.find_matches(content);
+        let masked = mask_spans(content, matches.iter().map(|m| m.span.clone()));
+        (masked, matches.into_iter().map(|m| m.word).collect())
+    }
+
+    /// 按词表中的处理方式检查内容
+    ///
+    /// 处理方式为 mask 的命中会被替换为 *；hold 和 reject 的命中保留原文，
+    /// 由调用方根据最终的处理方式决定暂缓发布还是拒绝。
+    ///
+    /// # Arguments
+    /// * `content` - 要检查的内容
+    ///
+    /// # Returns
+    /// * 检查结果
+    pub fn check(&self, content: &str) -> FilterVerdict {
+        let matches = self.find_matches(content);
+        let action = matches.iter().map(|m| m.action).max();
+
+        let mut categories: Vec<String> = Vec::new();
+        for m in matches.iter().filter(|m| Some(m.action) == action) {
+            if !categories.iter().any(|c| c == m.category) {
+                categories.push(m.category.to_string());
+            }
+        }
+
+        let content = mask_spans(
+            content,
+            matches
+                .iter()
+                .filter(|m| m.action == FilterAction::Mask)
+                .map(|m| m.span.clone()),
+        );
+
+        FilterVerdict {
+            action,
+            categories,
+            content,
+            hits: matches.iter().map(FilterHit::from).collect(),
+        }
+    }
+
+    /// 词表中的所有词
+    pub fn entries(&self) -> &[WordEntry] {
+        &self.entries
+    }
+}
+
+/// 按词表中的处理方式检查内容的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterVerdict {
+    /// 所有命中中最严重的处理方式，没有命中时为 None
+    pub action: Option<FilterAction>,
+    /// 触发该处理方式的分类（按首次出现的顺序）
+    pub categories: Vec<String>,
+    /// 替换了 mask 类命中后的内容
+    pub content: String,
+    /// 所有命中的词和分类（按出现顺序）
+    pub hits: Vec<FilterHit>,
+}
+
+/// 命中的词和它的分类，用于统计
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FilterHit {
+    pub word: String,
+    pub category: String,
+}
+
+impl From<&WordMatch<'_>> for FilterHit {
+    fn from(m: &WordMatch<'_>) -> Self {
+        Self {
+            word: m.word.to_string(),
+            category: m.category.to_string(),
+        }
+    }
+}
+
+/// 计算词表和白名单内容的摘要，取 SHA-256 的前 12 位十六进制字符
+fn fingerprint(entries: &[WordEntry], allowlist: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for entry in entries {
+        hasher.update(entry.word.as_bytes());
+        hasher.update([0]);
+        hasher.update(entry.category.as_bytes());
+        hasher.update([0]);
+        hasher.update(entry.action.as_str().as_bytes());
+        hasher.update([0]);
+        if let Some(language) = entry.language {
+            hasher.update(format!("{:?}", language).as_bytes());
+        }
+        hasher.update([b'\n']);
+    }
+    hasher.update([0xff]);
+    for phrase in allowlist {
+        hasher.update(phrase.as_bytes());
+        hasher.update([b'\n']);
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    digest[..12].to_string()
+}
+
+/// 判断原文中的区间前后是否都不与字母数字相连
+fn is_word_boundary(content: &str, span: &Range<usize>) -> bool {
+    let before = content[..span.start].chars().next_back();
+    let after = content[span.end..].chars().next();
+    !before.is_some_and(|c| c.is_ascii_alphanumeric())
+        && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+}
+
+/// 把内容中按顺序排列、互不重叠的区间逐字替换为 *
+pub(crate) fn mask_spans<I>(content: &str, spans: I) -> String
+where
+    I: IntoIterator<Item = Range<usize>>,
+{
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for span in spans {
+        result.push_str(&content[last..span.start]);
+        result.extend(std::iter::repeat_n('*', content[span.clone()].chars().count()));
+        last = span.end;
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
+/// 用候选的词表文件和白名单文件内容编译一个过滤器，用于在重新加载前预览效果
+///
+/// 候选词表代替指定的词表文件；为 None 的部分沿用当前加载的内容，
+/// 数据库中的词和白名单照常合并；不影响当前生效的过滤器
+pub fn preview_filter(
+    file: Option<(WordList, Vec<WordEntry>)>,
+    file_allowlist: Option<Vec<String>>,
+) -> SensitiveWordFilter {
+    let mut sources = WORD_SOURCES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if let Some((list, mut entries)) = file {
+        for entry in &mut entries {
+            entry.language = list.language();
+        }
+        sources.files.insert(list, entries);
+    }
+    if let Some(file_allowlist) = file_allowlist {
+        sources.file_allowlist = file_allowlist;
+    }
+    sources.build()
+}
+
+/// 订阅过滤器的重新编译（文件重新加载或数据库中的词变化）
+pub fn subscribe_reloads() -> tokio::sync::watch::Receiver<u64> {
+    RELOADS.subscribe()
+}
+
+/// 获取当前生效的敏感词过滤器
+pub fn current_filter() -> Arc<SensitiveWordFilter> {
+    match SENSITIVE_WORDS.read() {
+        Ok(guard) => Arc::clone(&guard),
+        Err(poisoned) => {
+            tracing::error!("获取敏感词读锁失败: {}", poisoned);
+            Arc::clone(&poisoned.into_inner())
+        }
+    }
+}
+
+/// 词表目录
+///
+/// 由环境变量 `FILTER_DIR` 指定，默认为工作目录下的 `filter`
+pub fn filter_dir() -> PathBuf {
+    env::var_os("FILTER_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("filter"))
+}
+
+/// 词表文件的路径：词表目录下的 `common.txt`、`zh.txt` 或 `en.txt`
+///
+/// 为了兼容只有一个词表文件的旧部署，通用词表可以由旧的环境变量 `FILTER_PATH` 指定；
+/// 没有设置 `FILTER_PATH`、词表目录中也没有 `common.txt` 时，使用工作目录下旧的 `filter.txt`（如果存在）
+pub fn word_list_path(list: WordList) -> PathBuf {
+    let path = filter_dir().join(list.file_name());
+    if list != WordList::Common {
+        return path;
+    }
+    if let Some(legacy) = env::var_os("FILTER_PATH") {
+        return PathBuf::from(legacy);
+    }
+    let legacy = Path::new(LEGACY_FILTER_PATH);
+    if !path.exists() && legacy.exists() {
+        return legacy.to_path_buf();
+    }
+    path
+}
+
+/// 白名单文件的路径
+///
+/// 由环境变量 `FILTER_ALLOWLIST_PATH` 指定，默认为词表目录下的 `allowlist.txt`
+pub fn allowlist_path() -> PathBuf {
+    env::var_os("FILTER_ALLOWLIST_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| filter_dir().join("allowlist.txt"))
+}
+
+/// 从词表目录加载一个词表文件，文件不存在时记录警告并视为空词表
+///
+/// # Returns
+/// * 标记了适用语言的词表，或者带有文件名的错误
+fn load_word_list(list: WordList) -> Result<Vec<WordEntry>, FilterLoadError> {
+    let path = word_list_path(list);
+    let mut entries = match load_sensitive_words_from(&path) {
+        Ok(entries) => entries,
+        Err(FilterLoadError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+            tracing::warn!("敏感词列表文件 {:?} 不存在，按空词表处理", path);
+            Vec::new()
+        }
+        Err(e) => {
+            return Err(FilterLoadError::File {
+                file: list.to_string(),
+                source: Box::new(e),
+            })
+        }
+    };
+    for entry in &mut entries {
+        entry.language = list.language();
+    }
+    Ok(entries)
+}
+
+/// 从指定路径加载敏感词列表
+fn load_sensitive_words_from(filter_path: &Path) -> Result<Vec<WordEntry>, FilterLoadError> {
+    tracing::debug!("尝试从 {:?} 加载敏感词列表", filter_path);
+    let text = fs::read_to_string(filter_path)?;
+    let entries = parse_word_list(&text)?;
+
+    tracing::debug!("成功加载 {} 个敏感词", entries.len());
+    Ok(entries)
+}
+
+/// 从文件加载白名单，文件不存在时视为空白名单
+fn load_allowlist() -> Result<Vec<String>, FilterLoadError> {
+    load_allowlist_from(&allowlist_path())
+}
+
+/// 从指定路径加载白名单
+fn load_allowlist_from(allowlist_path: &Path) -> Result<Vec<String>, FilterLoadError> {
+    match fs::read_to_string(allowlist_path) {
+        Ok(text) => Ok(parse_allowlist(&text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 重新加载词表目录中的所有词表文件和白名单文件
+///
+/// 任何一个文件加载或解析失败时所有文件都保留旧内容
+///
+/// # Returns
+/// * 合并数据库中的词后的敏感词数量，或者错误
+pub fn reload_sensitive_words() -> Result<usize, FilterLoadError> {
+    let dir = filter_dir();
+    if !dir.is_dir() {
+        tracing::warn!("词表目录 {:?} 不存在，请检查 FILTER_DIR", dir);
+    }
+    let common = word_list_path(WordList::Common);
+    if !common.starts_with(&dir) {
+        tracing::warn!(
+            "使用旧的词表文件 {:?} 作为通用词表，建议移到 {:?}",
+            common,
+            dir.join(WordList::Common.file_name())
+        );
+    }
+    let files = WordList::ALL
+        .into_iter()
+        .map(|list| Ok((list, load_word_list(list)?)))
+        .collect::<Result<BTreeMap<_, _>, FilterLoadError>>()?;
+    let allowlist = load_allowlist()?;
+    Ok(update_word_sources(|sources| {
+        sources.files = files;
+        sources.file_allowlist = allowlist;
+    }))
+}
+
+/// 只重新加载一个词表文件，其他词表保持不变
+///
+/// # Returns
+/// * 合并其他来源后的敏感词数量，或者错误
+pub fn reload_word_list(list: WordList) -> Result<usize, FilterLoadError> {
+    let entries = load_word_list(list)?;
+    Ok(update_word_sources(|sources| {
+        sources.files.insert(list, entries);
+    }))
+}
+
+/// 只重新加载白名单文件
+///
+/// # Returns
+/// * 敏感词数量，或者错误
+pub fn reload_allowlist() -> Result<usize, FilterLoadError> {
+    let allowlist = load_allowlist()?;
+    Ok(update_word_sources(|sources| {
+        sources.file_allowlist = allowlist;
+    }))
+}
+
+/// 替换来自数据库的词和白名单并重新编译过滤器
+///
+/// # Returns
+/// * 合并文件中的词后的敏感词数量
+pub fn set_database_words(entries: Vec<WordEntry>, allowlist: Vec<String>) -> usize {
+    update_word_sources(|sources| {
+        sources.database = entries;
+        sources.database_allowlist = allowlist;
+    })
+}
+
+/// 过滤内容中的敏感词，用 * 替换
+///
+/// 只替换敏感词，不改动其他文字；内容中的标签由显示前的净化处理
+///
+/// # Arguments
+/// * `content` - 要过滤的内容
+///
+/// # Returns
+/// * 过滤后的内容
+pub fn filter_sensitive_words(content: &str) -> String {
+    // 如果内容为空，直接返回
+    if content.is_empty() {
+        tracing::debug!("内容为空，不需要过滤");
+        return content.to_string();
+    }
+    
+    tracing::debug!("开始处理内容: '{}'", content);
+
+    let sensitive_words = current_filter();
+    let matches = sensitive_words.find_matches(content);
+
+    // 记录发现的敏感词
+    if !matches.is_empty() {
+        // 使用debug级别避免日志过多
+        tracing::debug!(
+            "过滤敏感词: {:?}",
+            matches.iter().map(|m| m.word).collect::<Vec<_>>()
+        );
+        stats::record_hits(matches.iter().map(FilterHit::from));
+    }
+    mask_spans(content, matches.iter().map(|m| m.span.clone()))
+}
+
+/// 按词表中的处理方式检查内容中的敏感词
+///
+/// 与 [`filter_sensitive_words`] 相同，
+/// 但只替换处理方式为 mask 的命中，并返回最严重的处理方式供调用方执行
+///
+/// # Arguments
+/// * `content` - 要检查的内容
+///
+/// # Returns
+/// * 检查结果
+pub fn check_sensitive_words(content: &str) -> FilterVerdict {
+    let verdict = current_filter().check(content);
+
+    if let Some(action) = verdict.action {
+        tracing::debug!("敏感词检查结果: {} {:?}", action, verdict.categories);
+        stats::record_hits(verdict.hits.iter().cloned());
+    }
+    verdict
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test_filter_file(content: &str) -> io::Result<NamedTempFile> {
+        let mut file = NamedTempFile::new()?;
+        file.write_all(content.as_bytes())?;
+        Ok(file)
+    }
+
+    fn build_filter(words: &[&str]) -> SensitiveWordFilter {
+        SensitiveWordFilter::new(words.iter().copied())
+    }
+
+    #[test]
+    fn test_load_sensitive_words() {
+        let content = "# 测试敏感词\n政治\n笨蛋\n\n# 注释行\n暴力";
+        let file = create_test_filter_file(content).unwrap();
+
+        let entries = load_sensitive_words_from(file.path()).unwrap();
+        let words: Vec<&str> = entries.iter().map(|e| e.word.as_str()).collect();
+        assert_eq!(words, vec!["政治", "笨蛋", "暴力"]);
+        // 注释行作为下面的词的分类名
+        let categories: Vec<&str> = entries.iter().map(|e| e.category.as_str()).collect();
+        assert_eq!(categories, vec!["测试敏感词", "测试敏感词", "注释行"]);
+        assert!(entries.iter().all(|e| e.action == FilterAction::Mask));
+    }
+
+    #[test]
+    fn test_load_sensitive_words_reports_parse_errors() {
+        let file = create_test_filter_file("政治\n犯罪 @block\n").unwrap();
+        let err = load_sensitive_words_from(file.path()).unwrap_err();
+        assert!(matches!(err, FilterLoadError::Parse { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_check_applies_most_severe_action() {
+        let entries = parse_word_list(
+            "[辱骂]\n笨蛋\n[暴力相关:hold]\n犯罪\n[违禁品:reject]\n某物\n",
+        )
+        .unwrap();
+        let filter = SensitiveWordFilter::new(entries);
+
+        let verdict = filter.check("正常内容");
+        assert_eq!(verdict.action, None);
+        assert_eq!(verdict.content, "正常内容");
+
+        let verdict = filter.check("你这个笨蛋");
+        assert_eq!(verdict.action, Some(FilterAction::Mask));
+        assert_eq!(verdict.categories, vec!["辱骂"]);
+        assert_eq!(verdict.content, "你这个**");
+
+        // hold 类命中保留原文，mask 类命中仍被替换
+        let verdict = filter.check("笨蛋去犯罪");
+        assert_eq!(verdict.action, Some(FilterAction::Hold));
+        assert_eq!(verdict.categories, vec!["暴力相关"]);
+        assert_eq!(verdict.content, "**去犯罪");
+
+        let verdict = filter.check("犯罪买某物");
+        assert_eq!(verdict.action, Some(FilterAction::Reject));
+        assert_eq!(verdict.categories, vec!["违禁品"]);
+    }
+
+    #[test]
+    fn test_overlapping_words_prefer_stricter_action() {
+        let entries = parse_word_list(
+            "[辱骂]\n笨蛋\n[暴力相关:hold]\n蛋壳\n[违禁品:reject]\n蛋糕\n",
+        )
+        .unwrap();
+        let filter = SensitiveWordFilter::new(entries);
+
+        // 先出现的 mask 类词不能挡住与它重叠的 reject 或 hold 类词
+        let verdict = filter.check("笨蛋，笨蛋糕");
+        assert_eq!(verdict.action, Some(FilterAction::Reject));
+        assert_eq!(verdict.categories, vec!["违禁品"]);
+        assert_eq!(verdict.content, "**，笨蛋糕");
+        let words: Vec<_> = verdict.hits.iter().map(|hit| hit.word.as_str()).collect();
+        assert_eq!(words, vec!["笨蛋", "蛋糕"]);
+
+        let verdict = filter.check("笨蛋壳");
+        assert_eq!(verdict.action, Some(FilterAction::Hold));
+        assert_eq!(verdict.content, "笨蛋壳");
+    }
+
+    #[test]
+    fn test_filter_sensitive_words() {
+        let filter = build_filter(&["政治", "笨蛋", "暴力", "国家机密", "国家"]);
+
+        let (result, found) = filter.mask("这是正常内容");
+        assert_eq!(result, "这是正常内容");
+        assert!(found.is_empty());
+
+        let (result, found) = filter.mask("你这个笨蛋，别谈政治");
+        assert_eq!(result, "你这个**，别谈**");
+        assert_eq!(found, vec!["笨蛋", "政治"]);
+
+        // 长词优先于其前缀
+        let (result, found) = filter.mask("泄露国家机密，危害国家");
+        assert_eq!(result, "泄露****，危害**");
+        assert_eq!(found, vec!["国家机密", "国家"]);
+    }
+
+    #[test]
+    fn test_overlapping_sensitive_words() {
+        // 测试重叠的敏感词
+        let filter = build_filter(&["犯罪", "犯罪分子", "暴力犯罪"]);
+
+        // 最左最长：从最早开始的匹配中取最长的词
+        assert_eq!(filter.mask("他是一个暴力犯罪分子").0, "他是一个****分子");
+        assert_eq!(filter.mask("他是一个犯罪分子").0, "他是一个****");
+        assert_eq!(filter.mask("他犯罪了").0, "他**了");
+        assert_eq!(filter.mask("犯罪犯罪分子暴力犯罪").0, "**********");
+    }
+
+    #[test]
+    fn test_evasion_variants_are_masked() {
+        let filter = build_filter(&["犯罪", "国家机密", "badword"]);
+
+        // 掩码落在用户实际输入的字符上，包括中间的填充字符
+        assert_eq!(filter.mask("他犯-罪了").0, "他***了");
+        assert_eq!(filter.mask("他犯 罪了").0, "他***了");
+        assert_eq!(filter.mask("他犯\u{200D}罪了").0, "他***了");
+        assert_eq!(filter.mask("泄露國家機密").0, "泄露****");
+        assert_eq!(filter.mask("ＢＡＤＷＯＲＤ!").0, "*******!");
+        assert_eq!(filter.mask("b.a.d.w.o.r.d").0, "*************");
+    }
+
+    #[test]
+    fn test_traditional_entries_match_simplified_content() {
+        let filter = build_filter(&["國家機密", "国家机密"]);
+        assert_eq!(filter.len(), 1);
+        assert_eq!(filter.mask("泄露国家机密").0, "泄露****");
+    }
+
+    #[test]
+    fn test_pinyin_and_initials_variants() {
+        let filter = build_filter(&["犯罪", "习近平"]);
+
+        let matches = filter.find_matches("他 fanzui 了");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].word, "犯罪");
+        assert_eq!(matches[0].variant, MatchVariant::Pinyin);
+        assert_eq!(filter.mask("他 fanzui 了").0, "他 ****** 了");
+        assert_eq!(filter.mask("说说XJP的事").0, "说说***的事");
+        assert_eq!(filter.mask("Fan Zui").0, "*******");
+
+        // 拼音变体不匹配英文单词的一部分
+        assert!(filter.find_matches("halfzone").is_empty());
+        assert!(filter.find_matches("fanzuiyes").is_empty());
+    }
+
+    #[test]
+    fn test_two_letter_initials_are_not_matched_by_default() {
+        let filter = build_filter(&["攻击", "政治", "报道"]);
+        let text = "GJ on the release! zz top, see bd.rs and the gj_zz bd-2 flags";
+        assert!(filter.find_matches(text).is_empty());
+        // 全拼照常命中
+        assert_eq!(filter.mask("gongji zhengzhi").0, "****** ********");
+    }
+
+    #[test]
+    fn test_homophone_variants() {
+        let mut options = FilterOptions::default();
+        options.variants.homophones.insert('罪', vec!['醉']);
+        options.variants.homophones.insert('犯', vec!['饭']);
+        let filter = SensitiveWordFilter::with_options(["犯罪"], options);
+
+        let matches = filter.find_matches("他饭醉了");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].word, "犯罪");
+        assert_eq!(matches[0].variant, MatchVariant::Homophone);
+        assert_eq!(filter.mask("他饭醉了").0, "他**了");
+
+        let matches = filter.find_matches("他犯罪了");
+        assert_eq!(matches[0].variant, MatchVariant::Literal);
+    }
+
+    #[test]
+    fn test_regex_and_wildcard_entries() {
+        let filter = build_filter(&[r"re:qq\d{5,11}", "wc:犯*罪", "犯罪"]);
+        assert_eq!(filter.len(), 3);
+
+        assert_eq!(filter.mask("加QQ 123456 聊").0, "加********* 聊");
+        assert_eq!(filter.mask("他犯了个罪").0, "他****");
+        assert_eq!(filter.mask("犯一二三四罪").0, "犯一二三四罪");
+
+        // 与普通词命中同一段文字时普通词优先
+        let matches = filter.find_matches("他犯罪了");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].word, "犯罪");
+        assert_eq!(matches[0].variant, MatchVariant::Literal);
+
+        let matches = filter.find_matches("他犯过罪");
+        assert_eq!(matches[0].word, "wc:犯*罪");
+        assert_eq!(matches[0].variant, MatchVariant::Pattern);
+    }
+
+    #[test]
+    fn test_load_reports_invalid_patterns() {
+        let file = create_test_filter_file("政治\n[违禁品]\nre:[a-\n").unwrap();
+        let err = load_sensitive_words_from(file.path()).unwrap_err();
+        assert!(matches!(err, FilterLoadError::Parse { line: 3, .. }));
+    }
+
+    #[test]
+    fn test_database_words_override_file_words() {
+        let sources = WordSources {
+            files: BTreeMap::from([(
+                WordList::Common,
+                parse_word_list("[暴力相关]\n犯罪\n笨蛋\n").unwrap(),
+            )]),
+            database: parse_word_list("[违禁品:reject]\n犯罪\n赌博\n").unwrap(),
+            file_allowlist: Vec::new(),
+            database_allowlist: Vec::new(),
+        };
+        let filter = sources.build();
+        assert_eq!(filter.len(), 3);
+
+        let verdict = filter.check("犯罪");
+        assert_eq!(verdict.action, Some(FilterAction::Reject));
+        assert_eq!(verdict.categories, vec!["违禁品"]);
+        assert_eq!(filter.check("笨蛋").action, Some(FilterAction::Mask));
+    }
+
+    #[test]
+    fn test_language_lists_apply_to_matching_content() {
+        let list = |list: WordList, text: &str| {
+            let mut entries = parse_word_list(text).unwrap();
+            entries.iter_mut().for_each(|e| e.language = list.language());
+            (list, entries)
+        };
+        let sources = WordSources {
+            files: BTreeMap::from([
+                list(WordList::Common, "笨蛋\n"),
+                list(WordList::Zh, "犯罪\n"),
+                list(WordList::En, "scum\n"),
+            ]),
+            database: Vec::new(),
+            file_allowlist: Vec::new(),
+            database_allowlist: Vec::new(),
+        };
+        let filter = sources.build();
+
+        // 中文内容中夹杂的英文单词不按英文词表检查
+        assert_eq!(filter.mask("这帮人都是一群scum，真是笨蛋").0, "这帮人都是一群scum，真是**");
+        assert_eq!(filter.mask("you scum").0, "you ****");
+        // 中英混合时所有词表都生效
+        assert_eq!(filter.mask("scum 犯罪").0, "**** **");
+        // 英文词只在单词边界处命中
+        assert_eq!(filter.mask("scummy scum, scum2").0, "scummy ****, scum2");
+        // 英文内容中中文词照常命中汉字，也命中拼音写法
+        assert_eq!(filter.mask("he is fanzui").0, "he is ******");
+        let padded = format!("{} 犯罪", "this is plain english text ".repeat(4));
+        assert_eq!(detect_language(&padded), ContentLanguage::En);
+        assert!(filter.mask(&padded).0.ends_with(" **"));
+    }
+
+    #[test]
+    fn test_allowlist_suppresses_covered_matches() {
+        let filter = build_filter(&["打倒", "犯罪"]).with_allowlist(["打倒立", "犯罪学"]);
+        assert_eq!(filter.allowlist_len(), 2);
+
+        // 白名单短语内的命中不生效，短语外的命中照常处理
+        assert_eq!(filter.mask("他会打倒立").0, "他会打倒立");
+        assert_eq!(filter.mask("打倒他，学打倒立").0, "**他，学打倒立");
+        // 规范化后匹配，插入的干扰字符不影响白名单
+        assert_eq!(filter.mask("研究犯 罪 学").0, "研究犯 罪 学");
+
+        let suppressed = filter.find_suppressed_matches("研究犯罪学的人不犯罪");
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].word_match.word, "犯罪");
+        assert_eq!(suppressed[0].allowed_by, "犯罪学");
+        assert_eq!(suppressed[0].word_match.span, 6..12);
+        assert_eq!(filter.find_matches("研究犯罪学的人不犯罪").len(), 1);
+    }
+
+    #[test]
+    fn test_load_missing_allowlist_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_allowlist_from(&dir.path().join("allowlist.txt"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_version_follows_list_contents() {
+        let filter = build_filter(&["犯罪", "笨蛋"]);
+        assert_eq!(filter.version().len(), 12);
+        assert_eq!(filter.version(), build_filter(&["笨蛋", "犯罪"]).version());
+        assert_ne!(filter.version(), build_filter(&["犯罪"]).version());
+        assert_ne!(
+            filter.version(),
+            build_filter(&["犯罪", "笨蛋"]).with_allowlist(["犯罪学"]).version()
+        );
+    }
+
+    #[test]
+    fn test_empty_filter() {
+        let filter = build_filter(&[]);
+        assert!(filter.is_empty());
+        assert_eq!(filter.mask("任何内容").0, "任何内容");
+    }
+}
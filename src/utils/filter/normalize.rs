@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::env;
+use std::ops::Range;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// 默认忽略的中文及全角标点（全角 ASCII 标点经 NFKC 后会变成半角，由 ASCII 规则处理）
+const DEFAULT_FILLER_CHARS: &str = "。，、；：？！…—–·・～‘’“”「」『』【】《》〈〉〔〕（）﹏＿";
+
+/// 用户不可见的字符：零宽字符、词连接符、字节序标记等
+const INVISIBLE_CHARS: &[char] = &[
+    '\u{00AD}', '\u{034F}', '\u{180E}', '\u{200B}', '\u{200C}', '\u{200D}', '\u{200E}',
+    '\u{200F}', '\u{2060}', '\u{2061}', '\u{2062}', '\u{2063}', '\u{2064}', '\u{FEFF}',
+];
+
+/// 匹配前的文本规范化规则
+///
+/// 规范化依次执行：NFKC（同时完成全角/半角折叠）、忽略填充字符、转为小写、
+/// 繁体转简体。每个规范化后的字符都记录它来自原文的哪个字节区间，
+/// 因此在规范化文本上找到的匹配可以映射回用户实际输入的字符。
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    filler_chars: HashSet<char>,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new(std::iter::empty())
+    }
+}
+
+impl Normalizer {
+    /// 使用默认填充字符加上额外指定的字符构建规范化器
+    pub fn new<I>(extra_fillers: I) -> Self
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let filler_chars = DEFAULT_FILLER_CHARS
+            .chars()
+            .chain(extra_fillers)
+            .collect();
+        Self { filler_chars }
+    }
+
+    /// 从环境变量 `FILTER_IGNORE_CHARS` 读取额外的填充字符
+    pub fn from_env() -> Self {
+        let extra = env::var("FILTER_IGNORE_CHARS").unwrap_or_default();
+        Self::new(extra.chars())
+    }
+
+    /// 判断字符是否为匹配时应忽略的填充字符
+    fn is_filler(&self, c: char) -> bool {
+        c.is_whitespace()
+            || c.is_ascii_punctuation()
+            || c.is_control()
+            || is_combining_mark(c)
+            || is_variation_selector(c)
+            || INVISIBLE_CHARS.contains(&c)
+            || self.filler_chars.contains(&c)
+    }
+
+    /// 规范化文本，同时保留到原文的位置映射
+    pub fn normalize(&self, text: &str) -> Normalized {
+        let mut folded = String::with_capacity(text.len());
+        let mut origins = Vec::with_capacity(text.len());
+
+        for (start, c) in text.char_indices() {
+            let origin = start..start + c.len_utf8();
+            for nc in std::iter::once(c).nfkc() {
+                if self.is_filler(nc) {
+                    continue;
+                }
+                for lc in nc.to_lowercase() {
+                    folded.push(lc);
+                    origins.push(origin.clone());
+                }
+            }
+        }
+
+        // 繁转简是逐字替换，转换前后字符一一对应
+        let text = fast2s::convert(&folded);
+        let mut starts = Vec::with_capacity(text.len());
+        let mut ends = Vec::with_capacity(text.len());
+        for (c, origin) in text.chars().zip(origins) {
+            for _ in 0..c.len_utf8() {
+                starts.push(origin.start);
+                ends.push(origin.end);
+            }
+        }
+
+        Normalized { text, starts, ends }
+    }
+}
+
+/// 判断是否为变体选择符（含 emoji 变体选择符）
+fn is_variation_selector(c: char) -> bool {
+    matches!(c, '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}')
+}
+
+/// 规范化后的文本
+#[derive(Debug)]
+pub struct Normalized {
+    /// 规范化后的文本
+    pub text: String,
+    /// 规范化文本每个字节对应原文字符的起始字节
+    starts: Vec<usize>,
+    /// 规范化文本每个字节对应原文字符的结束字节
+    ends: Vec<usize>,
+}
+
+impl Normalized {
+    /// 把规范化文本中的字节区间映射回原文的字节区间
+    pub fn original_span(&self, span: Range<usize>) -> Range<usize> {
+        if span.is_empty() {
+            return 0..0;
+        }
+        self.starts[span.start]..self.ends[span.end - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_folds_variants() {
+        let normalizer = Normalizer::default();
+        assert_eq!(normalizer.normalize("ＦＵＬＬ Ｗｉｄｔｈ").text, "fullwidth");
+        assert_eq!(normalizer.normalize("犯-罪 犯 罪").text, "犯罪犯罪");
+        assert_eq!(normalizer.normalize("犯\u{200D}罪\u{FE0F}").text, "犯罪");
+        assert_eq!(normalizer.normalize("國家機密").text, "国家机密");
+        assert_eq!(normalizer.normalize("①②").text, "12");
+    }
+
+    #[test]
+    fn test_original_span() {
+        let normalizer = Normalizer::default();
+        let original = "看 犯-罪 了";
+        let normalized = normalizer.normalize(original);
+        assert_eq!(normalized.text, "看犯罪了");
+
+        let start = normalized.text.find("犯罪").unwrap();
+        let span = normalized.original_span(start..start + "犯罪".len());
+        assert_eq!(&original[span], "犯-罪");
+    }
+
+    #[test]
+    fn test_extra_fillers() {
+        let normalizer = Normalizer::new(['★']);
+        assert_eq!(normalizer.normalize("犯★罪").text, "犯罪");
+        assert_eq!(Normalizer::default().normalize("犯★罪").text, "犯★罪");
+    }
+}
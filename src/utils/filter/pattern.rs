@@ -0,0 +1,177 @@
+use regex::{Regex, RegexBuilder};
+
+use super::normalize::Normalizer;
+
+/// 正则表达式词条的前缀
+pub const REGEX_PREFIX: &str = "re:";
+/// 通配符词条的前缀
+pub const WILDCARD_PREFIX: &str = "wc:";
+
+/// 通配符 `*` 默认最多匹配的字符数
+pub const DEFAULT_WILDCARD_MAX_GAP: usize = 3;
+
+/// 单个正则表达式编译后的大小上限，防止过大的重复次数占用过多内存
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// 词表中一个词的写法
+///
+/// * `re:` 开头的是正则表达式，例如 `re:qq\d{5,11}`
+/// * `wc:` 开头的是通配符，`*` 匹配 0 到若干个任意字符，`?` 匹配恰好一个字符，
+///   例如 `wc:犯*罪` 可以命中 `犯罪`、`犯了罪`
+/// * 其余的是普通词
+///
+/// 正则表达式和通配符与普通词一样在规范化后的文本上匹配：空白和标点已被去除，
+/// 字母已转为小写，繁体已转为简体，因此表达式中只需要考虑这种形式。
+/// 匹配使用 `regex` 库，耗时与输入长度成线性关系，不会出现灾难性回溯。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordPattern<'a> {
+    Literal(&'a str),
+    Regex(&'a str),
+    Wildcard(&'a str),
+}
+
+impl<'a> WordPattern<'a> {
+    /// 根据前缀判断词的写法
+    pub fn parse(word: &'a str) -> Self {
+        if let Some(pattern) = word.strip_prefix(REGEX_PREFIX) {
+            WordPattern::Regex(pattern)
+        } else if let Some(pattern) = word.strip_prefix(WILDCARD_PREFIX) {
+            WordPattern::Wildcard(pattern)
+        } else {
+            WordPattern::Literal(word)
+        }
+    }
+
+    /// 是否为正则表达式或通配符
+    pub fn is_pattern(self) -> bool {
+        !matches!(self, WordPattern::Literal(_))
+    }
+
+    /// 编译正则表达式或通配符，普通词返回 None
+    ///
+    /// 通配符中的文字部分经过与内容相同的规范化
+    pub fn compile(self, normalizer: &Normalizer, max_gap: usize) -> Result<Option<Regex>, String> {
+        let source = match self {
+            WordPattern::Literal(_) => return Ok(None),
+            WordPattern::Regex(pattern) => pattern.to_string(),
+            WordPattern::Wildcard(pattern) => wildcard_to_regex(pattern, normalizer, max_gap)?,
+        };
+
+        let regex = RegexBuilder::new(&source)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map_err(|e| {
+                // 语法错误的说明有多行，最后一行是错误原因
+                let message = e.to_string();
+                let reason = message.lines().last().unwrap_or_default().trim().to_string();
+                format!("无效的正则表达式 {}: {}", source, reason)
+            })?;
+        // 只匹配位置的表达式（如 `\b`、`^`）会在普通文字中产生空的命中，
+        // 因此不仅不能匹配空字符串，任何匹配都至少要包含一个字符
+        let can_match_empty = regex_syntax::parse(&source)
+            .map_or(true, |hir| hir.properties().minimum_len() == Some(0));
+        if can_match_empty {
+            return Err(format!("表达式不能匹配空字符串: {}", source));
+        }
+        Ok(Some(regex))
+    }
+}
+
+/// 检查词的写法是否正确，用于加载词表和通过接口添加词时报告错误
+pub fn validate_word(word: &str) -> Result<(), String> {
+    WordPattern::parse(word)
+        .compile(&Normalizer::default(), DEFAULT_WILDCARD_MAX_GAP)
+        .map(|_| ())
+}
+
+/// 把通配符转为正则表达式
+fn wildcard_to_regex(
+    pattern: &str,
+    normalizer: &Normalizer,
+    max_gap: usize,
+) -> Result<String, String> {
+    let mut source = String::new();
+    let mut literal = String::new();
+    let mut has_literal = false;
+
+    let mut flush = |literal: &mut String, source: &mut String| {
+        let normalized = normalizer.normalize(literal).text;
+        has_literal |= !normalized.is_empty();
+        source.push_str(&regex::escape(&normalized));
+        literal.clear();
+    };
+
+    for c in pattern.chars() {
+        match c {
+            '*' => {
+                flush(&mut literal, &mut source);
+                source.push_str(&format!(".{{0,{}}}", max_gap));
+            }
+            '?' => {
+                flush(&mut literal, &mut source);
+                source.push('.');
+            }
+            c => literal.push(c),
+        }
+    }
+    flush(&mut literal, &mut source);
+
+    if !has_literal {
+        return Err("通配符中至少要包含一个普通字符".to_string());
+    }
+    Ok(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(word: &str) -> Result<Option<Regex>, String> {
+        WordPattern::parse(word).compile(&Normalizer::default(), DEFAULT_WILDCARD_MAX_GAP)
+    }
+
+    #[test]
+    fn test_parse_prefixes() {
+        assert_eq!(WordPattern::parse("犯罪"), WordPattern::Literal("犯罪"));
+        assert_eq!(WordPattern::parse(r"re:\d+"), WordPattern::Regex(r"\d+"));
+        assert_eq!(WordPattern::parse("wc:犯*罪"), WordPattern::Wildcard("犯*罪"));
+        assert!(compile("犯罪").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_wildcard_gap_is_bounded() {
+        let regex = compile("wc:犯*罪").unwrap().unwrap();
+        assert!(regex.is_match("犯罪"));
+        assert!(regex.is_match("犯了个罪"));
+        assert!(!regex.is_match("犯一二三四罪"));
+
+        let regex = compile("wc:犯?罪").unwrap().unwrap();
+        assert!(regex.is_match("犯了罪"));
+        assert!(!regex.is_match("犯罪"));
+    }
+
+    #[test]
+    fn test_wildcard_literals_are_normalized_and_escaped() {
+        let regex = compile("wc:犯*罪.A").unwrap().unwrap();
+        assert_eq!(regex.as_str(), "犯.{0,3}罪a");
+    }
+
+    #[test]
+    fn test_invalid_patterns_are_rejected() {
+        assert!(validate_word("re:(abc").is_err());
+        assert!(validate_word("re:a*").is_err());
+        assert!(validate_word("wc:**").is_err());
+        assert!(validate_word(r"re:qq\d{5,11}").is_ok());
+    }
+
+    #[test]
+    fn test_zero_width_patterns_are_rejected() {
+        // 只匹配位置的表达式不匹配空字符串本身，但会在普通文字中产生空的命中
+        assert!(!Regex::new(r"\b").unwrap().is_match(""));
+        assert!(validate_word(r"re:\b").is_err());
+        assert!(validate_word("re:(?m)^").is_err());
+        assert!(validate_word("re:(?=x)").is_err());
+        assert!(validate_word(r"re:\bqq\b|\b").is_err());
+        assert!(validate_word(r"re:\bqq\d+\b").is_ok());
+    }
+}
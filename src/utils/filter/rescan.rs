@@ -0,0 +1,666 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{current_filter, subscribe_reloads, FilterAction, SensitiveWordFilter};
+use crate::models::format::ContentFormat;
+use crate::models::kind::ContentKind;
+use crate::utils::moderation::ModerationPipeline;
+use crate::utils::references::{record_references, ReferenceTarget};
+use crate::utils::render::render_content;
+use crate::utils::sanitize::{policy_for, sanitize_html};
+use crate::utils::tags::record_tags;
+use crate::utils::tags::store::refresh_tag_counts;
+use crate::utils::text::html_to_text;
+
+/// 每批扫描的默认行数
+const DEFAULT_BATCH_SIZE: i64 = 200;
+
+/// 词表变化后等待多久再自动开始扫描，期间的多次变化只触发一次
+const RELOAD_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// 重新扫描时对命中内容的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RescanPolicy {
+    /// 只记录命中，不修改内容，由管理员查看后处理
+    Flag,
+    /// 按词表中的处理方式处理：mask 替换文字，hold 转人工审核，reject 直接隐藏
+    Apply,
+}
+
+impl RescanPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RescanPolicy::Flag => "flag",
+            RescanPolicy::Apply => "apply",
+        }
+    }
+
+    /// 从环境变量 `FILTER_RESCAN_POLICY` 读取默认策略，默认为 flag
+    pub fn from_env() -> Self {
+        env::var("FILTER_RESCAN_POLICY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(RescanPolicy::Flag)
+    }
+}
+
+impl fmt::Display for RescanPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RescanPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flag" => Ok(RescanPolicy::Flag),
+            "apply" => Ok(RescanPolicy::Apply),
+            other => Err(format!("未知的扫描策略: {}", other)),
+        }
+    }
+}
+
+/// 任务的发起方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RescanSource {
+    /// 管理员通过接口发起
+    Manual,
+    /// 词表变化后自动发起
+    Reload,
+}
+
+impl RescanSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            RescanSource::Manual => "manual",
+            RescanSource::Reload => "reload",
+        }
+    }
+}
+
+/// 启动任务时的错误
+#[derive(Debug, thiserror::Error)]
+pub enum StartRescanError {
+    #[error("已有正在运行的重新扫描任务 {0}")]
+    AlreadyRunning(Uuid),
+
+    #[error("{0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// 对一条内容的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowOutcome {
+    /// 只记录
+    Flagged,
+    /// 替换了文字
+    Masked,
+    /// 改为指定状态，不再公开显示
+    Hidden(&'static str),
+}
+
+impl RowOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RowOutcome::Flagged => "flagged",
+            RowOutcome::Masked => "masked",
+            RowOutcome::Hidden(_) => "hidden",
+        }
+    }
+}
+
+/// 按策略决定如何处理一条命中的内容
+fn decide(policy: RescanPolicy, action: FilterAction) -> RowOutcome {
+    match (policy, action) {
+        (RescanPolicy::Flag, _) => RowOutcome::Flagged,
+        (RescanPolicy::Apply, FilterAction::Mask) => RowOutcome::Masked,
+        (RescanPolicy::Apply, FilterAction::Hold) => RowOutcome::Hidden("held"),
+        (RescanPolicy::Apply, FilterAction::Reject) => RowOutcome::Hidden("rejected"),
+    }
+}
+
+/// 启动一个重新扫描任务
+///
+/// 同一时间只能有一个任务在运行；`supersede` 为 true 时停止正在运行的任务后再启动，
+/// 否则返回 [`StartRescanError::AlreadyRunning`]
+pub async fn start_rescan(
+    pool: &PgPool,
+    pipeline: &Arc<ModerationPipeline>,
+    policy: RescanPolicy,
+    source: RescanSource,
+    started_by: &str,
+    supersede: bool,
+) -> Result<Uuid, StartRescanError> {
+    let mut tx = pool.begin().await?;
+
+    let running: Option<Uuid> =
+        sqlx::query_scalar("SELECT id FROM rescan_jobs WHERE status = 'running' FOR UPDATE")
+            .fetch_optional(&mut *tx)
+            .await?;
+    if let Some(running) = running {
+        if !supersede {
+            return Err(StartRescanError::AlreadyRunning(running));
+        }
+        sqlx::query(
+            "UPDATE rescan_jobs SET status = 'superseded', updated_at = now(), finished_at = now() WHERE id = $1",
+        )
+        .bind(running)
+        .execute(&mut *tx)
+        .await?;
+        tracing::info!("重新扫描任务 {} 被新任务取代", running);
+    }
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT (SELECT COUNT(*) FROM posts WHERE status = 'published')
+             + (SELECT COUNT(*) FROM comments WHERE status = 'published')
+        "#,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO rescan_jobs (policy, source, started_by, total)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(policy.as_str())
+    .bind(source.as_str())
+    .bind(started_by)
+    .bind(total)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "{} 启动重新扫描任务 {}，策略 {}，共 {} 条内容",
+        started_by,
+        id,
+        policy,
+        total
+    );
+    spawn_job(pool.clone(), Arc::clone(pipeline), id);
+    Ok(id)
+}
+
+/// 继续执行服务重启前没有完成的任务
+pub async fn resume_rescans(
+    pool: &PgPool,
+    pipeline: &Arc<ModerationPipeline>,
+) -> Result<(), sqlx::Error> {
+    let running: Vec<Uuid> =
+        sqlx::query_scalar("SELECT id FROM rescan_jobs WHERE status = 'running'")
+            .fetch_all(pool)
+            .await?;
+    for id in running {
+        tracing::info!("继续执行重新扫描任务 {}", id);
+        spawn_job(pool.clone(), Arc::clone(pipeline), id);
+    }
+    Ok(())
+}
+
+/// 词表变化后自动启动重新扫描
+///
+/// 由环境变量 `FILTER_RESCAN_ON_RELOAD=true` 开启。词表在短时间内多次变化时只扫描一次，
+/// 正在运行的任务会被新任务取代
+pub fn spawn_rescan_on_reload(pool: PgPool, pipeline: Arc<ModerationPipeline>) {
+    let enabled = env::var("FILTER_RESCAN_ON_RELOAD").is_ok_and(|v| v == "true" || v == "1");
+    if !enabled {
+        return;
+    }
+
+    let mut reloads = subscribe_reloads();
+    tokio::spawn(async move {
+        while reloads.changed().await.is_ok() {
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            reloads.borrow_and_update();
+            let policy = RescanPolicy::from_env();
+            let started = start_rescan(
+                &pool,
+                &pipeline,
+                policy,
+                RescanSource::Reload,
+                "system",
+                true,
+            );
+            if let Err(e) = started.await {
+                tracing::warn!("词表变化后无法启动重新扫描: {}", e);
+            }
+        }
+    });
+}
+
+fn spawn_job(pool: PgPool, pipeline: Arc<ModerationPipeline>, id: Uuid) {
+    tokio::spawn(async move {
+        if let Err(e) = run_job(&pool, &pipeline, id).await {
+            tracing::error!("重新扫描任务 {} 失败: {}", id, e);
+            let _ = sqlx::query(
+                r#"
+                UPDATE rescan_jobs
+                SET status = 'failed', error = $2, updated_at = now(), finished_at = now()
+                WHERE id = $1 AND status = 'running'
+                "#,
+            )
+            .bind(id)
+            .bind(e.to_string())
+            .execute(&pool)
+            .await;
+        }
+    });
+}
+
+/// 任务的当前进度
+struct JobState {
+    policy: RescanPolicy,
+    phase: String,
+    cursor: Option<(OffsetDateTime, Uuid)>,
+}
+
+async fn load_state(pool: &PgPool, id: Uuid) -> Result<Option<JobState>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT policy, phase, cursor_created_at, cursor_id
+        FROM rescan_jobs
+        WHERE id = $1 AND status = 'running'
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let policy: String = row.try_get("policy")?;
+    let cursor_created_at: Option<OffsetDateTime> = row.try_get("cursor_created_at")?;
+    let cursor_id: Option<Uuid> = row.try_get("cursor_id")?;
+    Ok(Some(JobState {
+        policy: policy.parse().unwrap_or(RescanPolicy::Flag),
+        phase: row.try_get("phase")?,
+        cursor: cursor_created_at.zip(cursor_id),
+    }))
+}
+
+/// 分批扫描，直到完成或任务不再处于运行状态
+async fn run_job(
+    pool: &PgPool,
+    pipeline: &ModerationPipeline,
+    id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let batch_size = env::var("FILTER_RESCAN_BATCH_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_BATCH_SIZE);
+
+    while let Some(state) = load_state(pool, id).await? {
+        let (kind, next_phase) = match state.phase.as_str() {
+            "posts" => (ContentKind::Post, "comments"),
+            "comments" => (ContentKind::Comment, "done"),
+            _ => {
+                sqlx::query(
+                    r#"
+                    UPDATE rescan_jobs
+                    SET status = 'completed', updated_at = now(), finished_at = now()
+                    WHERE id = $1 AND status = 'running'
+                    "#,
+                )
+                .bind(id)
+                .execute(pool)
+                .await?;
+                tracing::info!("重新扫描任务 {} 已完成", id);
+                return Ok(());
+            }
+        };
+
+        let filter = current_filter();
+        let batch = Batch {
+            id,
+            policy: state.policy,
+            kind,
+            next_phase,
+            cursor: state.cursor,
+            batch_size,
+        };
+        if !batch.run(pool, pipeline, &filter).await? {
+            tracing::info!("重新扫描任务 {} 已停止", id);
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// 一批内容的扫描
+struct Batch {
+    id: Uuid,
+    policy: RescanPolicy,
+    kind: ContentKind,
+    next_phase: &'static str,
+    cursor: Option<(OffsetDateTime, Uuid)>,
+    batch_size: i64,
+}
+
+/// 扫描的一条内容
+struct ScannedRow {
+    id: Uuid,
+    /// 保存的显示内容
+    content: String,
+    /// 用户提交的原文，保存原文之前创建的内容为 None
+    raw: Option<String>,
+    format: ContentFormat,
+    /// 评论所属的帖子，用于解析楼层引用
+    thread: Option<Uuid>,
+}
+
+/// 替换文字后重新生成的内容，事务提交后据此重新记录引用和话题
+struct Rerendered {
+    id: Uuid,
+    /// 内容中的引用；从显示内容替换的旧内容无法重新解析，为 None
+    references: Option<Vec<ReferenceTarget>>,
+    /// 提取话题用的原文、审核后的文本和格式
+    raw: String,
+    moderated: String,
+    format: ContentFormat,
+}
+
+/// 一批扫描的计数
+#[derive(Default)]
+struct BatchCounts {
+    scanned: i64,
+    matched: i64,
+    flagged: i64,
+    masked: i64,
+    hidden: i64,
+}
+
+impl Batch {
+    /// 扫描一批内容并在同一个事务中保存进度，保证重启后从上次提交的位置继续
+    ///
+    /// # Returns
+    /// * 任务是否仍在运行
+    async fn run(
+        &self,
+        pool: &PgPool,
+        pipeline: &ModerationPipeline,
+        filter: &SensitiveWordFilter,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // 评论的楼层引用在所属的帖子下查找
+        let thread = match self.kind {
+            ContentKind::Post => "NULL::uuid",
+            ContentKind::Comment => "post_id",
+        };
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT id, created_at, content, raw_content, content_format, {} AS thread FROM {}
+            WHERE status = 'published'
+              AND ($1::TIMESTAMPTZ IS NULL OR (created_at, id) > ($1, $2))
+            ORDER BY created_at, id
+            LIMIT $3
+            "#,
+            thread,
+            self.kind.table()
+        ))
+        .bind(self.cursor.map(|(created_at, _)| created_at))
+        .bind(self.cursor.map(|(_, id)| id))
+        .bind(self.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut counts = BatchCounts::default();
+        let mut cursor = None;
+        let mut rerendered = Vec::new();
+        for row in &rows {
+            let created_at: OffsetDateTime = row.try_get("created_at")?;
+            let scanned = ScannedRow {
+                id: row.try_get("id")?,
+                content: row.try_get("content")?,
+                raw: row.try_get("raw_content")?,
+                format: ContentFormat::from_db(row.try_get("content_format")?),
+                thread: row.try_get("thread")?,
+            };
+            let id = scanned.id;
+            cursor = Some((created_at, id));
+            counts.scanned += 1;
+
+            // 与发布时一样检查原文；保存原文之前创建的内容只能检查显示内容
+            let verdict = filter.check(scanned.raw.as_deref().unwrap_or(&scanned.content));
+            let Some(action) = verdict.action else {
+                continue;
+            };
+            counts.matched += 1;
+            let outcome = decide(self.policy, action);
+            match outcome {
+                RowOutcome::Flagged => counts.flagged += 1,
+                RowOutcome::Masked => {
+                    counts.masked += 1;
+                    let masked = match scanned.raw {
+                        Some(_) => self.rerender(&mut tx, pool, pipeline, scanned).await?,
+                        None => {
+                            self.mask_legacy(&mut tx, id, &verdict.content, filter.version())
+                                .await?
+                        }
+                    };
+                    rerendered.push(masked);
+                }
+                RowOutcome::Hidden(status) => {
+                    counts.hidden += 1;
+                    self.hide(&mut tx, id, status).await?;
+                }
+            }
+
+            let mut words: Vec<&str> = Vec::new();
+            for hit in &verdict.hits {
+                if !words.contains(&hit.word.as_str()) {
+                    words.push(&hit.word);
+                }
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO rescan_matches (job_id, content_type, content_id, action, outcome, categories, words)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(self.id)
+            .bind(self.kind.as_str())
+            .bind(id)
+            .bind(action.as_str())
+            .bind(outcome.as_str())
+            .bind(verdict.categories.join(", "))
+            .bind(words.join(", "))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // 不满一批说明本表已经扫描完毕，进入下一阶段并清空游标
+        let (phase, cursor) = if rows.len() as i64 == self.batch_size {
+            (None, cursor)
+        } else {
+            (Some(self.next_phase), None)
+        };
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE rescan_jobs
+            SET phase = COALESCE($2, phase),
+                cursor_created_at = $3,
+                cursor_id = $4,
+                scanned = scanned + $5,
+                matched = matched + $6,
+                flagged = flagged + $7,
+                masked = masked + $8,
+                hidden = hidden + $9,
+                updated_at = now()
+            WHERE id = $1 AND status = 'running'
+            "#,
+        )
+        .bind(self.id)
+        .bind(phase)
+        .bind(cursor.map(|(created_at, _)| created_at))
+        .bind(cursor.map(|(_, id)| id))
+        .bind(counts.scanned)
+        .bind(counts.matched)
+        .bind(counts.flagged)
+        .bind(counts.masked)
+        .bind(counts.hidden)
+        .execute(&mut *tx)
+        .await?;
+
+        // 任务已被取代：放弃这一批的修改
+        if updated.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+        tx.commit().await?;
+
+        // 替换后的内容可能少了引用或话题，与重新生成接口一样重新记录
+        for content in rerendered {
+            if let Some(references) = &content.references {
+                record_references(pool, self.kind, content.id, references).await;
+            }
+            if self.kind == ContentKind::Post {
+                record_tags(
+                    pool,
+                    content.id,
+                    &content.raw,
+                    &content.moderated,
+                    content.format,
+                )
+                .await;
+            }
+        }
+        Ok(true)
+    }
+
+    /// 用当前的词表和净化策略从原文重新生成显示内容，与重新生成接口的处理相同
+    async fn rerender(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        pool: &PgPool,
+        pipeline: &ModerationPipeline,
+        row: ScannedRow,
+    ) -> Result<Rerendered, sqlx::Error> {
+        let raw = row.raw.unwrap_or_default();
+        let rendered =
+            render_content(pool, pipeline, &raw, row.format, self.kind, row.thread).await;
+        sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET content = $2, filter_version = $3, sanitizer_policy = $4, sanitizer_version = $5,
+                content_text = $6
+            WHERE id = $1
+            "#,
+            self.kind.table()
+        ))
+        .bind(row.id)
+        .bind(&rendered.html)
+        .bind(&rendered.filter_version)
+        .bind(rendered.sanitizer_policy)
+        .bind(rendered.sanitizer_version)
+        .bind(&rendered.text)
+        .execute(&mut **tx)
+        .await?;
+        Ok(Rerendered {
+            id: row.id,
+            references: Some(rendered.references),
+            raw,
+            moderated: rendered.outcome.content,
+            format: row.format,
+        })
+    }
+
+    /// 替换没有原文的旧内容中的敏感词
+    ///
+    /// 替换直接作用在显示内容上，可能落在属性值中，因此替换后按当前策略重新净化
+    async fn mask_legacy(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+        masked: &str,
+        filter_version: &str,
+    ) -> Result<Rerendered, sqlx::Error> {
+        let policy = policy_for(self.kind);
+        let html = sanitize_html(masked, policy);
+        sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET content = $2, filter_version = $3, sanitizer_policy = $4, sanitizer_version = $5,
+                content_text = $6
+            WHERE id = $1
+            "#,
+            self.kind.table()
+        ))
+        .bind(id)
+        .bind(&html)
+        .bind(filter_version)
+        .bind(policy.name())
+        .bind(policy.version())
+        .bind(html_to_text(&html))
+        .execute(&mut **tx)
+        .await?;
+        Ok(Rerendered {
+            id,
+            references: None,
+            raw: html.clone(),
+            moderated: html,
+            format: ContentFormat::Html,
+        })
+    }
+
+    /// 把内容改为不公开的状态
+    async fn hide(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        id: Uuid,
+        status: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "UPDATE {} SET status = $2 WHERE id = $1",
+            self.kind.table()
+        ))
+        .bind(id)
+        .bind(status)
+        .execute(&mut **tx)
+        .await?;
+        // 话题只统计已公开的帖子
+        if self.kind == ContentKind::Post {
+            refresh_tag_counts(&mut **tx, &[id], &[]).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::filter::parse_word_list;
+
+    #[test]
+    fn test_policy_decides_row_outcome() {
+        let filter = SensitiveWordFilter::new(
+            parse_word_list("[暴力相关]\n犯罪\n[违禁品:reject]\n毒品\n[待审:hold]\n某词\n").unwrap(),
+        );
+
+        let action = filter.check("他犯罪了").action.unwrap();
+        assert_eq!(decide(RescanPolicy::Flag, action), RowOutcome::Flagged);
+        assert_eq!(decide(RescanPolicy::Apply, action), RowOutcome::Masked);
+
+        let action = filter.check("某词").action.unwrap();
+        assert_eq!(decide(RescanPolicy::Apply, action), RowOutcome::Hidden("held"));
+
+        let action = filter.check("犯罪和毒品").action.unwrap();
+        assert_eq!(decide(RescanPolicy::Apply, action), RowOutcome::Hidden("rejected"));
+    }
+}
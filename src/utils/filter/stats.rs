@@ -0,0 +1,175 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+use std::time::Duration;
+use time::{Date, OffsetDateTime};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::FilterHit;
+
+/// 等待写入的命中记录的最大数量，队列满时丢弃新的记录而不是阻塞请求
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// 默认每隔多少秒把累计的命中次数写入数据库
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 后台统计任务的发送端，任务启动前为空，此时命中不会被统计
+static HIT_SENDER: OnceLock<mpsc::Sender<(Date, FilterHit)>> = OnceLock::new();
+
+/// 记录一批命中
+///
+/// 只把记录放入队列，不等待数据库写入；统计任务未启动或队列已满时直接丢弃
+pub fn record_hits<I>(hits: I)
+where
+    I: IntoIterator<Item = FilterHit>,
+{
+    let Some(sender) = HIT_SENDER.get() else {
+        return;
+    };
+    // 按 UTC 日期统计，查询统计时也按 UTC 计算今天
+    let today = OffsetDateTime::now_utc().date();
+    for hit in hits {
+        if let Err(e) = sender.try_send((today, hit)) {
+            tracing::debug!("丢弃敏感词命中记录: {}", e);
+            return;
+        }
+    }
+}
+
+/// 启动后台统计任务
+///
+/// 任务在内存中按天、词和分类累计命中次数，每隔一段时间（环境变量
+/// `FILTER_STATS_FLUSH_SECS`，默认 10 秒）合并写入 `filter_hit_stats` 表。
+/// 写入失败时保留累计的次数，下次再试。
+pub fn start_hit_recorder(pool: PgPool) -> JoinHandle<()> {
+    let interval = env::var("FILTER_STATS_FLUSH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_FLUSH_INTERVAL);
+
+    let (sender, mut receiver) = mpsc::channel(QUEUE_CAPACITY);
+    if HIT_SENDER.set(sender).is_err() {
+        tracing::warn!("敏感词命中统计任务已经启动");
+    }
+
+    tokio::spawn(async move {
+        let mut buffer = HitBuffer::default();
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                received = receiver.recv() => match received {
+                    Some((day, hit)) => buffer.add(day, hit),
+                    None => break,
+                },
+                _ = ticker.tick() => flush(&pool, &mut buffer).await,
+            }
+        }
+        flush(&pool, &mut buffer).await;
+    })
+}
+
+/// 把累计的命中次数写入数据库
+async fn flush(pool: &PgPool, buffer: &mut HitBuffer) {
+    if buffer.is_empty() {
+        return;
+    }
+    let counts = buffer.take();
+
+    let mut days = Vec::with_capacity(counts.len());
+    let mut words = Vec::with_capacity(counts.len());
+    let mut categories = Vec::with_capacity(counts.len());
+    let mut hits = Vec::with_capacity(counts.len());
+    for ((day, hit), count) in &counts {
+        days.push(*day);
+        words.push(hit.word.as_str());
+        categories.push(hit.category.as_str());
+        hits.push(*count);
+    }
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO filter_hit_stats (day, word, category, hits)
+        SELECT * FROM UNNEST($1::DATE[], $2::TEXT[], $3::TEXT[], $4::BIGINT[])
+        ON CONFLICT (day, word, category)
+        DO UPDATE SET hits = filter_hit_stats.hits + EXCLUDED.hits
+        "#,
+    )
+    .bind(&days)
+    .bind(&words)
+    .bind(&categories)
+    .bind(&hits)
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => tracing::debug!("写入 {} 条敏感词命中统计", counts.len()),
+        Err(e) => {
+            tracing::warn!("写入敏感词命中统计失败，稍后重试: {}", e);
+            buffer.merge(counts);
+        }
+    }
+}
+
+/// 内存中按天、词和分类累计的命中次数
+#[derive(Debug, Default)]
+struct HitBuffer {
+    counts: HashMap<(Date, FilterHit), i64>,
+}
+
+impl HitBuffer {
+    fn add(&mut self, day: Date, hit: FilterHit) {
+        *self.counts.entry((day, hit)).or_insert(0) += 1;
+    }
+
+    fn merge(&mut self, counts: HashMap<(Date, FilterHit), i64>) {
+        for (key, count) in counts {
+            *self.counts.entry(key).or_insert(0) += count;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    fn take(&mut self) -> HashMap<(Date, FilterHit), i64> {
+        std::mem::take(&mut self.counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn hit(word: &str, category: &str) -> FilterHit {
+        FilterHit {
+            word: word.to_string(),
+            category: category.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hit_buffer_aggregates_per_day_word_and_category() {
+        let day = Date::from_calendar_date(2024, Month::July, 1).unwrap();
+        let next_day = day.next_day().unwrap();
+
+        let mut buffer = HitBuffer::default();
+        buffer.add(day, hit("犯罪", "暴力相关"));
+        buffer.add(day, hit("犯罪", "暴力相关"));
+        buffer.add(day, hit("犯罪", "其他"));
+        buffer.add(next_day, hit("犯罪", "暴力相关"));
+
+        let counts = buffer.take();
+        assert!(buffer.is_empty());
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&(day, hit("犯罪", "暴力相关"))], 2);
+
+        // 写入失败时放回的次数与之后的新记录合并
+        buffer.add(day, hit("犯罪", "暴力相关"));
+        buffer.merge(counts);
+        assert_eq!(buffer.take()[&(day, hit("犯罪", "暴力相关"))], 3);
+    }
+}
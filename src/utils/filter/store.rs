@@ -0,0 +1,55 @@
+use sqlx::{PgPool, Row};
+
+use super::{set_database_words, WordEntry};
+
+/// 读取数据库中的敏感词，按分类和词排序
+pub async fn load_database_words(pool: &PgPool) -> Result<Vec<WordEntry>, sqlx::Error> {
+    let rows =
+        sqlx::query("SELECT word, category, action FROM sensitive_words ORDER BY category, word")
+            .fetch_all(pool)
+            .await?;
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for row in rows {
+        let word: String = row.try_get("word")?;
+        let action: String = row.try_get("action")?;
+        let action = match action.parse() {
+            Ok(action) => action,
+            Err(e) => {
+                tracing::warn!("跳过敏感词 {}: {}", word, e);
+                continue;
+            }
+        };
+        entries.push(WordEntry {
+            word,
+            category: row.try_get("category")?,
+            action,
+            language: None,
+        });
+    }
+    Ok(entries)
+}
+
+/// 读取数据库中的白名单短语
+pub async fn load_database_allowlist(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT phrase FROM filter_allowlist ORDER BY phrase")
+        .fetch_all(pool)
+        .await
+}
+
+/// 从数据库加载敏感词和白名单并重新编译过滤器
+///
+/// 启动时以及每次通过管理接口修改数据库中的词或白名单后调用，使改动立即生效
+///
+/// # Returns
+/// * 合并文件中的词后的敏感词数量，或者数据库错误
+pub async fn reload_database_words(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let entries = load_database_words(pool).await?;
+    let allowlist = load_database_allowlist(pool).await?;
+    tracing::debug!(
+        "从数据库加载 {} 个敏感词和 {} 个白名单短语",
+        entries.len(),
+        allowlist.len()
+    );
+    Ok(set_database_words(entries, allowlist))
+}
@@ -0,0 +1,253 @@
+use pinyin::ToPinyin;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+/// 每个词最多生成的同音字变体数量，防止组合爆炸
+const MAX_HOMOPHONE_VARIANTS: usize = 64;
+
+/// 生成首字母变体默认所需的最少汉字数
+///
+/// 两个字母的首字母（如 `gj`、`zz`、`bd`）常见于普通英文缩写和标识符，默认不生成
+const DEFAULT_INITIALS_MIN_CHARS: usize = 3;
+
+/// 命中的是词的哪种写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchVariant {
+    /// 原词
+    Literal,
+    /// 同音字替换，如 饭醉
+    Homophone,
+    /// 全拼，如 fanzui
+    Pinyin,
+    /// 拼音首字母，如 fz
+    Initials,
+    /// 正则表达式或通配符词条
+    Pattern,
+}
+
+impl MatchVariant {
+    /// 拼音类变体由拉丁字母组成，只在不与其他字母数字相连时才算命中，
+    /// 避免 fz 这样的首字母命中普通英文单词的一部分
+    pub fn requires_word_boundary(self) -> bool {
+        matches!(self, MatchVariant::Pinyin | MatchVariant::Initials)
+    }
+}
+
+/// 变体生成规则
+#[derive(Debug, Clone)]
+pub struct VariantOptions {
+    /// 是否生成全拼变体
+    pub pinyin: bool,
+    /// 是否生成首字母变体
+    pub initials: bool,
+    /// 生成首字母变体所需的最少汉字数
+    pub initials_min_chars: usize,
+    /// 同音字表：字 -> 与它同音的其他字
+    pub homophones: HashMap<char, Vec<char>>,
+}
+
+impl Default for VariantOptions {
+    fn default() -> Self {
+        Self {
+            pinyin: true,
+            initials: true,
+            initials_min_chars: DEFAULT_INITIALS_MIN_CHARS,
+            homophones: HashMap::new(),
+        }
+    }
+}
+
+impl VariantOptions {
+    /// 从环境变量读取变体规则
+    ///
+    /// * `FILTER_VARIANTS` - 启用的变体，逗号分隔，默认 `pinyin,initials,homophone`
+    /// * `FILTER_INITIALS_MIN_CHARS` - 生成首字母变体所需的最少汉字数，默认 3
+    /// * `FILTER_HOMOPHONES_PATH` - 同音字表路径，默认 `homophones.txt`
+    pub fn from_env() -> Self {
+        let enabled = env::var("FILTER_VARIANTS")
+            .unwrap_or_else(|_| "pinyin,initials,homophone".to_string());
+        let enabled: HashSet<&str> = enabled.split(',').map(str::trim).collect();
+
+        let initials_min_chars = env::var("FILTER_INITIALS_MIN_CHARS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_INITIALS_MIN_CHARS);
+
+        let homophones = if enabled.contains("homophone") {
+            let path = env::var("FILTER_HOMOPHONES_PATH")
+                .unwrap_or_else(|_| "homophones.txt".to_string());
+            load_homophones(Path::new(&path)).unwrap_or_else(|e| {
+                tracing::warn!("无法加载同音字表 {}: {}", path, e);
+                HashMap::new()
+            })
+        } else {
+            HashMap::new()
+        };
+
+        Self {
+            pinyin: enabled.contains("pinyin"),
+            initials: enabled.contains("initials"),
+            initials_min_chars,
+            homophones,
+        }
+    }
+
+    /// 为规范化后的词生成指定类型的变体（不含原词本身）
+    pub fn generate(&self, word: &str, variant: MatchVariant) -> Vec<String> {
+        match variant {
+            MatchVariant::Literal => vec![word.to_string()],
+            MatchVariant::Homophone => self.homophone_variants(word),
+            MatchVariant::Pinyin if self.pinyin => to_pinyin(word, false).into_iter().collect(),
+            MatchVariant::Initials if self.initials => {
+                let han_count = word.chars().filter(|c| c.to_pinyin().is_some()).count();
+                if han_count >= self.initials_min_chars {
+                    to_pinyin(word, true).into_iter().collect()
+                } else {
+                    Vec::new()
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn homophone_variants(&self, word: &str) -> Vec<String> {
+        let options: Vec<Vec<char>> = word
+            .chars()
+            .map(|c| {
+                let mut choices = vec![c];
+                if let Some(alternatives) = self.homophones.get(&c) {
+                    choices.extend(alternatives);
+                }
+                choices
+            })
+            .collect();
+
+        // 按里程表顺序枚举所有组合，跳过全部取原字的第一个组合
+        let mut variants = Vec::new();
+        let mut indices = vec![0; options.len()];
+        loop {
+            let mut position = indices.len();
+            while position > 0 {
+                position -= 1;
+                indices[position] += 1;
+                if indices[position] < options[position].len() {
+                    break;
+                }
+                indices[position] = 0;
+            }
+            if indices.iter().all(|&i| i == 0) || variants.len() >= MAX_HOMOPHONE_VARIANTS {
+                break;
+            }
+            variants.push(
+                indices
+                    .iter()
+                    .zip(&options)
+                    .map(|(&i, choices)| choices[i])
+                    .collect(),
+            );
+        }
+        variants
+    }
+}
+
+/// 把词转为全拼或首字母，不含汉字的词返回 None
+fn to_pinyin(word: &str, initials_only: bool) -> Option<String> {
+    let mut result = String::new();
+    let mut has_han = false;
+    for c in word.chars() {
+        match c.to_pinyin() {
+            Some(p) => {
+                has_han = true;
+                result.push_str(if initials_only { p.first_letter() } else { p.plain() });
+            }
+            None => result.push(c),
+        }
+    }
+    has_han.then_some(result)
+}
+
+/// 从文件加载同音字表
+///
+/// 每行一组同音字，用空白分隔，以 # 开头的行为注释，例如：
+///
+/// ```text
+/// 犯 饭 范 泛
+/// 罪 醉 最
+/// ```
+pub fn load_homophones(path: &Path) -> io::Result<HashMap<char, Vec<char>>> {
+    let reader = io::BufReader::new(File::open(path)?);
+    let mut homophones: HashMap<char, Vec<char>> = HashMap::new();
+    for line in reader.lines() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let group: Vec<char> = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+        for &c in &group {
+            let entry = homophones.entry(c).or_default();
+            for &other in &group {
+                if other != c && !entry.contains(&other) {
+                    entry.push(other);
+                }
+            }
+        }
+    }
+    Ok(homophones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options_with_homophones() -> VariantOptions {
+        let mut homophones = HashMap::new();
+        homophones.insert('犯', vec!['饭', '范']);
+        homophones.insert('罪', vec!['醉']);
+        VariantOptions {
+            homophones,
+            ..VariantOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_pinyin_variants() {
+        let options = VariantOptions::default();
+        assert_eq!(options.generate("犯罪", MatchVariant::Pinyin), vec!["fanzui"]);
+        assert_eq!(options.generate("习近平", MatchVariant::Initials), vec!["xjp"]);
+        assert!(options.generate("犯罪", MatchVariant::Initials).is_empty());
+        assert!(options.generate("badword", MatchVariant::Pinyin).is_empty());
+
+        // 两个字的词需要显式开启
+        let options = VariantOptions {
+            initials_min_chars: 2,
+            ..VariantOptions::default()
+        };
+        assert_eq!(options.generate("犯罪", MatchVariant::Initials), vec!["fz"]);
+        assert!(options.generate("罪", MatchVariant::Initials).is_empty());
+    }
+
+    #[test]
+    fn test_homophone_variants() {
+        let options = options_with_homophones();
+        let variants = options.generate("犯罪", MatchVariant::Homophone);
+        assert_eq!(variants, vec!["犯醉", "饭罪", "饭醉", "范罪", "范醉"]);
+        assert!(options.generate("暴力", MatchVariant::Homophone).is_empty());
+    }
+
+    #[test]
+    fn test_disabled_variants() {
+        let options = VariantOptions {
+            pinyin: false,
+            initials: false,
+            ..VariantOptions::default()
+        };
+        assert!(options.generate("犯罪", MatchVariant::Pinyin).is_empty());
+        assert!(options.generate("犯罪", MatchVariant::Initials).is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use notify_debouncer_mini::notify::{self, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{allowlist_path, reload_allowlist, reload_word_list, word_list_path, WordList};
+
+/// 默认的防抖时间：编辑器保存文件时往往会连续触发多个事件
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 文件监听器，被丢弃时停止监听
+pub struct FilterWatcher {
+    _debouncers: Vec<Debouncer<RecommendedWatcher>>,
+}
+
+/// 监听词表目录中的各个词表文件和白名单文件，文件变化后只重新加载变化的那个文件
+///
+/// 文件路径见 [`word_list_path`] 和 [`allowlist_path`]，防抖时间由环境变量 `FILTER_WATCH_DEBOUNCE_MS` 指定，
+/// 默认 500 毫秒。新文件解析失败时保留该文件的旧内容并记录警告，其他文件不受影响。
+///
+/// # Returns
+/// * 监听器，需要在服务运行期间一直持有
+pub fn watch_sensitive_words() -> notify::Result<FilterWatcher> {
+    let debounce = env::var("FILTER_WATCH_DEBOUNCE_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_DEBOUNCE);
+
+    let mut debouncers = Vec::new();
+    for list in WordList::ALL {
+        let path = word_list_path(list);
+        tracing::info!("开始监听敏感词列表文件 {:?}", path);
+        debouncers.push(watch_file(&path, debounce, move || match reload_word_list(list) {
+            Ok(count) => tracing::info!("敏感词列表文件 {} 已变化，重新加载后共 {} 个词", list, count),
+            Err(err) => tracing::warn!("敏感词列表文件已变化，但无法加载，继续使用旧词表: {}", err),
+        })?);
+    }
+
+    let path = allowlist_path();
+    tracing::info!("开始监听白名单文件 {:?}", path);
+    debouncers.push(watch_file(&path, debounce, || match reload_allowlist() {
+        Ok(_) => tracing::info!("白名单文件已变化，已重新加载"),
+        Err(err) => tracing::warn!("白名单文件已变化，但无法加载，继续使用旧白名单: {}", err),
+    })?);
+
+    Ok(FilterWatcher {
+        _debouncers: debouncers,
+    })
+}
+
+/// 监听单个文件，文件变化并经过防抖后调用 `on_change`
+///
+/// 监听的是文件所在的目录而不是文件本身：很多编辑器保存时会先写临时文件再重命名，
+/// 直接监听文件会在第一次保存后失效。
+fn watch_file<F>(
+    path: &Path,
+    debounce: Duration,
+    mut on_change: F,
+) -> notify::Result<Debouncer<RecommendedWatcher>>
+where
+    F: FnMut() + Send + 'static,
+{
+    let (directory, file_name) = split_path(path)?;
+
+    let mut debouncer = new_debouncer(debounce, move |result: DebounceEventResult| match result {
+        Ok(events) => {
+            if events
+                .iter()
+                .any(|event| event.path.file_name() == Some(file_name.as_os_str()))
+            {
+                on_change();
+            }
+        }
+        Err(err) => tracing::warn!("监听敏感词列表文件出错: {}", err),
+    })?;
+    debouncer
+        .watcher()
+        .watch(&directory, RecursiveMode::NonRecursive)?;
+
+    Ok(debouncer)
+}
+
+/// 把文件路径拆分为所在目录和文件名，相对路径按当前工作目录解析
+fn split_path(path: &Path) -> notify::Result<(PathBuf, OsString)> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| notify::Error::generic(&format!("无效的文件路径: {:?}", path)))?
+        .to_os_string();
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let directory = directory.canonicalize().map_err(notify::Error::io)?;
+    Ok((directory, file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_watch_file_reports_changes_to_the_watched_file_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("filter.txt");
+        fs::write(&path, "政治\n").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let _watcher = watch_file(&path, Duration::from_millis(50), move || {
+            let _ = tx.send(());
+        })
+        .unwrap();
+
+        fs::write(dir.path().join("other.txt"), "无关\n").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        // 模拟编辑器先写临时文件再重命名的保存方式
+        let temp = dir.path().join(".filter.txt.swp");
+        fs::write(&temp, "政治\n犯罪\n").unwrap();
+        fs::rename(&temp, &path).unwrap();
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+}
@@ -0,0 +1,291 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::{Arc, RwLock};
+use url::{Host, Url};
+
+pub mod preview;
+pub mod store;
+
+lazy_static! {
+    /// 当前生效的域名黑名单
+    static ref BLOCKLIST: RwLock<Arc<DomainBlocklist>> = RwLock::new(Arc::new(DomainBlocklist::default()));
+
+    /// a 标签的开始标签
+    static ref ANCHOR_TAG: Regex = Regex::new(r#"(?is)<a\b[^>]*>"#).unwrap();
+
+    /// 开始标签中的 href 属性，值可以带双引号、单引号或不带引号
+    static ref HREF_ATTRIBUTE: Regex =
+        Regex::new(r#"(?is)\bhref\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap();
+
+    /// 文本中的地址：带协议或以 www. 开头的地址，以及只写出域名的地址
+    ///
+    /// 中文里的地址常常与前后的文字连在一起，因此路径部分遇到汉字即结束；
+    /// 带协议的地址允许汉字域名，只写出域名的地址只识别 ASCII 域名
+    static ref BARE_URL: Regex = Regex::new(concat!(
+        r#"(?i)(?:[a-z][a-z0-9+.-]*://|www[.。．｡])"#,
+        r#"(?:[\p{L}\p{N}-]+[.。．｡])*(?:[a-z0-9-]+|\p{Han}+)(?::\d+)?"#,
+        r#"(?:[/?#][^\s<>"'\p{Han}]*)?"#,
+        r#"|(?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?[.。．｡])+[a-z]{2,}(?:/[^\s<>"'\p{Han}]*)?"#,
+    ))
+    .unwrap();
+}
+
+/// 地址末尾通常属于句子而不是地址的标点
+const TRAILING_PUNCTUATION: &[char] = &[
+    '.', ',', ';', ':', '!', '?', ')', ']', '}', '。', '，', '；', '：', '！', '？', '）', '、',
+];
+
+/// 内容中的一个链接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// 原文中的地址
+    pub url: String,
+    /// 规范化后的主机名（小写、punycode），无法解析出主机名时为 None，例如相对地址
+    pub host: Option<String>,
+    /// 移除链接时删去的原文区间：a 标签为整个开始标签，文本中的地址为地址本身
+    pub span: Range<usize>,
+    /// 是否明确写成了链接（a 标签、带协议或以 www. 开头）；只写出域名的地址不计入数量限制
+    pub explicit: bool,
+}
+
+/// 提取内容中的链接，包括 a 标签的 href 和文本中的地址
+///
+/// # Returns
+/// * 按出现顺序排列、区间互不重叠的链接
+pub fn extract_links(content: &str) -> Vec<Link> {
+    let mut links: Vec<Link> = ANCHOR_TAG
+        .find_iter(content)
+        .filter_map(|tag| {
+            let captures = HREF_ATTRIBUTE.captures(tag.as_str())?;
+            let url = captures
+                .iter()
+                .skip(1)
+                .flatten()
+                .next()?
+                .as_str()
+                .trim()
+                .to_string();
+            Some(Link {
+                host: parse_host(&url),
+                url,
+                span: tag.range(),
+                explicit: true,
+            })
+        })
+        .collect();
+
+    let mut bare = Vec::new();
+    for m in BARE_URL.find_iter(content) {
+        // a 标签中的地址已经作为标签的一部分提取过
+        if links.iter().any(|link| link.span.contains(&m.start())) {
+            continue;
+        }
+        let url = m.as_str().trim_end_matches(TRAILING_PUNCTUATION);
+        if url.is_empty() {
+            continue;
+        }
+        let explicit =
+            url.contains("://") || url.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("www"));
+        bare.push(Link {
+            host: parse_host(url),
+            url: url.to_string(),
+            span: m.start()..m.start() + url.len(),
+            explicit,
+        });
+    }
+
+    links.extend(bare);
+    links.sort_by_key(|link| link.span.start);
+    links
+}
+
+/// 解析地址中的主机名
+///
+/// 没有协议的地址按 http 处理，以 `/` 开头的相对地址没有主机名；mailto 地址取 @ 之后的域名
+fn parse_host(url: &str) -> Option<String> {
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) if url.starts_with("//") => Url::parse(&format!("http:{}", url)).ok()?,
+        Err(_) if url.starts_with(['/', '.', '#', '?']) => return None,
+        Err(_) => Url::parse(&format!("http://{}", url)).ok()?,
+    };
+    if parsed.scheme() == "mailto" {
+        let (_, domain) = parsed.path().rsplit_once('@')?;
+        return normalize_domain(domain).ok();
+    }
+    match parsed.host()? {
+        Host::Domain(domain) => Some(domain.trim_end_matches('.').to_string()),
+        host => Some(host.to_string()),
+    }
+}
+
+/// 规范化域名：转为小写，国际化域名转为 punycode，去掉开头的 `*.` 和末尾的点
+///
+/// # Returns
+/// * 规范化后的域名，或者说明原因的错误信息
+pub fn normalize_domain(domain: &str) -> Result<String, String> {
+    let trimmed = domain.trim();
+    let trimmed = trimmed.strip_prefix("*.").unwrap_or(trimmed);
+    let trimmed = trimmed.trim_end_matches(['.', '。', '．', '｡']);
+    if trimmed.is_empty() {
+        return Err("Domain cannot be empty".to_string());
+    }
+    match Host::parse(trimmed) {
+        Ok(Host::Domain(domain)) => Ok(domain),
+        Ok(host) => Ok(host.to_string()),
+        Err(e) => Err(format!("Invalid domain {}: {}", domain.trim(), e)),
+    }
+}
+
+/// 按域名后缀匹配的黑名单
+///
+/// 黑名单中的 `example.com` 同时挡住 `example.com` 和它的所有子域名，例如 `www.example.com`，
+/// 但不会挡住 `badexample.com`。
+#[derive(Debug, Default)]
+pub struct DomainBlocklist {
+    domains: HashSet<String>,
+}
+
+impl DomainBlocklist {
+    /// 从域名列表构建黑名单，无法解析的域名会被跳过
+    pub fn new<I, S>(domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let domains = domains
+            .into_iter()
+            .filter_map(|domain| match normalize_domain(domain.as_ref()) {
+                Ok(domain) => Some(domain),
+                Err(e) => {
+                    tracing::warn!("跳过域名黑名单中的无效域名: {}", e);
+                    None
+                }
+            })
+            .collect();
+        Self { domains }
+    }
+
+    /// 黑名单中的域名数量
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// 黑名单是否为空
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// 查找挡住这个主机名的黑名单域名
+    ///
+    /// # Arguments
+    /// * `host` - 规范化后的主机名
+    ///
+    /// # Returns
+    /// * 命中的黑名单域名，没有命中时为 None
+    pub fn find(&self, host: &str) -> Option<&str> {
+        std::iter::successors(Some(host), |suffix| suffix.split_once('.').map(|(_, rest)| rest))
+            .find_map(|suffix| self.domains.get(suffix))
+            .map(String::as_str)
+    }
+}
+
+/// 获取当前生效的域名黑名单
+pub fn current_blocklist() -> Arc<DomainBlocklist> {
+    match BLOCKLIST.read() {
+        Ok(guard) => Arc::clone(&guard),
+        Err(poisoned) => {
+            tracing::error!("获取域名黑名单读锁失败: {}", poisoned);
+            Arc::clone(&poisoned.into_inner())
+        }
+    }
+}
+
+/// 替换当前生效的域名黑名单
+///
+/// # Returns
+/// * 新黑名单中的域名数量
+pub fn set_blocked_domains<I, S>(domains: I) -> usize
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let blocklist = Arc::new(DomainBlocklist::new(domains));
+    let count = blocklist.len();
+    let mut current = BLOCKLIST.write().unwrap_or_else(|e| e.into_inner());
+    *current = blocklist;
+    count
+}
+
+/// 从内容中删去指定的链接
+///
+/// a 标签只删去开始标签，链接文字保留；多出的结束标签会在净化时被丢弃
+pub fn strip_links<'a, I>(content: &str, links: I) -> String
+where
+    I: IntoIterator<Item = &'a Link>,
+{
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for link in links {
+        result.push_str(&content[last..link.span.start]);
+        last = link.span.end;
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(content: &str) -> Vec<Option<String>> {
+        extract_links(content).into_iter().map(|l| l.host).collect()
+    }
+
+    #[test]
+    fn test_extract_links_from_markup_and_text() {
+        let content = r#"看 <a href="https://Bit.ly/abc">这里</a> 和 www.example.com/x。还有 t.cn/xyz"#;
+        let links = extract_links(content);
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].host.as_deref(), Some("bit.ly"));
+        assert_eq!(&content[links[0].span.clone()], r#"<a href="https://Bit.ly/abc">"#);
+        assert_eq!(links[1].url, "www.example.com/x");
+        assert!(links[1].explicit);
+        assert_eq!(links[2].host.as_deref(), Some("t.cn"));
+        assert!(!links[2].explicit);
+
+        assert_eq!(hosts("mail <a href='mailto:a@Spam.com'>me</a>"), vec![Some("spam.com".to_string())]);
+        assert_eq!(hosts("<a href=/local>x</a>"), vec![None]);
+        assert_eq!(hosts("<a href=//Spam.com/x>x</a>"), vec![Some("spam.com".to_string())]);
+        assert!(extract_links("版本 1.2.3，没有链接").is_empty());
+    }
+
+    #[test]
+    fn test_internationalized_domains_are_normalized() {
+        assert_eq!(normalize_domain("例子.中国").unwrap(), "xn--fsqu00a.xn--fiqs8s");
+        assert_eq!(normalize_domain("*.Example.COM.").unwrap(), "example.com");
+        assert!(normalize_domain("bad/domain").is_err());
+        assert_eq!(
+            hosts("访问 http://例子。中国/a"),
+            vec![Some("xn--fsqu00a.xn--fiqs8s".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_blocklist_matches_domain_suffixes() {
+        let blocklist = DomainBlocklist::new(["example.com", "例子.中国"]);
+        assert_eq!(blocklist.find("example.com"), Some("example.com"));
+        assert_eq!(blocklist.find("a.b.example.com"), Some("example.com"));
+        assert_eq!(blocklist.find("badexample.com"), None);
+        assert_eq!(blocklist.find("xn--fsqu00a.xn--fiqs8s"), Some("xn--fsqu00a.xn--fiqs8s"));
+    }
+
+    #[test]
+    fn test_strip_links() {
+        let content = r#"<a href="http://spam.com">点我</a> 或 http://spam.com/x 。"#;
+        let links = extract_links(content);
+        assert_eq!(strip_links(content, &links), "点我</a> 或  。");
+    }
+}
@@ -0,0 +1,750 @@
+use hyper::client::connect::dns::Name;
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::redirect;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error as StdError;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use url::{Host, Url};
+use uuid::Uuid;
+
+use super::{current_blocklist, extract_links, store};
+use crate::models::kind::ContentKind;
+use crate::models::link::LinkPreview;
+use crate::utils::filter::{current_filter, FilterAction};
+
+/// 抓取一个页面（包括跳转和读取内容）的默认时间上限
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 默认最多跟随的跳转次数
+pub const DEFAULT_MAX_REDIRECTS: usize = 3;
+
+/// 默认最多读取的页面字节数，预览信息通常在页面开头的 head 中
+pub const DEFAULT_MAX_BYTES: usize = 256 * 1024;
+
+/// 每条内容最多显示预览的链接数量
+pub const MAX_PREVIEWS_PER_CONTENT: usize = 3;
+
+/// 同时抓取的页面数量
+const MAX_CONCURRENT_FETCHES: usize = 4;
+
+const MAX_TITLE_CHARS: usize = 200;
+const MAX_DESCRIPTION_CHARS: usize = 500;
+const MAX_SITE_NAME_CHARS: usize = 100;
+
+lazy_static! {
+    /// meta 标签
+    static ref META_TAG: Regex = Regex::new(r"(?is)<meta\b[^>]*>").unwrap();
+
+    /// 标签中的属性，值可以带双引号、单引号或不带引号
+    static ref ATTRIBUTE: Regex =
+        Regex::new(r#"(?is)([a-z][a-z0-9_:.-]*)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap();
+
+    /// title 标签中的文字
+    static ref TITLE_TAG: Regex = Regex::new(r"(?is)<title\b[^>]*>(.*?)</title>").unwrap();
+}
+
+/// 抓取预览失败的原因
+#[derive(Error, Debug)]
+pub enum PreviewError {
+    #[error("Invalid URL {0}")]
+    InvalidUrl(String),
+
+    #[error("Unsupported scheme {0}")]
+    UnsupportedScheme(String),
+
+    #[error("Refusing to fetch from non-public address {0}")]
+    ForbiddenAddress(String),
+
+    #[error("Too many redirects (max {0})")]
+    TooManyRedirects(usize),
+
+    #[error("Request failed: {}", describe(.0))]
+    Request(#[from] reqwest::Error),
+
+    #[error("Unexpected status {0}")]
+    Status(u16),
+
+    #[error("Not an HTML page: {0}")]
+    NotHtml(String),
+
+    #[error("Page has no title")]
+    NoTitle,
+
+    #[error("Preview contains filtered words")]
+    Filtered,
+}
+
+/// 把错误的原因链连起来，便于看出是哪个检查拒绝了请求；已经包含在信息中的原因不再重复
+fn describe(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(": ");
+            message.push_str(&cause_message);
+        }
+        source = cause.source();
+    }
+    message
+}
+
+/// 地址是否可以从公网访问
+///
+/// 私有、回环、链路本地、运营商 NAT、组播、文档和保留地址都不是公网地址；
+/// 映射到 IPv6 的 IPv4 地址按其中的 IPv4 地址判断
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b == 18 || b == 19))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // fc00::/7 唯一本地地址
+        || (segments[0] & 0xfe00) == 0xfc00
+        // fe80::/10 链路本地地址，fec0::/10 已废弃的站点本地地址
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] & 0xffc0) == 0xfec0
+        // 2001:db8::/32 文档地址
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        // 64:ff9b::/96 NAT64 和 ::a.b.c.d 兼容地址，可能指向内网的 IPv4 地址
+        || (segments[0] == 0x0064 && segments[1] == 0xff9b)
+        || segments[..6] == [0; 6])
+}
+
+/// 抓取预览的配置
+#[derive(Debug, Clone)]
+pub struct PreviewConfig {
+    /// 是否抓取新链接的预览；关闭后已缓存的预览仍然显示
+    pub enabled: bool,
+    /// 抓取一个页面的时间上限
+    pub timeout: Duration,
+    /// 最多跟随的跳转次数
+    pub max_redirects: usize,
+    /// 最多读取的页面字节数，超出的部分不读取
+    pub max_bytes: usize,
+    /// 虽然不是公网地址但允许访问的地址，用于测试时访问本地的模拟服务
+    pub allowed_addresses: Vec<IpAddr>,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout: DEFAULT_TIMEOUT,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            max_bytes: DEFAULT_MAX_BYTES,
+            allowed_addresses: Vec::new(),
+        }
+    }
+}
+
+impl PreviewConfig {
+    /// 从环境变量读取配置
+    ///
+    /// * `LINK_PREVIEW_ENABLED` - 是否抓取链接预览，默认 true
+    /// * `LINK_PREVIEW_TIMEOUT_MS` - 抓取一个页面的时间上限，默认 5000 毫秒
+    /// * `LINK_PREVIEW_MAX_REDIRECTS` - 最多跟随的跳转次数，默认 3
+    /// * `LINK_PREVIEW_MAX_BYTES` - 最多读取的页面字节数，默认 262144
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: env::var("LINK_PREVIEW_ENABLED").map_or(true, |v| v != "false" && v != "0"),
+            timeout: env::var("LINK_PREVIEW_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.timeout),
+            max_redirects: env::var("LINK_PREVIEW_MAX_REDIRECTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_redirects),
+            max_bytes: env::var("LINK_PREVIEW_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_bytes),
+            allowed_addresses: defaults.allowed_addresses,
+        }
+    }
+}
+
+fn is_allowed(ip: IpAddr, allowed: &[IpAddr]) -> bool {
+    is_public_address(ip) || allowed.contains(&ip)
+}
+
+/// 检查地址的协议和直接写出的 IP 地址；域名在解析时检查
+fn check_url(url: &Url, allowed: &[IpAddr]) -> Result<(), PreviewError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(PreviewError::UnsupportedScheme(url.scheme().to_string()));
+    }
+    let ip = match url.host() {
+        Some(Host::Domain(_)) => return Ok(()),
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+        None => return Err(PreviewError::InvalidUrl(url.to_string())),
+    };
+    if is_allowed(ip, allowed) {
+        Ok(())
+    } else {
+        Err(PreviewError::ForbiddenAddress(ip.to_string()))
+    }
+}
+
+/// 只返回公网地址的域名解析
+///
+/// 连接使用的就是这里检查过的地址，因此跳转后的域名和解析结果在检查后改变的域名也无法指向内网
+struct PublicResolver {
+    allowed: Arc<[IpAddr]>,
+}
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let allowed = Arc::clone(&self.allowed);
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_allowed(addr.ip(), &allowed))
+                .collect();
+            if addrs.is_empty() {
+                return Err(Box::new(PreviewError::ForbiddenAddress(host)) as _);
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// 从页面中取出的预览信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PagePreview {
+    pub title: String,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+}
+
+/// 抓取链接预览
+///
+/// 只访问公网地址：直接写出的 IP 地址在请求前和每次跳转时检查，域名在解析时检查。
+/// 跳转次数、读取的字节数和总时间都有上限，同时抓取的页面数量也有限制
+pub struct PreviewFetcher {
+    client: reqwest::Client,
+    config: PreviewConfig,
+    permits: Semaphore,
+}
+
+impl PreviewFetcher {
+    pub fn new(config: PreviewConfig) -> Result<Self, String> {
+        let allowed: Arc<[IpAddr]> = config.allowed_addresses.clone().into();
+        let redirect_allowed = Arc::clone(&allowed);
+        let max_redirects = config.max_redirects;
+        let policy = redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() > max_redirects {
+                return attempt.error(PreviewError::TooManyRedirects(max_redirects));
+            }
+            match check_url(attempt.url(), &redirect_allowed) {
+                Ok(()) => attempt.follow(),
+                Err(e) => attempt.error(e),
+            }
+        });
+
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .redirect(policy)
+            .dns_resolver(Arc::new(PublicResolver { allowed }))
+            // 代理会替我们解析域名，绕过上面的检查
+            .no_proxy()
+            .user_agent(concat!("zero/", env!("CARGO_PKG_VERSION"), " (link preview)"))
+            .build()
+            .map_err(|e| format!("无法创建链接预览客户端: {}", e))?;
+        Ok(Self {
+            client,
+            config,
+            permits: Semaphore::new(MAX_CONCURRENT_FETCHES),
+        })
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        Self::new(PreviewConfig::from_env())
+    }
+
+    pub fn config(&self) -> &PreviewConfig {
+        &self.config
+    }
+
+    /// 抓取页面并取出预览信息
+    ///
+    /// 标题、描述和站点名称经过敏感词检查：mask 类命中被替换，更严重的命中使整个预览不显示
+    pub async fn fetch(&self, url: &str) -> Result<PagePreview, PreviewError> {
+        let url = Url::parse(url).map_err(|_| PreviewError::InvalidUrl(url.to_string()))?;
+        check_url(&url, &self.config.allowed_addresses)?;
+
+        let _permit = self.permits.acquire().await.ok();
+        let mut response = self
+            .client
+            .get(url)
+            .header(ACCEPT, "text/html,application/xhtml+xml")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(PreviewError::Status(response.status().as_u16()));
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !content_type.starts_with("text/html") && !content_type.starts_with("application/xhtml+xml") {
+            return Err(PreviewError::NotHtml(content_type));
+        }
+
+        let final_url = response.url().clone();
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            let remaining = self.config.max_bytes - body.len();
+            body.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+            if body.len() >= self.config.max_bytes {
+                break;
+            }
+        }
+
+        let preview = parse_preview(&String::from_utf8_lossy(&body), &final_url)
+            .ok_or(PreviewError::NoTitle)?;
+        Ok(PagePreview {
+            title: screen(preview.title)?,
+            description: preview.description.map(screen).transpose()?,
+            site_name: preview.site_name.map(screen).transpose()?,
+        })
+    }
+}
+
+/// 用当前词表检查预览中的文字
+fn screen(text: String) -> Result<String, PreviewError> {
+    let verdict = current_filter().check(&text);
+    match verdict.action {
+        None => Ok(text),
+        Some(FilterAction::Mask) => Ok(verdict.content),
+        Some(_) => Err(PreviewError::Filtered),
+    }
+}
+
+/// 从页面中取出预览信息
+///
+/// 优先使用 Open Graph 标签，其次是 Twitter 卡片和普通的 meta 标签，标题最后使用 title 标签；
+/// 没有站点名称时使用域名。没有标题的页面不显示预览
+///
+/// # Arguments
+/// * `html` - 页面内容，可以只是开头的一部分
+/// * `url` - 跳转之后的页面地址
+pub fn parse_preview(html: &str, url: &Url) -> Option<PagePreview> {
+    let mut meta: HashMap<String, String> = HashMap::new();
+    for tag in META_TAG.find_iter(html) {
+        let mut key = None;
+        let mut content = None;
+        for attribute in ATTRIBUTE.captures_iter(tag.as_str()) {
+            let value = attribute.iter().skip(2).flatten().next().map_or("", |m| m.as_str());
+            match attribute[1].to_ascii_lowercase().as_str() {
+                "property" | "name" => key = Some(value.trim().to_ascii_lowercase()),
+                "content" => content = Some(clean_text(value)),
+                _ => {}
+            }
+        }
+        if let (Some(key), Some(content)) = (key, content) {
+            meta.entry(key).or_insert(content);
+        }
+    }
+    let pick = |keys: &[&str]| {
+        keys.iter()
+            .filter_map(|key| meta.get(*key))
+            .find(|value| !value.is_empty())
+            .cloned()
+    };
+
+    let title = pick(&["og:title", "twitter:title"]).or_else(|| {
+        TITLE_TAG
+            .captures(html)
+            .map(|captures| clean_text(&captures[1]))
+            .filter(|title| !title.is_empty())
+    })?;
+    let description = pick(&["og:description", "twitter:description", "description"]);
+    let site_name = pick(&["og:site_name", "application-name"])
+        .or_else(|| url.host_str().map(|host| host.trim_start_matches("www.").to_string()));
+
+    Some(PagePreview {
+        title: truncate(title, MAX_TITLE_CHARS),
+        description: description.map(|d| truncate(d, MAX_DESCRIPTION_CHARS)),
+        site_name: site_name.map(|s| truncate(s, MAX_SITE_NAME_CHARS)),
+    })
+}
+
+/// 解码实体并合并空白
+fn clean_text(raw: &str) -> String {
+    html_escape::decode_html_entities(raw)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 超过长度的文字截断并加上省略号
+fn truncate(text: String, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+/// 内容中需要显示预览的链接
+///
+/// 只取明确写出的 http 和 https 链接，去掉片段、重复的地址和黑名单中的域名，
+/// 按出现顺序最多取 [`MAX_PREVIEWS_PER_CONTENT`] 个
+///
+/// # Arguments
+/// * `html` - 净化后的显示内容
+pub fn preview_urls(html: &str) -> Vec<String> {
+    let blocklist = current_blocklist();
+    let mut urls: Vec<String> = Vec::new();
+    for link in extract_links(html) {
+        if urls.len() == MAX_PREVIEWS_PER_CONTENT {
+            break;
+        }
+        let Some(host) = link.host.as_deref() else {
+            continue;
+        };
+        if !link.explicit || blocklist.find(host).is_some() {
+            continue;
+        }
+        let raw = html_escape::decode_html_entities(&link.url);
+        let Ok(mut url) = Url::parse(&raw).or_else(|_| Url::parse(&format!("http://{}", raw))) else {
+            continue;
+        };
+        if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+            continue;
+        }
+        url.set_fragment(None);
+        let url = url.to_string();
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// 记录内容中需要显示预览的链接，并在后台抓取还没有缓存或缓存已经过期的预览
+///
+/// 发帖、发评论和重新生成内容后调用；抓取的结果只写入缓存，不影响内容本身
+///
+/// # Arguments
+/// * `html` - 净化后的显示内容，已经被审核流水线删去的链接不会显示预览
+pub async fn record_links(
+    pool: &PgPool,
+    fetcher: &Arc<PreviewFetcher>,
+    kind: ContentKind,
+    id: Uuid,
+    html: &str,
+) {
+    if !fetcher.config.enabled {
+        return;
+    }
+    let urls = preview_urls(html);
+    let pending = match store::set_content_links(pool, kind, id, &urls).await {
+        Ok(pending) => pending,
+        Err(e) => {
+            tracing::warn!("无法记录 {} {} 中的链接: {}", kind.as_str(), id, e);
+            return;
+        }
+    };
+
+    for url in pending {
+        let pool = pool.clone();
+        let fetcher = Arc::clone(fetcher);
+        tokio::spawn(async move {
+            let result = fetcher.fetch(&url).await.map_err(|e| e.to_string());
+            if let Err(e) = &result {
+                tracing::debug!("无法抓取链接预览 {}: {}", url, e);
+            }
+            if let Err(e) = store::save_preview(&pool, &url, result).await {
+                tracing::warn!("无法保存链接预览 {}: {}", url, e);
+            }
+        });
+    }
+}
+
+/// 查询一批内容的链接预览，查询失败时只记录警告，内容照常显示
+///
+/// # Returns
+/// * 内容 id 到预览列表的映射，没有预览的内容不在其中
+pub async fn previews_for(
+    pool: &PgPool,
+    kind: ContentKind,
+    ids: &[Uuid],
+) -> HashMap<Uuid, Vec<LinkPreview>> {
+    store::load_previews(pool, kind, ids)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("无法查询链接预览: {}", e);
+            HashMap::new()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::{header, StatusCode},
+        response::{IntoResponse, Redirect},
+        routing::get,
+        Router,
+    };
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const PAGE: &str = r#"<!doctype html><html><head>
+        <title>备用标题</title>
+        <meta property="og:title" content="Open Graph &amp; 标题">
+        <meta name="description" content="  页面
+            描述 ">
+        <meta property='og:site_name' content='示例站点'>
+        </head><body>正文</body></html>"#;
+
+    /// 在本地随机端口启动一个模拟的网站
+    fn spawn_stub(app: Router) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        });
+        addr
+    }
+
+    fn html(body: String) -> impl IntoResponse {
+        ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], body)
+    }
+
+    /// 允许访问模拟网站的抓取器
+    fn fetcher(addr: SocketAddr, config: PreviewConfig) -> PreviewFetcher {
+        PreviewFetcher::new(PreviewConfig {
+            allowed_addresses: vec![addr.ip()],
+            ..config
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_public_addresses() {
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "224.0.0.1",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_parse_preview() {
+        let url = Url::parse("https://www.example.com/a").unwrap();
+        assert_eq!(
+            parse_preview(PAGE, &url),
+            Some(PagePreview {
+                title: "Open Graph & 标题".to_string(),
+                description: Some("页面 描述".to_string()),
+                site_name: Some("示例站点".to_string()),
+            })
+        );
+
+        let preview = parse_preview("<TITLE>\n 只有 标题 </TITLE>", &url).unwrap();
+        assert_eq!(preview.title, "只有 标题");
+        assert_eq!(preview.description, None);
+        assert_eq!(preview.site_name.as_deref(), Some("example.com"));
+
+        let long = format!("<title>{}</title>", "长".repeat(300));
+        assert_eq!(parse_preview(&long, &url).unwrap().title.chars().count(), MAX_TITLE_CHARS + 1);
+
+        assert_eq!(parse_preview("<p>没有标题</p>", &url), None);
+    }
+
+    #[test]
+    fn test_preview_urls() {
+        let html = concat!(
+            r#"<a href="https://example.com/a?x=1&amp;y=2#top" rel="nofollow">链接</a> "#,
+            r#"<a href="https://example.com/a?x=1&amp;y=2">再次</a> "#,
+            r#"<a href="mailto:a@example.com">邮件</a> <a href="/local">站内</a> "#,
+            "www.example.org/b 和 example.net",
+        );
+        assert_eq!(
+            preview_urls(html),
+            vec!["https://example.com/a?x=1&y=2", "http://www.example.org/b"]
+        );
+
+        let many = (0..5)
+            .map(|i| format!("https://example.com/{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(preview_urls(&many).len(), MAX_PREVIEWS_PER_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_fetches_preview_from_page() {
+        let addr = spawn_stub(Router::new().route("/page", get(|| async { html(PAGE.to_string()) })));
+        let preview = fetcher(addr, PreviewConfig::default())
+            .fetch(&format!("http://{}/page", addr))
+            .await
+            .unwrap();
+        assert_eq!(preview.title, "Open Graph & 标题");
+        assert_eq!(preview.site_name.as_deref(), Some("示例站点"));
+    }
+
+    #[tokio::test]
+    async fn test_refuses_non_public_addresses() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let addr = spawn_stub(Router::new().route(
+            "/page",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                html(PAGE.to_string())
+            }),
+        ));
+        let fetcher = PreviewFetcher::new(PreviewConfig::default()).unwrap();
+        let port = addr.port();
+        for url in [
+            format!("http://127.0.0.1:{}/page", port),
+            format!("http://localhost:{}/page", port),
+            format!("http://[::ffff:127.0.0.1]:{}/page", port),
+            format!("http://2130706433:{}/page", port),
+            "http://169.254.169.254/latest/meta-data/".to_string(),
+        ] {
+            let error = fetcher.fetch(&url).await.unwrap_err();
+            assert!(error.to_string().contains("non-public address"), "{}: {}", url, error);
+        }
+        assert!(fetcher.fetch("ftp://example.com/").await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_redirects_are_capped_and_checked() {
+        let addr = spawn_stub(
+            Router::new()
+                .route("/page", get(|| async { html(PAGE.to_string()) }))
+                .route(
+                    "/hop/:n",
+                    get(|axum::extract::Path(n): axum::extract::Path<usize>| async move {
+                        if n == 0 {
+                            Redirect::temporary("/page")
+                        } else {
+                            Redirect::temporary(&format!("/hop/{}", n - 1))
+                        }
+                    }),
+                )
+                .route(
+                    "/internal",
+                    get(|| async { Redirect::temporary("http://127.0.0.2/page") }),
+                ),
+        );
+        let fetcher = fetcher(
+            addr,
+            PreviewConfig {
+                max_redirects: 3,
+                ..PreviewConfig::default()
+            },
+        );
+
+        assert!(fetcher.fetch(&format!("http://{}/hop/2", addr)).await.is_ok());
+        let error = fetcher.fetch(&format!("http://{}/hop/3", addr)).await.unwrap_err();
+        assert!(error.to_string().contains("Too many redirects"), "{}", error);
+        let error = fetcher.fetch(&format!("http://{}/internal", addr)).await.unwrap_err();
+        assert!(error.to_string().contains("non-public address 127.0.0.2"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_body_size_time_and_type_are_limited() {
+        let addr = spawn_stub(
+            Router::new()
+                .route(
+                    "/big",
+                    get(|| async { html(format!("<title>开头</title>{}", "x".repeat(1 << 20))) }),
+                )
+                .route(
+                    "/late",
+                    get(|| async { html(format!("{}<title>太晚</title>", "x".repeat(1 << 20))) }),
+                )
+                .route(
+                    "/slow",
+                    get(|| async {
+                        tokio::time::sleep(Duration::from_secs(2)).await;
+                        html(PAGE.to_string())
+                    }),
+                )
+                .route("/json", get(|| async { axum::Json(serde_json::json!({"title": "x"})) }))
+                .route("/missing", get(|| async { StatusCode::NOT_FOUND })),
+        );
+        let fetcher = fetcher(
+            addr,
+            PreviewConfig {
+                timeout: Duration::from_millis(300),
+                max_bytes: 4096,
+                ..PreviewConfig::default()
+            },
+        );
+        let url = |path: &str| format!("http://{}{}", addr, path);
+
+        assert_eq!(fetcher.fetch(&url("/big")).await.unwrap().title, "开头");
+        assert!(matches!(fetcher.fetch(&url("/late")).await, Err(PreviewError::NoTitle)));
+        assert!(matches!(fetcher.fetch(&url("/slow")).await, Err(PreviewError::Request(e)) if e.is_timeout()));
+        assert!(matches!(fetcher.fetch(&url("/json")).await, Err(PreviewError::NotHtml(_))));
+        assert!(matches!(fetcher.fetch(&url("/missing")).await, Err(PreviewError::Status(404))));
+    }
+}
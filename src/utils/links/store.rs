@@ -0,0 +1,151 @@
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::preview::PagePreview;
+use super::set_blocked_domains;
+use crate::models::kind::ContentKind;
+use crate::models::link::LinkPreview;
+
+/// 从数据库加载域名黑名单并替换当前生效的黑名单
+///
+/// 启动时以及每次通过管理接口修改黑名单后调用，使改动立即生效
+///
+/// # Returns
+/// * 黑名单中的域名数量，或者数据库错误
+pub async fn reload_blocked_domains(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let domains: Vec<String> =
+        sqlx::query_scalar("SELECT domain FROM link_blocklist ORDER BY domain")
+            .fetch_all(pool)
+            .await?;
+    tracing::debug!("从数据库加载 {} 个黑名单域名", domains.len());
+    Ok(set_blocked_domains(domains))
+}
+
+/// 替换一条内容中需要显示预览的链接
+///
+/// 还没有缓存的地址、缓存已经过期的地址（成功的 7 天，失败的 1 天）以及抓取中断的地址
+/// 会被标记为等待抓取；正在抓取的地址不会重复抓取
+///
+/// # Returns
+/// * 需要抓取的地址
+pub async fn set_content_links(
+    pool: &PgPool,
+    kind: ContentKind,
+    id: Uuid,
+    urls: &[String],
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM content_links WHERE content_kind = $1 AND content_id = $2")
+        .bind(kind.as_str())
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    if urls.is_empty() {
+        tx.commit().await?;
+        return Ok(Vec::new());
+    }
+
+    let pending: Vec<String> = sqlx::query_scalar(
+        r#"
+        INSERT INTO link_previews (url)
+        SELECT unnest($1::text[])
+        ON CONFLICT (url) DO UPDATE SET status = 'pending', updated_at = now()
+        WHERE (link_previews.status = 'ready' AND link_previews.updated_at < now() - interval '7 days')
+           OR (link_previews.status = 'failed' AND link_previews.updated_at < now() - interval '1 day')
+           OR (link_previews.status = 'pending' AND link_previews.updated_at < now() - interval '10 minutes')
+        RETURNING url
+        "#,
+    )
+    .bind(urls)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO content_links (content_kind, content_id, position, url)
+        SELECT $1, $2, ordinal - 1, url
+        FROM unnest($3::text[]) WITH ORDINALITY AS links(url, ordinal)
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(id)
+    .bind(urls)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(pending)
+}
+
+/// 保存抓取结果
+///
+/// 失败时清空之前的预览，页面已经不存在或不再适合显示时不再显示旧的预览
+pub async fn save_preview(
+    pool: &PgPool,
+    url: &str,
+    result: Result<PagePreview, String>,
+) -> Result<(), sqlx::Error> {
+    let (status, preview, error) = match result {
+        Ok(preview) => ("ready", Some(preview), None),
+        Err(error) => ("failed", None, Some(error)),
+    };
+    sqlx::query(
+        r#"
+        UPDATE link_previews
+        SET status = $2, title = $3, description = $4, site_name = $5, error = $6, updated_at = now()
+        WHERE url = $1
+        "#,
+    )
+    .bind(url)
+    .bind(status)
+    .bind(preview.as_ref().map(|p| &p.title))
+    .bind(preview.as_ref().and_then(|p| p.description.as_ref()))
+    .bind(preview.as_ref().and_then(|p| p.site_name.as_ref()))
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 查询一批内容的链接预览，只包含有标题的预览
+///
+/// 缓存过期后重新抓取期间仍然显示之前的预览
+///
+/// # Returns
+/// * 内容 id 到按出现顺序排列的预览的映射
+pub async fn load_previews(
+    pool: &PgPool,
+    kind: ContentKind,
+    ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<LinkPreview>>, sqlx::Error> {
+    let mut previews: HashMap<Uuid, Vec<LinkPreview>> = HashMap::new();
+    if ids.is_empty() {
+        return Ok(previews);
+    }
+    let rows = sqlx::query(
+        r#"
+        SELECT cl.content_id, lp.url, lp.title, lp.description, lp.site_name
+        FROM content_links cl
+        JOIN link_previews lp ON lp.url = cl.url
+        WHERE cl.content_kind = $1 AND cl.content_id = ANY($2) AND lp.title IS NOT NULL
+        ORDER BY cl.content_id, cl.position
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(ids)
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        previews
+            .entry(row.try_get("content_id")?)
+            .or_default()
+            .push(LinkPreview {
+                url: row.try_get("url")?,
+                title: row.try_get("title")?,
+                description: row.try_get("description")?,
+                site_name: row.try_get("site_name")?,
+            });
+    }
+    Ok(previews)
+}
@@ -0,0 +1,82 @@
+use lazy_static::lazy_static;
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+use regex::Regex;
+use std::ops::Range;
+
+use crate::models::format::ContentFormat;
+
+lazy_static! {
+    /// HTML 标签
+    static ref TAG: Regex = Regex::new(r"<[^>]*>").unwrap();
+}
+
+/// 把 Markdown 转换为 HTML，结果仍需经过净化才能显示
+///
+/// 支持强调、列表、代码块、引用和链接等 CommonMark 语法。单个换行按换行显示（与 HTML 格式中
+/// 换行转为 `<br>` 一致），而不是像 CommonMark 那样合并为空格。
+///
+/// # Arguments
+/// * `text` - Markdown 原文
+/// * `allow_html` - 是否保留原文中的 HTML 标签；不保留时标签作为普通文字显示
+pub fn markdown_to_html(text: &str, allow_html: bool) -> String {
+    let parser = Parser::new_ext(text, Options::empty()).map(|event| match event {
+        Event::SoftBreak => Event::HardBreak,
+        Event::Html(html) | Event::InlineHtml(html) if !allow_html => Event::Text(html),
+        event => event,
+    });
+    let mut output = String::with_capacity(text.len() * 3 / 2);
+    html::push_html(&mut output, parser);
+    output
+}
+
+/// 原文中不解析引用和话题的区间
+///
+/// Markdown 中是代码、链接、图片和 HTML 标签，HTML 格式中是标签。
+/// 这些地方的文字按原样显示或者已经是链接，其中的 `>>` 和 `#` 不是内容的一部分
+pub fn literal_ranges(text: &str, format: ContentFormat) -> Vec<Range<usize>> {
+    match format {
+        ContentFormat::Markdown => Parser::new_ext(text, Options::empty())
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::Code(_) | Event::Html(_) | Event::InlineHtml(_) => Some(range),
+                Event::Start(Tag::CodeBlock(_) | Tag::Link { .. } | Tag::Image { .. }) => Some(range),
+                _ => None,
+            })
+            .collect(),
+        ContentFormat::Html => TAG.find_iter(text).map(|m| m.range()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_html() {
+        assert_eq!(
+            markdown_to_html("**粗体** 和 *斜体*\n第二行", false),
+            "<p><strong>粗体</strong> 和 <em>斜体</em><br />\n第二行</p>\n"
+        );
+        assert_eq!(
+            markdown_to_html("- a\n- b\n\n> 引用", false),
+            "<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n<blockquote>\n<p>引用</p>\n</blockquote>\n"
+        );
+        assert_eq!(
+            markdown_to_html("```\nlet a = 1;\n```", false),
+            "<pre><code>let a = 1;\n</code></pre>\n"
+        );
+        assert_eq!(
+            markdown_to_html("[链接](https://example.com)", false),
+            "<p><a href=\"https://example.com\">链接</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_raw_html_is_escaped_unless_allowed() {
+        assert_eq!(
+            markdown_to_html("<b>粗体</b>", false),
+            "<p>&lt;b&gt;粗体&lt;/b&gt;</p>\n"
+        );
+        assert_eq!(markdown_to_html("<b>粗体</b>", true), "<p><b>粗体</b></p>\n");
+    }
+}
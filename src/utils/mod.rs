@@ -1,4 +1,13 @@
 pub mod pagination;
 pub mod error;
 pub mod sanitize;
-pub mod filter;
\ No newline at end of file
+pub mod filter;
+pub mod actor;
+pub mod moderation;
+pub mod render;
+pub mod links;
+pub mod references;
+pub mod tags;
+pub mod api_key;
+pub mod markdown;
+pub mod text;
@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{ContentClassifier, Decision, Verdict};
+
+/// 外部审核服务的默认超时时间
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// 外部审核服务不可用（超时、出错或返回无法识别的结果）时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailMode {
+    /// 放行，只记录警告
+    Open,
+    /// 转人工审核
+    Closed,
+}
+
+impl FromStr for FailMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(FailMode::Open),
+            "closed" => Ok(FailMode::Closed),
+            other => Err(format!("未知的失败处理方式: {}", other)),
+        }
+    }
+}
+
+/// 发给外部审核服务的请求
+#[derive(Serialize)]
+struct ClassifyRequest<'a> {
+    content: &'a str,
+}
+
+/// 外部审核服务的响应
+#[derive(Deserialize)]
+struct ClassifyResponse {
+    decision: Decision,
+    #[serde(default)]
+    reasons: Vec<String>,
+    /// decision 为 mask 时替换后的内容
+    content: Option<String>,
+}
+
+/// 调用外部 HTTP 审核服务的分类器
+///
+/// 以 JSON `{"content": "..."}` POST 到配置的地址，服务返回
+/// `{"decision": "allow|mask|hold|reject", "reasons": [...], "content": "..."}`，
+/// 其中 `content` 可选，只在 decision 为 mask 时使用。
+pub struct HttpClassifier {
+    client: reqwest::Client,
+    url: String,
+    fail_mode: FailMode,
+}
+
+impl HttpClassifier {
+    pub fn new(url: String, timeout: Duration, fail_mode: FailMode) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("无法创建审核服务客户端: {}", e))?;
+        Ok(Self {
+            client,
+            url,
+            fail_mode,
+        })
+    }
+
+    async fn request(&self, content: &str) -> Result<ClassifyResponse, reqwest::Error> {
+        self.client
+            .post(&self.url)
+            .json(&ClassifyRequest { content })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// 服务不可用时按失败处理方式给出结论
+    fn unavailable(&self, error: reqwest::Error) -> Verdict {
+        tracing::warn!("外部审核服务不可用: {}", error);
+        match self.fail_mode {
+            FailMode::Open => Verdict::allow(),
+            FailMode::Closed => Verdict {
+                decision: Decision::Hold,
+                reasons: vec!["moderation service unavailable".to_string()],
+                content: None,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl ContentClassifier for HttpClassifier {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn classify(&self, content: &str) -> Verdict {
+        match self.request(content).await {
+            Ok(response) => Verdict {
+                decision: response.decision,
+                reasons: response.reasons,
+                content: response
+                    .content
+                    .filter(|_| response.decision == Decision::Mask),
+            },
+            Err(e) => self.unavailable(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::net::{SocketAddr, TcpListener};
+
+    /// 在本地随机端口启动一个模拟的审核服务
+    fn spawn_stub(app: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        });
+        format!("http://{}/classify", addr)
+    }
+
+    fn classifier(url: String, fail_mode: FailMode) -> HttpClassifier {
+        HttpClassifier::new(url, Duration::from_millis(200), fail_mode).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_uses_service_decision() {
+        let url = spawn_stub(Router::new().route(
+            "/classify",
+            post(|Json(body): Json<Value>| async move {
+                let content = body["content"].as_str().unwrap_or_default();
+                if content.contains("spam") {
+                    Json(json!({"decision": "mask", "reasons": ["spam"], "content": "****"}))
+                } else {
+                    Json(json!({"decision": "allow"}))
+                }
+            }),
+        ));
+
+        let classifier = classifier(url, FailMode::Closed);
+        let verdict = classifier.classify("buy spam").await;
+        assert_eq!(verdict.decision, Decision::Mask);
+        assert_eq!(verdict.reasons, vec!["spam"]);
+        assert_eq!(verdict.content.as_deref(), Some("****"));
+
+        assert_eq!(classifier.classify("hello").await, Verdict::allow());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_follows_fail_mode() {
+        let url = spawn_stub(Router::new().route(
+            "/classify",
+            post(|| async {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                Json(json!({"decision": "reject"}))
+            }),
+        ));
+
+        let open = classifier(url.clone(), FailMode::Open).classify("内容").await;
+        assert_eq!(open.decision, Decision::Allow);
+
+        let closed = classifier(url, FailMode::Closed).classify("内容").await;
+        assert_eq!(closed.decision, Decision::Hold);
+        assert_eq!(closed.reasons, vec!["moderation service unavailable"]);
+    }
+
+    #[tokio::test]
+    async fn test_error_status_and_bad_body_follow_fail_mode() {
+        let url = spawn_stub(
+            Router::new()
+                .route("/classify", post(|| async { StatusCode::SERVICE_UNAVAILABLE }))
+                .route("/bad", post(|| async { Json(json!({"decision": "maybe"})) })),
+        );
+        let bad = url.replace("/classify", "/bad");
+
+        let verdict = classifier(url, FailMode::Closed).classify("内容").await;
+        assert_eq!(verdict.decision, Decision::Hold);
+
+        let verdict = classifier(bad, FailMode::Open).classify("内容").await;
+        assert_eq!(verdict.decision, Decision::Allow);
+    }
+}
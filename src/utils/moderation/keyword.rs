@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::{ContentClassifier, Decision, Verdict};
+use crate::utils::filter::{check_sensitive_words, SensitiveWordFilter};
+
+/// 基于敏感词表的分类器
+///
+/// 默认使用当前生效的词表（随词表重新加载而更新），命中按词表中的处理方式给出结论
+#[derive(Default)]
+pub struct KeywordClassifier {
+    filter: Option<Arc<SensitiveWordFilter>>,
+}
+
+impl KeywordClassifier {
+    /// 使用指定的过滤器而不是当前生效的词表
+    pub fn with_filter(filter: SensitiveWordFilter) -> Self {
+        Self {
+            filter: Some(Arc::new(filter)),
+        }
+    }
+}
+
+#[async_trait]
+impl ContentClassifier for KeywordClassifier {
+    fn name(&self) -> &str {
+        "keyword"
+    }
+
+    async fn classify(&self, content: &str) -> Verdict {
+        let verdict = match &self.filter {
+            Some(filter) => filter.check(content),
+            None => check_sensitive_words(content),
+        };
+
+        let reasons = if verdict.action.is_some() {
+            vec![format!(
+                "contains prohibited words in category {}",
+                verdict.categories.join(", ")
+            )]
+        } else {
+            Vec::new()
+        };
+
+        Verdict {
+            decision: verdict.action.map_or(Decision::Allow, Decision::from),
+            reasons,
+            content: Some(verdict.content),
+        }
+    }
+}
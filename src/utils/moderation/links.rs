@@ -0,0 +1,176 @@
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::{ContentClassifier, Decision, Verdict};
+use crate::utils::links::{current_blocklist, extract_links, strip_links, DomainBlocklist};
+
+/// 每条内容默认最多包含的链接数量
+pub const DEFAULT_MAX_LINKS: usize = 5;
+
+/// 内容中有指向黑名单域名的链接时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkAction {
+    /// 拒绝发布
+    Reject,
+    /// 删去这些链接后发布
+    Strip,
+}
+
+impl FromStr for LinkAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(LinkAction::Reject),
+            "strip" => Ok(LinkAction::Strip),
+            other => Err(format!("未知的链接处理方式: {}", other)),
+        }
+    }
+}
+
+/// 检查内容中链接的分类器
+///
+/// 从 a 标签和文本中提取链接，按域名黑名单（默认使用当前生效的黑名单）处理，
+/// 并限制每条内容中不同链接的数量，超过时拒绝发布。
+/// 只写出域名、没有协议也不以 www. 开头的地址只检查黑名单，不计入数量。
+pub struct LinkClassifier {
+    action: LinkAction,
+    max_links: usize,
+    blocklist: Option<Arc<DomainBlocklist>>,
+}
+
+impl LinkClassifier {
+    pub fn new(action: LinkAction, max_links: usize) -> Self {
+        Self {
+            action,
+            max_links,
+            blocklist: None,
+        }
+    }
+
+    /// 使用指定的黑名单而不是当前生效的黑名单
+    pub fn with_blocklist(mut self, blocklist: DomainBlocklist) -> Self {
+        self.blocklist = Some(Arc::new(blocklist));
+        self
+    }
+}
+
+#[async_trait]
+impl ContentClassifier for LinkClassifier {
+    fn name(&self) -> &str {
+        "links"
+    }
+
+    async fn classify(&self, content: &str) -> Verdict {
+        let links = extract_links(content);
+        if links.is_empty() {
+            return Verdict::default();
+        }
+        let blocklist = self.blocklist.clone().unwrap_or_else(current_blocklist);
+
+        let (blocked, allowed): (Vec<_>, Vec<_>) = links.iter().partition(|link| {
+            link.host
+                .as_deref()
+                .is_some_and(|host| blocklist.find(host).is_some())
+        });
+
+        let mut verdict = Verdict::default();
+        if !blocked.is_empty() {
+            let mut domains: Vec<&str> = Vec::new();
+            for domain in blocked
+                .iter()
+                .filter_map(|link| blocklist.find(link.host.as_deref()?))
+            {
+                if !domains.contains(&domain) {
+                    domains.push(domain);
+                }
+            }
+            match self.action {
+                LinkAction::Reject => {
+                    verdict.decision = Decision::Reject;
+                    verdict
+                        .reasons
+                        .push(format!("links to blocked domain {}", domains.join(", ")));
+                }
+                LinkAction::Strip => {
+                    verdict.decision = Decision::Mask;
+                    verdict
+                        .reasons
+                        .push(format!("removed links to blocked domain {}", domains.join(", ")));
+                    verdict.content = Some(strip_links(content, blocked.iter().copied()));
+                }
+            }
+        }
+
+        // 删去的链接不再计入数量；同一个地址出现多次只算一次
+        let counted = if self.action == LinkAction::Strip {
+            allowed
+        } else {
+            links.iter().collect()
+        };
+        let distinct: HashSet<String> = counted
+            .iter()
+            .filter(|link| link.explicit)
+            .map(|link| link.url.to_lowercase())
+            .collect();
+        if distinct.len() > self.max_links {
+            verdict.decision = Decision::Reject;
+            verdict.reasons.push(format!(
+                "too many links: {} (at most {})",
+                distinct.len(),
+                self.max_links
+            ));
+        }
+
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classifier(action: LinkAction, max_links: usize) -> LinkClassifier {
+        LinkClassifier::new(action, max_links)
+            .with_blocklist(DomainBlocklist::new(["bit.ly", "例子.中国"]))
+    }
+
+    #[tokio::test]
+    async fn test_blocked_links_are_rejected_or_stripped() {
+        let content = r#"福利 <a href="https://www.bit.ly/x">点这里</a> http://例子。中国/a 以及 https://ok.com"#;
+
+        let verdict = classifier(LinkAction::Reject, 5).classify(content).await;
+        assert_eq!(verdict.decision, Decision::Reject);
+        assert_eq!(
+            verdict.reasons,
+            vec!["links to blocked domain bit.ly, xn--fsqu00a.xn--fiqs8s"]
+        );
+
+        let verdict = classifier(LinkAction::Strip, 5).classify(content).await;
+        assert_eq!(verdict.decision, Decision::Mask);
+        assert_eq!(
+            verdict.content.as_deref(),
+            Some("福利 点这里</a>  以及 https://ok.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_link_limit_counts_distinct_links() {
+        let content = "https://a.com https://a.com https://b.com example.org";
+        assert_eq!(
+            classifier(LinkAction::Reject, 2).classify(content).await.decision,
+            Decision::Allow
+        );
+
+        let verdict = classifier(LinkAction::Reject, 1).classify(content).await;
+        assert_eq!(verdict.decision, Decision::Reject);
+        assert_eq!(verdict.reasons, vec!["too many links: 2 (at most 1)"]);
+
+        // 删去的链接不计入数量
+        let content = "https://bit.ly/1 https://bit.ly/2 https://a.com";
+        let verdict = classifier(LinkAction::Strip, 1).classify(content).await;
+        assert_eq!(verdict.decision, Decision::Mask);
+    }
+}
@@ -0,0 +1,331 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod http;
+mod keyword;
+mod links;
+mod pii;
+
+pub use http::{FailMode, HttpClassifier};
+pub use keyword::KeywordClassifier;
+pub use links::{LinkAction, LinkClassifier};
+pub use pii::{find_pii, PiiClassifier, PiiKind, PiiMatch, PII_CATEGORY};
+
+use crate::utils::filter::FilterAction;
+
+/// 审核结论，按严重程度从低到高排列
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    /// 原样发布
+    #[default]
+    Allow,
+    /// 替换部分内容后发布
+    Mask,
+    /// 暂不公开，等待人工审核
+    Hold,
+    /// 拒绝发布
+    Reject,
+}
+
+impl From<FilterAction> for Decision {
+    fn from(action: FilterAction) -> Self {
+        match action {
+            FilterAction::Mask => Decision::Mask,
+            FilterAction::Hold => Decision::Hold,
+            FilterAction::Reject => Decision::Reject,
+        }
+    }
+}
+
+/// 单个分类器对内容的判断
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verdict {
+    pub decision: Decision,
+    /// 做出这个判断的原因，面向用户或审核人员
+    pub reasons: Vec<String>,
+    /// 分类器修改后的内容（如替换了敏感词），为 None 时沿用输入
+    pub content: Option<String>,
+}
+
+impl Verdict {
+    /// 不做任何处理的判断
+    pub fn allow() -> Self {
+        Self::default()
+    }
+}
+
+/// 内容分类器
+///
+/// 审核流水线按顺序调用各个分类器，前一个分类器修改后的内容作为后一个的输入
+#[async_trait]
+pub trait ContentClassifier: Send + Sync {
+    /// 分类器名称，用于日志和审核原因
+    fn name(&self) -> &str;
+
+    /// 判断内容应如何处理
+    async fn classify(&self, content: &str) -> Verdict;
+}
+
+/// 审核流水线中的一条原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModerationReason {
+    /// 给出原因的分类器
+    pub classifier: String,
+    /// 该分类器的结论
+    pub decision: Decision,
+    pub reason: String,
+}
+
+/// 审核流水线的最终结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModerationOutcome {
+    /// 各分类器结论中最严重的一个
+    pub decision: Decision,
+    /// 所有分类器给出的原因，按分类器顺序排列
+    pub reasons: Vec<ModerationReason>,
+    /// 经过各分类器修改后的内容
+    pub content: String,
+}
+
+impl ModerationOutcome {
+    /// 把导致最终结论的原因连接成一条说明
+    pub fn summary(&self) -> String {
+        self.reasons
+            .iter()
+            .filter(|r| r.decision == self.decision)
+            .map(|r| r.reason.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// 按顺序执行的分类器链
+#[derive(Clone, Default)]
+pub struct ModerationPipeline {
+    classifiers: Vec<Arc<dyn ContentClassifier>>,
+}
+
+impl ModerationPipeline {
+    pub fn new(classifiers: Vec<Arc<dyn ContentClassifier>>) -> Self {
+        Self { classifiers }
+    }
+
+    /// 从环境变量构建流水线
+    ///
+    /// * `MODERATION_CLASSIFIERS` - 按顺序启用的分类器，逗号分隔，默认 `links,pii,keyword`，可选：
+    ///   * `links` - 检查链接的域名黑名单和链接数量（见 [`LinkClassifier`]）
+    ///   * `pii` - 检测手机号、邮箱等联系方式（见 [`PiiClassifier`]）
+    ///   * `keyword` - 按敏感词表检查（见 [`KeywordClassifier`]）
+    ///   * `http` - 调用外部审核服务（见 [`HttpClassifier`]）
+    /// * `MODERATION_LINK_ACTION` - 链接指向黑名单域名时的处理方式：`reject` 拒绝发布（默认），`strip` 删去这些链接
+    /// * `MODERATION_MAX_LINKS` - 每条内容最多包含的不同链接数量，默认 5
+    /// * `MODERATION_PII_ACTION` - 检测到联系方式时的处理方式：`mask`（默认）、`hold` 或 `reject`
+    /// * `MODERATION_PII_KINDS` - 检测的联系方式种类，逗号分隔，默认全部
+    /// * `MODERATION_HTTP_URL` - 外部审核服务地址，启用 `http` 时必填
+    /// * `MODERATION_HTTP_TIMEOUT_MS` - 外部审核服务超时时间，默认 2000 毫秒
+    /// * `MODERATION_HTTP_FAIL_MODE` - 外部审核服务不可用时的处理方式：`open` 放行（默认），`closed` 转人工审核
+    pub fn from_env() -> Result<Self, String> {
+        let names = env::var("MODERATION_CLASSIFIERS").unwrap_or_else(|_| "links,pii,keyword".to_string());
+
+        let mut classifiers: Vec<Arc<dyn ContentClassifier>> = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "keyword" => classifiers.push(Arc::new(KeywordClassifier::default())),
+                "http" => {
+                    let url = env::var("MODERATION_HTTP_URL")
+                        .map_err(|_| "启用 http 分类器时必须设置 MODERATION_HTTP_URL".to_string())?;
+                    let timeout = env::var("MODERATION_HTTP_TIMEOUT_MS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .map(Duration::from_millis)
+                        .unwrap_or(http::DEFAULT_TIMEOUT);
+                    let fail_mode = match env::var("MODERATION_HTTP_FAIL_MODE") {
+                        Ok(mode) => mode.parse()?,
+                        Err(_) => FailMode::Open,
+                    };
+                    classifiers.push(Arc::new(HttpClassifier::new(url, timeout, fail_mode)?));
+                }
+                "links" => {
+                    let action = match env::var("MODERATION_LINK_ACTION") {
+                        Ok(action) => action.parse()?,
+                        Err(_) => LinkAction::Reject,
+                    };
+                    let max_links = env::var("MODERATION_MAX_LINKS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(links::DEFAULT_MAX_LINKS);
+                    classifiers.push(Arc::new(LinkClassifier::new(action, max_links)));
+                }
+                "pii" => {
+                    let action = match env::var("MODERATION_PII_ACTION") {
+                        Ok(action) => action.parse()?,
+                        Err(_) => FilterAction::Mask,
+                    };
+                    let kinds = match env::var("MODERATION_PII_KINDS") {
+                        Ok(kinds) => kinds
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|k| !k.is_empty())
+                            .map(str::parse)
+                            .collect::<Result<Vec<PiiKind>, _>>()?,
+                        Err(_) => PiiKind::ALL.to_vec(),
+                    };
+                    classifiers.push(Arc::new(PiiClassifier::new(action, kinds)));
+                }
+                other => return Err(format!("未知的审核分类器: {}", other)),
+            }
+        }
+        Ok(Self::new(classifiers))
+    }
+
+    /// 分类器名称，按执行顺序排列
+    pub fn names(&self) -> Vec<&str> {
+        self.classifiers.iter().map(|c| c.name()).collect()
+    }
+
+    /// 依次执行各分类器
+    ///
+    /// 任一分类器给出 reject 后不再执行后面的分类器
+    pub async fn run(&self, content: &str) -> ModerationOutcome {
+        let mut outcome = ModerationOutcome {
+            decision: Decision::Allow,
+            reasons: Vec::new(),
+            content: content.to_string(),
+        };
+
+        for classifier in &self.classifiers {
+            let verdict = classifier.classify(&outcome.content).await;
+            if verdict.decision != Decision::Allow {
+                tracing::debug!(
+                    "审核分类器 {} 的结论: {} {:?}",
+                    classifier.name(),
+                    verdict.decision,
+                    verdict.reasons
+                );
+            }
+
+            outcome.decision = outcome.decision.max(verdict.decision);
+            outcome
+                .reasons
+                .extend(verdict.reasons.into_iter().map(|reason| ModerationReason {
+                    classifier: classifier.name().to_string(),
+                    decision: verdict.decision,
+                    reason,
+                }));
+            if let Some(content) = verdict.content {
+                outcome.content = content;
+            }
+            if outcome.decision == Decision::Reject {
+                break;
+            }
+        }
+        outcome
+    }
+}
+
+impl Decision {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Decision::Allow => "allow",
+            Decision::Mask => "mask",
+            Decision::Hold => "hold",
+            Decision::Reject => "reject",
+        }
+    }
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Decision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Decision::Allow),
+            "mask" => Ok(Decision::Mask),
+            "hold" => Ok(Decision::Hold),
+            "reject" => Ok(Decision::Reject),
+            other => Err(format!("未知的审核结论: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 给出固定结论的分类器
+    struct Fixed {
+        name: &'static str,
+        verdict: Verdict,
+    }
+
+    #[async_trait]
+    impl ContentClassifier for Fixed {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn classify(&self, _content: &str) -> Verdict {
+            self.verdict.clone()
+        }
+    }
+
+    fn fixed(name: &'static str, decision: Decision, reason: &str) -> Arc<dyn ContentClassifier> {
+        Arc::new(Fixed {
+            name,
+            verdict: Verdict {
+                decision,
+                reasons: vec![reason.to_string()],
+                content: None,
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_keeps_most_severe_decision() {
+        let pipeline = ModerationPipeline::new(vec![
+            fixed("a", Decision::Mask, "masked"),
+            fixed("b", Decision::Hold, "needs review"),
+            fixed("c", Decision::Allow, "looks fine"),
+        ]);
+        let outcome = pipeline.run("内容").await;
+        assert_eq!(outcome.decision, Decision::Hold);
+        assert_eq!(outcome.reasons.len(), 3);
+        assert_eq!(outcome.reasons[1].classifier, "b");
+        assert_eq!(outcome.content, "内容");
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_stops_after_reject() {
+        let pipeline = ModerationPipeline::new(vec![
+            fixed("a", Decision::Mask, "masked"),
+            fixed("b", Decision::Reject, "banned"),
+            fixed("c", Decision::Hold, "never reached"),
+        ]);
+        let outcome = pipeline.run("内容").await;
+        assert_eq!(outcome.decision, Decision::Reject);
+        assert_eq!(outcome.reasons.len(), 2);
+        assert_eq!(outcome.summary(), "banned");
+    }
+
+    #[tokio::test]
+    async fn test_keyword_classifier_masks_content_for_later_classifiers() {
+        let pipeline = ModerationPipeline::new(vec![Arc::new(KeywordClassifier::with_filter(
+            crate::utils::filter::SensitiveWordFilter::new(["犯罪"]),
+        ))]);
+        let outcome = pipeline.run("他犯罪了").await;
+        assert_eq!(outcome.decision, Decision::Mask);
+        assert_eq!(outcome.content, "他**了");
+    }
+}
@@ -0,0 +1,299 @@
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::{ContentClassifier, Decision, Verdict};
+use crate::utils::filter::{mask_spans, stats, FilterAction, FilterHit};
+
+/// 个人信息命中在统计中使用的分类
+pub const PII_CATEGORY: &str = "个人信息";
+
+lazy_static! {
+    /// 邮箱地址
+    static ref EMAIL: Regex =
+        Regex::new(r"(?i)[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}").unwrap();
+
+    /// 18 位身份证号：6 位地区码、8 位出生日期、3 位顺序码和 1 位校验码
+    static ref ID_CARD: Regex = Regex::new(
+        r"[1-9][0-9]{5}(?:18|19|20)[0-9]{2}(?:0[1-9]|1[0-2])(?:0[1-9]|[12][0-9]|3[01])[0-9]{3}[0-9Xx]"
+    )
+    .unwrap();
+
+    /// 中国大陆手机号，允许 +86 前缀以及用空格或短横线分隔的 3-4-4 写法
+    static ref PHONE: Regex =
+        Regex::new(r"(?:(?:\+|00)86[ -]?)?1[3-9][0-9](?:[ -]?[0-9]{4}){2}").unwrap();
+
+    /// QQ 号：前面需要有 QQ、扣扣等提示词，否则与普通数字无法区分
+    static ref QQ: Regex = Regex::new(
+        r"(?i)(?:qq|扣扣|企鹅)\s*(?:号码|号)?\s*(?:是|为)?\s*[:：]?\s*(?P<id>[1-9][0-9]{4,10})"
+    )
+    .unwrap();
+
+    /// 微信号：前面需要有微信、vx 等提示词；微信号以字母开头，6 到 20 位
+    static ref WECHAT: Regex = Regex::new(
+        r"(?i)(?:微信|威信|薇信|wechat|weixin|vx|wx|v信)\s*(?:号码|号)?\s*(?:是|为)?\s*[:：]?\s*(?P<id>[a-z][-_a-z0-9]{5,19})"
+    )
+    .unwrap();
+}
+
+/// 个人信息的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    IdCard,
+    Phone,
+    Qq,
+    Wechat,
+}
+
+impl PiiKind {
+    /// 按检测优先级排列：同一段文字只归入最先检测到它的种类，例如身份证号中的数字不会再被当作手机号
+    pub const ALL: [PiiKind; 5] = [
+        PiiKind::Email,
+        PiiKind::IdCard,
+        PiiKind::Phone,
+        PiiKind::Qq,
+        PiiKind::Wechat,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PiiKind::Email => "email",
+            PiiKind::IdCard => "id_card",
+            PiiKind::Phone => "phone",
+            PiiKind::Qq => "qq",
+            PiiKind::Wechat => "wechat",
+        }
+    }
+
+    /// 统计中使用的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            PiiKind::Email => "邮箱",
+            PiiKind::IdCard => "身份证号",
+            PiiKind::Phone => "手机号",
+            PiiKind::Qq => "QQ号",
+            PiiKind::Wechat => "微信号",
+        }
+    }
+
+    fn regex(self) -> &'static Regex {
+        match self {
+            PiiKind::Email => &EMAIL,
+            PiiKind::IdCard => &ID_CARD,
+            PiiKind::Phone => &PHONE,
+            PiiKind::Qq => &QQ,
+            PiiKind::Wechat => &WECHAT,
+        }
+    }
+}
+
+impl FromStr for PiiKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PiiKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("未知的个人信息种类: {}", s))
+    }
+}
+
+/// 内容中的一处个人信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiiMatch {
+    pub kind: PiiKind,
+    /// 个人信息本身在原文中的字节区间，不包括前面的提示词
+    pub span: Range<usize>,
+}
+
+/// 查找内容中的个人信息
+///
+/// # Arguments
+/// * `content` - 要检查的内容
+/// * `kinds` - 要检测的种类
+///
+/// # Returns
+/// * 按出现顺序排列、互不重叠的命中
+pub fn find_pii(content: &str, kinds: &[PiiKind]) -> Vec<PiiMatch> {
+    let mut matches: Vec<PiiMatch> = Vec::new();
+    for kind in PiiKind::ALL.into_iter().filter(|kind| kinds.contains(kind)) {
+        for captures in kind.regex().captures_iter(content) {
+            let m = captures.name("id").unwrap_or_else(|| captures.get(0).unwrap());
+            let span = m.range();
+            // 数字类信息前后紧邻其他数字时是更长数字串的一部分，不算命中
+            if matches!(kind, PiiKind::Phone | PiiKind::IdCard | PiiKind::Qq)
+                && !is_digit_boundary(content, &span)
+            {
+                continue;
+            }
+            if kind == PiiKind::IdCard && !is_valid_id_number(m.as_str()) {
+                continue;
+            }
+            if matches
+                .iter()
+                .any(|other| other.span.start < span.end && span.start < other.span.end)
+            {
+                continue;
+            }
+            matches.push(PiiMatch { kind, span });
+        }
+    }
+    matches.sort_by_key(|m| m.span.start);
+    matches
+}
+
+/// 判断区间前后是否都不紧邻数字
+fn is_digit_boundary(content: &str, span: &Range<usize>) -> bool {
+    let before = content[..span.start].chars().next_back();
+    let after = content[span.end..].chars().next();
+    !before.is_some_and(|c| c.is_ascii_digit()) && !after.is_some_and(|c| c.is_ascii_digit())
+}
+
+/// 按 GB 11643 校验 18 位身份证号的最后一位
+fn is_valid_id_number(number: &str) -> bool {
+    const WEIGHTS: [u32; 17] = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+    const CHECK_CODES: [char; 11] = ['1', '0', 'X', '9', '8', '7', '6', '5', '4', '3', '2'];
+
+    let chars: Vec<char> = number.chars().collect();
+    if chars.len() != 18 {
+        return false;
+    }
+    let mut sum = 0;
+    for (c, weight) in chars.iter().zip(WEIGHTS) {
+        match c.to_digit(10) {
+            Some(digit) => sum += digit * weight,
+            None => return false,
+        }
+    }
+    chars[17].to_ascii_uppercase() == CHECK_CODES[(sum % 11) as usize]
+}
+
+/// 检测手机号、微信号、QQ 号、邮箱和身份证号的分类器
+///
+/// 命中按配置的处理方式给出结论：mask 用 * 替换这些信息（QQ 号和微信号前的提示词保留），
+/// hold 转人工审核，reject 拒绝发布。命中与敏感词一样计入命中统计，
+/// 分类为“个人信息”，词为信息的种类，不记录信息本身。
+pub struct PiiClassifier {
+    action: FilterAction,
+    kinds: Vec<PiiKind>,
+}
+
+impl PiiClassifier {
+    pub fn new(action: FilterAction, kinds: Vec<PiiKind>) -> Self {
+        Self { action, kinds }
+    }
+}
+
+impl Default for PiiClassifier {
+    fn default() -> Self {
+        Self::new(FilterAction::Mask, PiiKind::ALL.to_vec())
+    }
+}
+
+#[async_trait]
+impl ContentClassifier for PiiClassifier {
+    fn name(&self) -> &str {
+        "pii"
+    }
+
+    async fn classify(&self, content: &str) -> Verdict {
+        let matches = find_pii(content, &self.kinds);
+        if matches.is_empty() {
+            return Verdict::default();
+        }
+
+        stats::record_hits(matches.iter().map(|m| FilterHit {
+            word: m.kind.label().to_string(),
+            category: PII_CATEGORY.to_string(),
+        }));
+
+        let mut kinds: Vec<&str> = Vec::new();
+        for m in &matches {
+            if !kinds.contains(&m.kind.as_str()) {
+                kinds.push(m.kind.as_str());
+            }
+        }
+
+        let content = (self.action == FilterAction::Mask)
+            .then(|| mask_spans(content, matches.iter().map(|m| m.span.clone())));
+        Verdict {
+            decision: Decision::from(self.action),
+            reasons: vec![format!("contains personal information: {}", kinds.join(", "))],
+            content,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &str) -> Vec<(PiiKind, &str)> {
+        find_pii(content, &PiiKind::ALL)
+            .into_iter()
+            .map(|m| (m.kind, &content[m.span]))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_contact_details() {
+        assert_eq!(
+            kinds("电话 138-1234-5678，或者 +86 139 1234 5678"),
+            vec![
+                (PiiKind::Phone, "138-1234-5678"),
+                (PiiKind::Phone, "+86 139 1234 5678")
+            ]
+        );
+        assert_eq!(
+            kinds("加我微信：abc_12345 或 QQ号 123456789，邮箱 a.b@example.com"),
+            vec![
+                (PiiKind::Wechat, "abc_12345"),
+                (PiiKind::Qq, "123456789"),
+                (PiiKind::Email, "a.b@example.com")
+            ]
+        );
+        // 更长数字串中的片段和没有提示词的数字都不算
+        assert!(kinds("订单号 2013812345678901 共 123456789 件").is_empty());
+        // 邮箱中的手机号只算作邮箱
+        assert_eq!(
+            kinds("13812345678@163.com"),
+            vec![(PiiKind::Email, "13812345678@163.com")]
+        );
+    }
+
+    #[test]
+    fn test_id_numbers_require_valid_checksum() {
+        assert!(is_valid_id_number("11010519491231002X"));
+        assert!(is_valid_id_number("11010519491231002x"));
+        assert!(!is_valid_id_number("110105194912310021"));
+        assert_eq!(
+            kinds("身份证 11010519491231002X"),
+            vec![(PiiKind::IdCard, "11010519491231002X")]
+        );
+        assert!(kinds("身份证 110105194912310021").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_classifier_masks_or_rejects() {
+        let content = "我的微信是 zhang_san88 有事找我";
+        let verdict = PiiClassifier::default().classify(content).await;
+        assert_eq!(verdict.decision, Decision::Mask);
+        assert_eq!(
+            verdict.content.as_deref(),
+            Some("我的微信是 *********** 有事找我")
+        );
+        assert_eq!(verdict.reasons, vec!["contains personal information: wechat"]);
+
+        let verdict = PiiClassifier::new(FilterAction::Reject, vec![PiiKind::Phone])
+            .classify("call 13812345678 or wx: zhang_san88")
+            .await;
+        assert_eq!(verdict.decision, Decision::Reject);
+        assert_eq!(verdict.content, None);
+        assert_eq!(verdict.reasons, vec!["contains personal information: phone"]);
+    }
+}
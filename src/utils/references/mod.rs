@@ -0,0 +1,305 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::ops::Range;
+use uuid::Uuid;
+
+use crate::models::format::ContentFormat;
+use crate::models::kind::ContentKind;
+use crate::models::reference::Backlink;
+use crate::utils::markdown::literal_ranges;
+
+pub mod store;
+
+/// 短 id 的长度：帖子 id 的前 8 位十六进制数字
+pub const SHORT_ID_LEN: usize = 8;
+
+lazy_static! {
+    /// `>>shortid` 和 `>>#floor` 引用，后面紧跟的字符在匹配后另行检查
+    static ref REFERENCE: Regex = Regex::new(r"(?i)>>(?:#([0-9]{1,6})|([0-9a-f]{8}))").unwrap();
+    /// 净化后内容中的站内链接，链接文字不含其他标签
+    static ref INTERNAL_LINK: Regex = Regex::new(
+        r##"<a href="#(post|comment)-([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})"[^>]*>([^<]*)</a>"##
+    )
+    .unwrap();
+}
+
+/// 内容中写出的一个引用
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Reference {
+    /// `>>shortid`：以这 8 位十六进制数字开头的帖子，小写
+    ShortId(String),
+    /// `>>#floor`：同一帖子下的第几楼评论
+    Floor(i32),
+}
+
+/// 引用在内容中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceMatch {
+    pub span: Range<usize>,
+    pub reference: Reference,
+}
+
+/// 引用指向的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReferenceTarget {
+    pub kind: ContentKind,
+    pub id: Uuid,
+}
+
+impl ReferenceTarget {
+    /// 站内链接，只是页面中的锚点
+    pub fn href(&self) -> String {
+        format!("#{}-{}", self.kind.as_str(), self.id)
+    }
+}
+
+/// 帖子的短 id
+pub fn short_id(id: Uuid) -> String {
+    id.simple().to_string()[..SHORT_ID_LEN].to_string()
+}
+
+/// 找出内容中的引用
+///
+/// 引用后面紧跟字母、数字或 `-` 时不算引用，例如 `>>abcd12345` 和粘贴的完整 id；
+/// 代码、链接和标签中的文字不解析
+pub fn find_references(text: &str, format: ContentFormat) -> Vec<ReferenceMatch> {
+    let skipped = literal_ranges(text, format);
+    REFERENCE
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let span = captures.get(0)?.range();
+            let continues = text[span.end..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == '-');
+            if continues || skipped.iter().any(|range| range.contains(&span.start)) {
+                return None;
+            }
+            let reference = match (captures.get(1), captures.get(2)) {
+                (Some(floor), _) => Reference::Floor(floor.as_str().parse().ok()?),
+                (_, Some(id)) => Reference::ShortId(id.as_str().to_ascii_lowercase()),
+                _ => return None,
+            };
+            Some(ReferenceMatch { span, reference })
+        })
+        .collect()
+}
+
+/// 把引用替换为站内链接
+///
+/// 在审核之后、格式转换之前处理。找到的引用写成对应格式的链接，链接文字保持原样；
+/// 找不到的引用作为普通文字显示，Markdown 中转义 `>`，避免行首的引用被当成引用块
+///
+/// # Arguments
+/// * `text` - 审核流水线处理后的文本
+/// * `targets` - 已经找到的引用
+pub fn link_references(
+    text: &str,
+    format: ContentFormat,
+    targets: &HashMap<Reference, ReferenceTarget>,
+) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for m in find_references(text, format) {
+        result.push_str(&text[last..m.span.start]);
+        // 去掉开头的 >>
+        let label = &text[m.span.start + 2..m.span.end];
+        match (targets.get(&m.reference), format) {
+            (Some(target), ContentFormat::Markdown) => {
+                let _ = write!(result, "[\\>\\>{}]({})", label, target.href());
+            }
+            (Some(target), ContentFormat::Html) => {
+                let _ = write!(result, "<a href=\"{}\">>>{}</a>", target.href(), label);
+            }
+            (None, ContentFormat::Markdown) => {
+                let _ = write!(result, "\\>\\>{}", label);
+            }
+            (None, ContentFormat::Html) => result.push_str(&text[m.span.clone()]),
+        }
+        last = m.span.end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// 保存内容引用的内容，用于反向链接
+///
+/// 引用已经写进显示内容，保存失败只记录日志，不影响发布
+pub async fn record_references(
+    pool: &PgPool,
+    kind: ContentKind,
+    id: Uuid,
+    targets: &[ReferenceTarget],
+) {
+    if let Err(e) = store::record_references(pool, kind, id, targets).await {
+        tracing::warn!("保存{} {} 的引用失败: {}", kind.as_str(), id, e);
+    }
+}
+
+/// 内容中站内链接指向的内容
+pub fn linked_targets(html: &str) -> Vec<ReferenceTarget> {
+    INTERNAL_LINK
+        .captures_iter(html)
+        .filter_map(|captures| {
+            Some(ReferenceTarget {
+                kind: ContentKind::from_db(&captures[1]),
+                id: Uuid::parse_str(&captures[2]).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// 去掉指向不可见内容的站内链接，链接文字保持原样
+pub fn unlink_hidden(html: &str, visible: &HashSet<Uuid>) -> String {
+    INTERNAL_LINK
+        .replace_all(html, |captures: &regex::Captures| {
+            let shown = Uuid::parse_str(&captures[2]).is_ok_and(|id| visible.contains(&id));
+            if shown { captures[0].to_string() } else { captures[3].to_string() }
+        })
+        .into_owned()
+}
+
+/// 按被引用内容现在的状态去掉输出内容中的站内链接
+///
+/// 链接在写入时生成，被引用的内容之后可能被暂缓或拒绝，也可能在之后才公开，
+/// 所以输出前只保留指向已公开内容的链接。查询失败时去掉所有站内链接
+pub async fn unlink_unpublished<'a>(
+    pool: &PgPool,
+    contents: impl IntoIterator<Item = &'a mut String>,
+) {
+    let mut contents: Vec<&mut String> = contents.into_iter().collect();
+    let targets: Vec<ReferenceTarget> = contents
+        .iter()
+        .flat_map(|content| linked_targets(content))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+    let visible = store::published_targets(pool, &targets)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("无法查询被引用内容的状态: {}", e);
+            HashSet::new()
+        });
+    for content in &mut contents {
+        **content = unlink_hidden(content, &visible);
+    }
+}
+
+/// 查询引用了某个帖子或它的评论的内容，查询失败时返回空列表
+pub async fn referenced_by(pool: &PgPool, post_id: Uuid) -> Vec<Backlink> {
+    store::backlinks(pool, post_id).await.unwrap_or_else(|e| {
+        tracing::warn!("无法查询引用帖子 {} 的内容: {}", post_id, e);
+        Vec::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::render::render_html;
+    use crate::utils::sanitize::SanitizerPolicy;
+
+    fn references(text: &str) -> Vec<Reference> {
+        find_references(text, ContentFormat::Markdown)
+            .into_iter()
+            .map(|m| m.reference)
+            .collect()
+    }
+
+    fn targets() -> HashMap<Reference, ReferenceTarget> {
+        let post = Uuid::parse_str("abcd1234-0000-4000-8000-000000000001").unwrap();
+        let comment = Uuid::parse_str("00000000-0000-4000-8000-000000000002").unwrap();
+        [
+            (
+                Reference::ShortId("abcd1234".to_string()),
+                ReferenceTarget { kind: ContentKind::Post, id: post },
+            ),
+            (
+                Reference::Floor(3),
+                ReferenceTarget { kind: ContentKind::Comment, id: comment },
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_find_references() {
+        assert_eq!(
+            references(">>ABCD1234 说得对，>>#3 也是\n>>>#12"),
+            vec![
+                Reference::ShortId("abcd1234".to_string()),
+                Reference::Floor(3),
+                Reference::Floor(12),
+            ]
+        );
+        assert!(references(">>abcd12345 >>abcd1234-0000 >>#1234567 >>xyz12345 >#3").is_empty());
+        assert!(references("`>>abcd1234`\n\n```\n>>#3\n```\n\n[>>#3](https://example.com)").is_empty());
+        assert!(find_references(r#"<a title=">>#3">x</a>"#, ContentFormat::Html).is_empty());
+    }
+
+    #[test]
+    fn test_link_references() {
+        let targets = targets();
+        assert_eq!(
+            link_references(">>abcd1234 和 >>#3，>>#4 不存在", ContentFormat::Markdown, &targets),
+            "[\\>\\>abcd1234](#post-abcd1234-0000-4000-8000-000000000001) 和 \
+             [\\>\\>#3](#comment-00000000-0000-4000-8000-000000000002)，\\>\\>#4 不存在"
+        );
+        assert_eq!(
+            link_references(">>#3 <b>>>#4</b>", ContentFormat::Html, &targets),
+            "<a href=\"#comment-00000000-0000-4000-8000-000000000002\">>>#3</a> <b>>>#4</b>"
+        );
+    }
+
+    #[test]
+    fn test_rendered_references() {
+        let targets = targets();
+        let policy = SanitizerPolicy::default();
+        let render = |text: &str| {
+            let linked = link_references(text, ContentFormat::Markdown, &targets);
+            render_html(&linked, ContentFormat::Markdown, &policy, true)
+        };
+        assert_eq!(
+            render(">>abcd1234\n同意"),
+            "<p><a href=\"#post-abcd1234-0000-4000-8000-000000000001\" rel=\"nofollow noreferrer noopener\">\
+             &gt;&gt;abcd1234</a><br>\n同意</p>\n"
+        );
+        // 找不到的引用在行首也不会变成引用块
+        assert_eq!(render(">>ffff0000 不存在"), "<p>&gt;&gt;ffff0000 不存在</p>\n");
+    }
+
+    #[test]
+    fn test_unlink_hidden_targets() {
+        let targets = targets();
+        let policy = SanitizerPolicy::default();
+        let linked = link_references(">>abcd1234 和 >>#3", ContentFormat::Markdown, &targets);
+        let html = render_html(&linked, ContentFormat::Markdown, &policy, true);
+        assert_eq!(
+            linked_targets(&html),
+            vec![targets[&Reference::ShortId("abcd1234".to_string())], targets[&Reference::Floor(3)]]
+        );
+
+        // 被引用的评论之后被暂缓，只保留帖子的链接
+        let post = Uuid::parse_str("abcd1234-0000-4000-8000-000000000001").unwrap();
+        assert_eq!(
+            unlink_hidden(&html, &[post].into_iter().collect()),
+            "<p><a href=\"#post-abcd1234-0000-4000-8000-000000000001\" rel=\"nofollow noreferrer noopener\">\
+             &gt;&gt;abcd1234</a> 和 &gt;&gt;#3</p>\n"
+        );
+        assert_eq!(
+            unlink_hidden(&html, &HashSet::new()),
+            "<p>&gt;&gt;abcd1234 和 &gt;&gt;#3</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_short_id() {
+        let id = Uuid::parse_str("ABCD1234-0000-4000-8000-000000000001").unwrap();
+        assert_eq!(short_id(id), "abcd1234");
+    }
+}
@@ -0,0 +1,174 @@
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use super::{Reference, ReferenceTarget};
+use crate::models::kind::ContentKind;
+use crate::models::reference::Backlink;
+
+/// 找出引用指向的内容
+///
+/// 不论内容现在是否公开都会找到，是否显示为链接在输出时按当前状态决定，
+/// 之后才公开的内容也能显示链接。有多个帖子以同样的 8 位开头时不算找到；
+/// 楼层只匹配同一帖子下的评论。找不到的引用不在结果中
+///
+/// # Arguments
+/// * `references` - 内容中写出的引用
+/// * `thread` - 评论所属的帖子，帖子为 None，此时楼层引用都找不到
+pub async fn resolve_references(
+    pool: &PgPool,
+    references: &[Reference],
+    thread: Option<Uuid>,
+) -> Result<HashMap<Reference, ReferenceTarget>, sqlx::Error> {
+    let mut short_ids = Vec::new();
+    let mut floors = Vec::new();
+    for reference in references {
+        match reference {
+            Reference::ShortId(short_id) => short_ids.push(short_id.clone()),
+            Reference::Floor(floor) => floors.push(*floor),
+        }
+    }
+
+    let mut targets = HashMap::new();
+    if !short_ids.is_empty() {
+        let rows = sqlx::query(
+            r#"
+            SELECT left(id::text, 8) AS short_id, (array_agg(id))[1] AS id
+            FROM posts
+            WHERE left(id::text, 8) = ANY($1)
+            GROUP BY left(id::text, 8)
+            HAVING COUNT(*) = 1
+            "#,
+        )
+        .bind(&short_ids)
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            targets.insert(
+                Reference::ShortId(row.get("short_id")),
+                ReferenceTarget { kind: ContentKind::Post, id: row.get("id") },
+            );
+        }
+    }
+    if let (Some(post_id), false) = (thread, floors.is_empty()) {
+        let rows = sqlx::query(
+            r#"
+            SELECT floor, id FROM comments
+            WHERE post_id = $1 AND floor = ANY($2)
+            "#,
+        )
+        .bind(post_id)
+        .bind(&floors)
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            targets.insert(
+                Reference::Floor(row.get("floor")),
+                ReferenceTarget { kind: ContentKind::Comment, id: row.get("id") },
+            );
+        }
+    }
+    Ok(targets)
+}
+
+/// 找出引用指向的内容中现在公开的部分
+///
+/// 评论所属的帖子也要公开
+pub async fn published_targets(
+    pool: &PgPool,
+    targets: &[ReferenceTarget],
+) -> Result<HashSet<Uuid>, sqlx::Error> {
+    let ids_of = |kind: ContentKind| -> Vec<Uuid> {
+        targets.iter().filter(|target| target.kind == kind).map(|target| target.id).collect()
+    };
+    let post_ids = ids_of(ContentKind::Post);
+    let comment_ids = ids_of(ContentKind::Comment);
+    let ids = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT id FROM posts WHERE id = ANY($1) AND status = 'published'
+        UNION ALL
+        SELECT c.id FROM comments c
+        JOIN posts p ON p.id = c.post_id AND p.status = 'published'
+        WHERE c.id = ANY($2) AND c.status = 'published'
+        "#,
+    )
+    .bind(&post_ids)
+    .bind(&comment_ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(ids.into_iter().collect())
+}
+
+/// 替换一条内容引用的内容
+pub async fn record_references(
+    pool: &PgPool,
+    kind: ContentKind,
+    id: Uuid,
+    targets: &[ReferenceTarget],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM content_references WHERE source_kind = $1 AND source_id = $2")
+        .bind(kind.as_str())
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    if !targets.is_empty() {
+        let target_kinds: Vec<&str> = targets.iter().map(|t| t.kind.as_str()).collect();
+        let target_ids: Vec<Uuid> = targets.iter().map(|t| t.id).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO content_references (source_kind, source_id, target_kind, target_id)
+            SELECT $1, $2, target_kind, target_id
+            FROM unnest($3::text[], $4::uuid[]) AS targets(target_kind, target_id)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(kind.as_str())
+        .bind(id)
+        .bind(&target_kinds)
+        .bind(&target_ids)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 查询引用了某个帖子或它的评论的内容
+///
+/// 只返回已公开的帖子和评论，被引用的评论也要公开，按引用的时间排序
+pub async fn backlinks(pool: &PgPool, post_id: Uuid) -> Result<Vec<Backlink>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT r.source_kind, r.source_id, r.target_kind, r.target_id,
+            COALESCE(p.id, c.post_id) AS post_id, c.floor
+        FROM content_references r
+        LEFT JOIN posts p ON r.source_kind = 'post' AND p.id = r.source_id
+            AND p.status = 'published'
+        LEFT JOIN comments c ON r.source_kind = 'comment' AND c.id = r.source_id
+            AND c.status = 'published'
+        WHERE ((r.target_kind = 'post' AND r.target_id = $1)
+            OR (r.target_kind = 'comment'
+                AND r.target_id IN (
+                    SELECT id FROM comments WHERE post_id = $1 AND status = 'published')))
+            AND (p.id IS NOT NULL OR c.id IS NOT NULL)
+        ORDER BY r.created_at, r.source_id
+        "#,
+    )
+    .bind(post_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Backlink {
+            kind: ContentKind::from_db(row.get("source_kind")),
+            id: row.get("source_id"),
+            post_id: row.get("post_id"),
+            floor: row.get("floor"),
+            target_kind: ContentKind::from_db(row.get("target_kind")),
+            target_id: row.get("target_id"),
+        })
+        .collect())
+}
+
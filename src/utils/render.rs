@@ -0,0 +1,405 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::models::format::ContentFormat;
+use crate::models::kind::ContentKind;
+use crate::utils::filter::current_filter;
+use crate::utils::markdown::markdown_to_html;
+use crate::utils::moderation::{ModerationOutcome, ModerationPipeline};
+use crate::utils::references::store::resolve_references;
+use crate::utils::references::{find_references, link_references, ReferenceTarget};
+use crate::utils::sanitize::{policy_for, sanitize_content, sanitize_html, SanitizerPolicy};
+use crate::utils::text::html_to_text;
+
+/// 启动时每批补充的已有内容行数
+const REFRESH_BATCH_SIZE: i64 = 500;
+
+lazy_static! {
+    static ref OPTIONS: RenderOptions = RenderOptions::from_env();
+
+    /// HTML 格式原文中的标记：标签（属性值中可以有 `>`）、注释和声明
+    static ref MARKUP: Regex = Regex::new(
+        r#"<(?:/?[A-Za-z](?:[^>"']|"[^"]*"|'[^']*')*>|!--(?s:.*?)-->|![^>]*>)"#
+    )
+    .unwrap();
+}
+
+/// 生成显示内容的配置
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// 请求没有指定格式时使用的格式
+    pub default_format: ContentFormat,
+    /// 是否接受原始 HTML：为 false 时拒绝 html 格式的内容，Markdown 中的标签作为普通文字显示
+    pub allow_html: bool,
+}
+
+impl RenderOptions {
+    /// 从环境变量读取配置
+    ///
+    /// * `CONTENT_DEFAULT_FORMAT` - 默认格式，`html` 或 `markdown`；不设置时为 `html`，
+    ///   不接受原始 HTML 时为 `markdown`
+    /// * `CONTENT_ALLOW_HTML` - 是否接受原始 HTML，默认 true
+    pub fn from_env() -> Self {
+        let allow_html = env::var("CONTENT_ALLOW_HTML").map_or(true, |v| v != "false" && v != "0");
+        let fallback = if allow_html {
+            ContentFormat::Html
+        } else {
+            ContentFormat::Markdown
+        };
+        let default_format = match env::var("CONTENT_DEFAULT_FORMAT") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                tracing::warn!("{}，使用默认格式 {}", e, fallback.as_str());
+                fallback
+            }),
+            Err(_) => fallback,
+        };
+        Self {
+            default_format,
+            allow_html,
+        }
+    }
+
+    /// 确定新内容使用的格式
+    ///
+    /// # Returns
+    /// * 请求指定的格式或默认格式；不接受原始 HTML 时请求 html 格式返回错误信息
+    pub fn resolve_format(&self, requested: Option<ContentFormat>) -> Result<ContentFormat, String> {
+        let format = requested.unwrap_or(self.default_format);
+        if format == ContentFormat::Html && !self.allow_html {
+            return Err("HTML content is not accepted, use markdown".to_string());
+        }
+        Ok(format)
+    }
+}
+
+/// 获取生成显示内容的配置
+pub fn render_options() -> &'static RenderOptions {
+    &OPTIONS
+}
+
+/// 由用户提交的原文生成的可显示内容
+#[derive(Debug, Clone)]
+pub struct RenderedContent {
+    /// 审核流水线的结果，其中的内容是替换敏感词后、转换和净化前的文本
+    pub outcome: ModerationOutcome,
+    /// 净化后可直接显示的 HTML
+    pub html: String,
+    /// 由 HTML 生成的纯文本，与 HTML 一起保存
+    pub text: String,
+    /// 生成时使用的词表版本
+    pub filter_version: String,
+    /// 生成时使用的净化策略名称
+    pub sanitizer_policy: &'static str,
+    /// 生成时使用的净化策略版本
+    pub sanitizer_version: &'static str,
+    /// 内容中找到的引用，按第一次出现的顺序，不重复
+    pub references: Vec<ReferenceTarget>,
+}
+
+/// 把审核后的文本按格式转换为 HTML 并净化
+///
+/// 这是生成显示内容时唯一改动标记的一步：审核流水线只替换或删去命中的文字，
+/// 标签、属性和链接协议全部由净化策略决定，不在允许列表中的标记被去掉，其余文字原样保留。
+/// HTML 格式中标签以外的 `&` 按普通文字处理，`&#60;`、`&amp;` 等照原样显示
+///
+/// # Arguments
+/// * `text` - 审核流水线处理后的文本
+/// * `format` - 原文格式
+/// * `policy` - 净化策略
+/// * `allow_html` - Markdown 中的 HTML 标签是否交给净化策略处理，否则作为普通文字显示
+pub fn render_html(
+    text: &str,
+    format: ContentFormat,
+    policy: &SanitizerPolicy,
+    allow_html: bool,
+) -> String {
+    match format {
+        ContentFormat::Html => sanitize_content(&escape_text_ampersands(text), policy),
+        ContentFormat::Markdown => sanitize_html(&markdown_to_html(text, allow_html), policy),
+    }
+}
+
+/// 转义 HTML 标记以外的 `&`，标签中的属性值不变
+fn escape_text_ampersands(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut last = 0;
+    for markup in MARKUP.find_iter(text) {
+        escaped.push_str(&text[last..markup.start()].replace('&', "&amp;"));
+        escaped.push_str(markup.as_str());
+        last = markup.end();
+    }
+    escaped.push_str(&text[last..].replace('&', "&amp;"));
+    escaped
+}
+
+/// 把原文依次经过审核流水线、引用解析、格式转换和净化，生成可显示的内容
+///
+/// 发帖、发评论以及管理员重新生成内容时都使用这个函数，保证同样的原文得到同样的结果。
+/// 审核流水线处理的是原文，因此 Markdown 的语法符号不会影响敏感词匹配；
+/// 审核后文本中的 `>>shortid` 和 `>>#floor` 引用替换为站内链接，找不到的引用作为普通文字显示；
+/// 净化使用内容种类对应的净化策略
+///
+/// # Arguments
+/// * `thread` - 评论所属的帖子，用于解析楼层引用；帖子为 None
+pub async fn render_content(
+    pool: &PgPool,
+    pipeline: &ModerationPipeline,
+    raw: &str,
+    format: ContentFormat,
+    kind: ContentKind,
+    thread: Option<Uuid>,
+) -> RenderedContent {
+    let filter_version = current_filter().version().to_string();
+    let policy = policy_for(kind);
+    let outcome = pipeline.run(raw).await;
+
+    let found: Vec<_> = find_references(&outcome.content, format)
+        .into_iter()
+        .map(|m| m.reference)
+        .collect();
+    let targets = if found.is_empty() {
+        Default::default()
+    } else {
+        resolve_references(pool, &found, thread).await.unwrap_or_else(|e| {
+            tracing::warn!("解析内容中的引用失败，引用按普通文字显示: {}", e);
+            Default::default()
+        })
+    };
+    let mut references = Vec::new();
+    for target in found.iter().filter_map(|reference| targets.get(reference)) {
+        if !references.contains(target) {
+            references.push(*target);
+        }
+    }
+    let linked = link_references(&outcome.content, format, &targets);
+
+    let html = render_html(&linked, format, policy, OPTIONS.allow_html);
+    let text = html_to_text(&html);
+    RenderedContent {
+        outcome,
+        html,
+        text,
+        filter_version,
+        sanitizer_policy: policy.name(),
+        sanitizer_version: policy.version(),
+        references,
+    }
+}
+
+/// 为已有内容补上写入时生成的显示内容和纯文本
+///
+/// 显示内容在写入时生成，输出时不再净化。保存策略版本之前的内容在输出时才把换行转为 `<br>`
+/// 并用内置的 `default` 策略净化，启动时把这一步的结果写回一次，显示效果不变。
+/// 已经记录了策略版本的内容不在这里改动：它们的 HTML 已经净化过，用新策略再净化一遍
+/// 只会去掉标记而无法恢复，需要按当前策略生成时使用重新生成接口（见 [`count_stale_html`]）。
+/// 保存纯文本之前的内容由保存的 HTML 生成纯文本。
+///
+/// # Returns
+/// * 更新的行数，或者数据库错误
+pub async fn backfill_stored_content(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let policy = SanitizerPolicy::default();
+    let mut updated = 0;
+    for kind in [ContentKind::Post, ContentKind::Comment] {
+        let select = format!(
+            r#"
+            SELECT id, content, sanitizer_version IS NULL AS legacy
+            FROM {}
+            WHERE (sanitizer_version IS NULL OR content_text IS NULL)
+                AND ($1::uuid IS NULL OR id > $1)
+            ORDER BY id
+            LIMIT $2
+            "#,
+            kind.table()
+        );
+        let convert = format!(
+            r#"
+            UPDATE {}
+            SET content = $2, content_text = $3, sanitizer_policy = $4, sanitizer_version = $5
+            WHERE id = $1 AND sanitizer_version IS NULL
+            "#,
+            kind.table()
+        );
+        let fill_text = format!(
+            "UPDATE {} SET content_text = $2 WHERE id = $1 AND content = $3",
+            kind.table()
+        );
+
+        let mut last: Option<Uuid> = None;
+        loop {
+            let rows: Vec<(Uuid, String, bool)> = sqlx::query_as(&select)
+                .bind(last)
+                .bind(REFRESH_BATCH_SIZE)
+                .fetch_all(pool)
+                .await?;
+            let Some((id, _, _)) = rows.last() else {
+                break;
+            };
+            last = Some(*id);
+
+            let mut tx = pool.begin().await?;
+            for (id, content, legacy) in &rows {
+                if *legacy {
+                    let html = sanitize_content(content, &policy);
+                    sqlx::query(&convert)
+                        .bind(id)
+                        .bind(&html)
+                        .bind(html_to_text(&html))
+                        .bind(policy.name())
+                        .bind(policy.version())
+                        .execute(&mut *tx)
+                        .await?;
+                } else {
+                    // 期间内容被重新生成时已经带有纯文本，不覆盖
+                    sqlx::query(&fill_text)
+                        .bind(id)
+                        .bind(html_to_text(content))
+                        .bind(content)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+            tx.commit().await?;
+            updated += rows.len() as u64;
+            tracing::debug!("补充已有{}的显示内容和纯文本，累计 {} 行", kind.table(), updated);
+        }
+    }
+    Ok(updated)
+}
+
+/// 统计净化策略或版本与当前配置不同的内容
+///
+/// 这些内容保留原有的显示内容，可以用重新生成接口从原文按当前策略生成
+pub async fn count_stale_html(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    let mut stale = 0;
+    for kind in [ContentKind::Post, ContentKind::Comment] {
+        let policy = policy_for(kind);
+        let query = format!(
+            r#"
+            SELECT COUNT(*)
+            FROM {}
+            WHERE sanitizer_policy IS DISTINCT FROM $1 OR sanitizer_version IS DISTINCT FROM $2
+            "#,
+            kind.table()
+        );
+        let count: i64 = sqlx::query_scalar(&query)
+            .bind(policy.name())
+            .bind(policy.version())
+            .fetch_one(pool)
+            .await?;
+        stale += count;
+    }
+    Ok(stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::filter::SensitiveWordFilter;
+    use crate::utils::moderation::KeywordClassifier;
+    use crate::utils::sanitize::{PolicyConfig, SanitizerPolicies};
+    use regex::Regex;
+    use std::sync::Arc;
+
+    /// 每行一条用例，以 # 开头的行和空行忽略
+    fn corpus(text: &'static str) -> impl Iterator<Item = &'static str> {
+        text.lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+    }
+
+    /// 检查 HTML 中只有策略允许的标签、属性和链接协议
+    fn assert_only_allowed_markup(input: &str, html: &str) {
+        let tag = Regex::new(r"<(/?)([a-zA-Z][a-zA-Z0-9-]*)([^>]*)>").unwrap();
+        let attribute = Regex::new(r#"([^\s=/"']+)(?:="([^"]*)")?"#).unwrap();
+        let scheme = Regex::new(r"^([a-zA-Z][a-zA-Z0-9+.-]*):").unwrap();
+        let allowed_tags = PolicyConfig::default().tags;
+        for captures in tag.captures_iter(html) {
+            let name = captures[2].to_lowercase();
+            assert!(
+                allowed_tags.contains(&name),
+                "tag <{}> survived in {:?} -> {:?}",
+                name,
+                input,
+                html
+            );
+            for attr in attribute.captures_iter(&captures[3]) {
+                let attr_name = attr[1].to_lowercase();
+                assert!(
+                    ["href", "title", "rel"].contains(&attr_name.as_str()),
+                    "attribute {} survived in {:?} -> {:?}",
+                    attr_name,
+                    input,
+                    html
+                );
+                let value = attr.get(2).map_or("", |v| v.as_str());
+                if attr_name == "href" {
+                    if let Some(s) = scheme.captures(value.trim()) {
+                        assert!(
+                            ["http", "https", "mailto"].contains(&s[1].to_lowercase().as_str()),
+                            "href {:?} survived in {:?} -> {:?}",
+                            value,
+                            input,
+                            html
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_benign_text_is_unchanged() {
+        let pipeline = ModerationPipeline::new(vec![Arc::new(KeywordClassifier::with_filter(
+            SensitiveWordFilter::new(["犯罪"]),
+        ))]);
+        let policies = SanitizerPolicies::parse(include_str!("../../sanitizer.toml")).unwrap();
+        let inputs: Vec<_> = corpus(include_str!("../../tests/corpus/benign.txt")).collect();
+        let markdown: Vec<_> = corpus(include_str!("../../tests/corpus/benign.markdown.expected")).collect();
+        assert_eq!(markdown.len(), inputs.len(), "markdown 的预期结果与用例数量不同");
+
+        for (input, markdown) in inputs.iter().zip(markdown) {
+            // 审核流水线不改动没有命中的文字
+            assert_eq!(pipeline.run(input).await.content, *input);
+
+            for kind in [ContentKind::Post, ContentKind::Comment] {
+                let policy = policies.get(kind);
+                // HTML 格式中没有标记的文字照原样显示
+                let html = render_html(input, ContentFormat::Html, policy, true);
+                assert_eq!(html_to_text(&html), *input, "html / {}: {:?}", kind.as_str(), html);
+                // Markdown 格式按 CommonMark 解释代码和实体等语法，生成预期的显示内容
+                let html = render_html(input, ContentFormat::Markdown, policy, true);
+                assert_eq!(html, format!("{}\n", markdown), "markdown / {}: {:?}", kind.as_str(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_html_text_keeps_ampersands() {
+        let policy = SanitizerPolicy::default();
+        assert_eq!(
+            render_html(
+                "<b title=\"a > b\">&#60;b&#62;</b> &amp;&nbsp;<!-- &amp; --><a href=\"/?a=1&amp;b=2\">&</a>",
+                ContentFormat::Html,
+                &policy,
+                true
+            ),
+            "<b title=\"a &gt; b\">&amp;#60;b&amp;#62;</b> &amp;amp;&amp;nbsp;\
+             <a href=\"/?a=1&amp;b=2\" rel=\"nofollow noreferrer noopener\">&amp;</a>"
+        );
+    }
+
+    #[test]
+    fn test_malicious_markup_is_neutralized() {
+        let policy = SanitizerPolicy::default();
+        for input in corpus(include_str!("../../tests/corpus/malicious.txt")) {
+            for format in [ContentFormat::Html, ContentFormat::Markdown] {
+                for allow_html in [true, false] {
+                    let html = render_html(input, format, &policy, allow_html);
+                    assert_only_allowed_markup(input, &html);
+                }
+            }
+        }
+    }
+}
@@ -1,45 +1,273 @@
 
 use ammonia::Builder as AmmoniaBuilder;
 use lazy_static::lazy_static;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::models::kind::ContentKind;
+
+/// Name of the built-in policy, used for content types the configuration
+/// does not assign and when there is no configuration file at all.
+pub const DEFAULT_POLICY: &str = "default";
+
+/// Tags ammonia always removes together with their content. Allowing them
+/// would make ammonia panic, so policies are not allowed to list them.
+const FORBIDDEN_TAGS: &[&str] = &["script", "style"];
 
 lazy_static! {
-    // A single, reusable sanitizer policy for all content
-    static ref SANITIZER: ammonia::Builder<'static> = {
-        let mut builder = AmmoniaBuilder::default();
-        // Allow a minimal, safe set of tags
-        builder
-            .tags([
+    // The policies in effect, loaded once from the configuration file
+    static ref POLICIES: SanitizerPolicies = SanitizerPolicies::from_env();
+}
+
+/// The markup one policy lets through, as written in the configuration file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyConfig {
+    /// Allowed tags
+    pub tags: BTreeSet<String>,
+    /// Attributes allowed on every allowed tag
+    #[serde(default)]
+    pub generic_attributes: BTreeSet<String>,
+    /// Attributes allowed on specific tags
+    #[serde(default)]
+    pub tag_attributes: BTreeMap<String, BTreeSet<String>>,
+    /// Allowed URL schemes for links
+    #[serde(default = "default_url_schemes")]
+    pub url_schemes: BTreeSet<String>,
+    /// The rel attribute forced onto every link, if any
+    #[serde(default)]
+    pub link_rel: Option<String>,
+}
+
+fn default_url_schemes() -> BTreeSet<String> {
+    ["http", "https", "mailto"].into_iter().map(String::from).collect()
+}
+
+impl Default for PolicyConfig {
+    /// The original single policy: a minimal, safe set of tags, `href` on
+    /// anchors only, and no javascript:, data: or vbscript: URLs.
+    fn default() -> Self {
+        Self {
+            tags: [
                 "a", "b", "strong", "i", "em", "code", "pre", "p", "br", "ul", "ol", "li", "blockquote",
-            ].into_iter().collect::<HashSet<_>>())
-            // Only allow safe attributes on anchors
-            .generic_attributes(["title"].into_iter().collect::<HashSet<_>>())
-            .link_rel(Some("nofollow noreferrer noopener"))
-            // Restrict attributes per tag (only href for anchors)
-            .tag_attributes({
-                let mut m: HashMap<&str, HashSet<&str>> = HashMap::new();
-                m.insert("a", ["href"].into_iter().collect());
-                m
-            })
-            // Only safe URL schemes for links (disallow javascript:, data:, vbscript:)
-            .url_schemes(["http", "https", "mailto"].into_iter().collect::<HashSet<_>>());
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            generic_attributes: ["title".to_string()].into_iter().collect(),
+            tag_attributes: [("a".to_string(), ["href".to_string()].into_iter().collect())]
+                .into_iter()
+                .collect(),
+            url_schemes: default_url_schemes(),
+            link_rel: Some("nofollow noreferrer noopener".to_string()),
+        }
+    }
+}
+
+/// A named, compiled sanitizer policy.
+pub struct SanitizerPolicy {
+    name: &'static str,
+    version: String,
+    builder: AmmoniaBuilder<'static>,
+}
+
+impl SanitizerPolicy {
+    /// Builds a policy from its configuration.
+    ///
+    /// Policies are built once at startup, so the configured names are
+    /// leaked to get the `'static` lifetime the ammonia builder needs.
+    ///
+    /// # Returns
+    /// * The policy, or a message explaining why the configuration is invalid
+    pub fn new(name: &str, config: &PolicyConfig) -> Result<Self, String> {
+        if let Some(tag) = FORBIDDEN_TAGS.iter().find(|tag| config.tags.contains(**tag)) {
+            return Err(format!("净化策略 {} 不能允许 {} 标签", name, tag));
+        }
+        // ammonia refuses to clean when rel is both forced and allowed
+        let allows_rel = config.generic_attributes.contains("rel")
+            || config
+                .tag_attributes
+                .get("a")
+                .is_some_and(|attributes| attributes.contains("rel"));
+        if config.link_rel.is_some() && allows_rel {
+            return Err(format!(
+                "净化策略 {} 设置了 link_rel，不能再允许 rel 属性",
+                name
+            ));
+        }
+
+        let leak = |s: &String| -> &'static str { Box::leak(s.clone().into_boxed_str()) };
+        let mut builder = AmmoniaBuilder::default();
         builder
-    };
+            .tags(config.tags.iter().map(leak).collect())
+            .generic_attributes(config.generic_attributes.iter().map(leak).collect())
+            .tag_attributes(
+                config
+                    .tag_attributes
+                    .iter()
+                    .map(|(tag, attributes)| (leak(tag), attributes.iter().map(leak).collect()))
+                    .collect::<HashMap<_, _>>(),
+            )
+            .url_schemes(config.url_schemes.iter().map(leak).collect())
+            .link_rel(config.link_rel.as_ref().map(leak));
+
+        // The version is a digest of the configuration, so it changes
+        // whenever the allowed tags, attributes, URL schemes or rel change.
+        let canonical = serde_json::to_string(config).map_err(|e| e.to_string())?;
+        let digest = format!("{:x}", Sha256::digest(canonical.as_bytes()));
+
+        Ok(Self {
+            name: Box::leak(name.to_string().into_boxed_str()),
+            version: digest[..12].to_string(),
+            builder,
+        })
+    }
+
+    /// The name the policy has in the configuration file.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Version of the policy, stored alongside content rendered with it.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Cleans HTML according to this policy.
+    pub fn clean(&self, html: &str) -> String {
+        self.builder.clean(html).to_string()
+    }
+}
+
+impl Default for SanitizerPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_POLICY, &PolicyConfig::default())
+            .expect("the built-in sanitizer policy is valid")
+    }
+}
+
+/// The sanitizer configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SanitizerConfig {
+    /// Which policy each content type uses
+    #[serde(default)]
+    content: ContentPolicies,
+    /// Named policies
+    #[serde(default)]
+    policies: BTreeMap<String, PolicyConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContentPolicies {
+    post: Option<String>,
+    comment: Option<String>,
+}
+
+/// The policy each content type uses.
+pub struct SanitizerPolicies {
+    post: Arc<SanitizerPolicy>,
+    comment: Arc<SanitizerPolicy>,
+}
+
+impl Default for SanitizerPolicies {
+    fn default() -> Self {
+        let policy = Arc::new(SanitizerPolicy::default());
+        Self {
+            post: Arc::clone(&policy),
+            comment: policy,
+        }
+    }
+}
+
+impl SanitizerPolicies {
+    /// Parses a configuration file in TOML format.
+    ///
+    /// Content types without an assigned policy use the `default` policy,
+    /// which is the built-in one unless the file defines its own.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: SanitizerConfig = toml::from_str(text).map_err(|e| e.to_string())?;
+
+        let mut policies: HashMap<&str, Arc<SanitizerPolicy>> = HashMap::new();
+        for (name, policy) in &config.policies {
+            policies.insert(name, Arc::new(SanitizerPolicy::new(name, policy)?));
+        }
+        if !policies.contains_key(DEFAULT_POLICY) {
+            policies.insert(DEFAULT_POLICY, Arc::new(SanitizerPolicy::default()));
+        }
+
+        let lookup = |name: Option<&String>| {
+            let name = name.map_or(DEFAULT_POLICY, String::as_str);
+            policies
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("未知的净化策略: {}", name))
+        };
+        Ok(Self {
+            post: lookup(config.content.post.as_ref())?,
+            comment: lookup(config.content.comment.as_ref())?,
+        })
+    }
+
+    /// Loads the configuration file; a missing file means the built-in policy everywhere.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// Loads the file named by `SANITIZER_CONFIG_PATH` (default `sanitizer.toml`).
+    /// An invalid file is logged and replaced by the built-in policy, which is
+    /// the strictest one this service has ever used.
+    fn from_env() -> Self {
+        let path = env::var("SANITIZER_CONFIG_PATH").unwrap_or_else(|_| "sanitizer.toml".to_string());
+        Self::load(Path::new(&path)).unwrap_or_else(|e| {
+            tracing::warn!("无法加载净化策略配置，使用内置策略: {}", e);
+            Self::default()
+        })
+    }
+
+    /// The policy for a content type.
+    pub fn get(&self, kind: ContentKind) -> &SanitizerPolicy {
+        match kind {
+            ContentKind::Post => &self.post,
+            ContentKind::Comment => &self.comment,
+        }
+    }
+}
+
+/// The policy in effect for a content type.
+pub fn policy_for(kind: ContentKind) -> &'static SanitizerPolicy {
+    POLICIES.get(kind)
 }
 
 /// Sanitizes user-generated content to make it safe for display
 /// while preserving basic formatting (e.g., line breaks and simple tags).
-pub fn sanitize_content(content: &str) -> String {
+pub fn sanitize_content(content: &str, policy: &SanitizerPolicy) -> String {
     // Convert newlines to <br> for readability before sanitization.
-    // The sanitizer will keep <br> because it's in the allowlist.
+    // The sanitizer will keep <br> if the policy allows it.
     let with_breaks = content.replace('\n', "<br>");
+    sanitize_html(&with_breaks, policy)
+}
 
-    // Clean using a strict allowlist policy.
+/// Sanitizes HTML without touching newlines, for markup that already
+/// encodes its line breaks (rendered Markdown, stored content).
+pub fn sanitize_html(html: &str, policy: &SanitizerPolicy) -> String {
+    // Clean using the policy's allowlist.
     // - Removes event handlers (onload, onerror, etc.)
-    // - Removes style and other unsafe attributes
+    // - Removes style and other attributes the policy does not allow
     // - Strips dangerous tags (script, iframe, etc.)
-    // - Rejects javascript: / data: / vbscript: URLs
-    SANITIZER.clean(&with_breaks).to_string()
+    // - Rejects URLs whose scheme the policy does not allow
+    policy.clean(html)
 }
 
 /// Sanitizes content for database insertion
@@ -71,6 +299,10 @@ pub fn sanitize_for_db(content: &str) -> String {
 mod tests {
     use super::*;
 
+    fn sanitize_content(content: &str) -> String {
+        super::sanitize_content(content, &SanitizerPolicy::default())
+    }
+
     #[test]
     fn test_sanitize_content() {
         // <script> should be removed entirely
@@ -103,4 +335,47 @@ mod tests {
         let result = sanitize_content("<img src=\"data:text/html,<script>alert(1)</script>\">");
         assert!(!result.to_lowercase().contains("data:"));
     }
+
+    #[test]
+    fn test_sanitize_html_keeps_newlines() {
+        let html = "<pre><code>a\nb\n</code></pre>\n<p>x</p>";
+        let policy = SanitizerPolicy::default();
+        assert_eq!(sanitize_html(html, &policy), html);
+        assert_eq!(sanitize_html("<p onclick=\"x()\">a</p>", &policy), "<p>a</p>");
+    }
+
+    #[test]
+    fn test_policies_per_content_type() {
+        let policies = SanitizerPolicies::parse(
+            r#"
+            [content]
+            comment = "inline"
+
+            [policies.inline]
+            tags = ["b", "br"]
+            link_rel = "nofollow"
+            "#,
+        )
+        .unwrap();
+        let html = "<ul><li><b>a</b></li></ul>";
+        let post = policies.get(ContentKind::Post);
+        let comment = policies.get(ContentKind::Comment);
+        assert_eq!(post.name(), DEFAULT_POLICY);
+        assert_eq!(sanitize_html(html, post), html);
+        assert_eq!(comment.name(), "inline");
+        assert_eq!(sanitize_html(html, comment), "<b>a</b>");
+        assert_ne!(post.version(), comment.version());
+        assert_eq!(post.version(), SanitizerPolicy::default().version());
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        assert!(SanitizerPolicies::parse("[content]\npost = \"missing\"").is_err());
+        assert!(SanitizerPolicies::parse("[policies.bad]\ntags = [\"script\"]").is_err());
+        assert!(SanitizerPolicies::parse(
+            "[policies.bad]\ntags = [\"a\"]\ngeneric_attributes = [\"rel\"]\nlink_rel = \"nofollow\""
+        )
+        .is_err());
+        assert!(SanitizerPolicies::parse("[policies.bad]\ntagz = []").is_err());
+    }
 }
@@ -0,0 +1,255 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use unicode_normalization::UnicodeNormalization;
+use uuid::Uuid;
+
+use crate::models::format::ContentFormat;
+use crate::utils::filter::{current_filter, SensitiveWordFilter};
+use crate::utils::markdown::literal_ranges;
+
+pub mod store;
+
+/// 每个帖子最多记录的话题数量，多出的忽略
+pub const MAX_TAGS: usize = 10;
+
+/// 话题名称的最大字符数，更长的不算话题
+pub const MAX_TAG_CHARS: usize = 32;
+
+/// 启动时每批提取话题的帖子数
+const BACKFILL_BATCH_SIZE: i64 = 500;
+
+lazy_static! {
+    /// `#话题`：`#` 后面连续的字母、数字和下划线
+    static ref HASHTAG: Regex = Regex::new(r"#([\p{L}\p{N}\p{M}_]+)").unwrap();
+}
+
+/// 规范化话题名称：NFKC（全角转半角）、转为小写、繁体转简体
+///
+/// 同一个话题的不同写法（`#Rust`、`#ＲＵＳＴ`，`#話題`、`#话题`）对应同一个名称
+pub fn normalize_tag(name: &str) -> String {
+    let folded: String = name.nfkc().flat_map(char::to_lowercase).collect();
+    fast2s::convert(&folded)
+}
+
+/// 找出内容中的话题，返回规范化后的名称，按第一次出现的顺序，不重复
+///
+/// `#` 前面必须是开头、空白或标点，因此链接中的 `#fragment`、HTML 实体 `&#39;`
+/// 以及紧跟在文字后面的 `#` 不算话题；只有数字的（如 `>>#3` 楼层引用）和超过
+/// [`MAX_TAG_CHARS`] 个字符的也不算。代码、链接和标签中的文字不解析
+pub fn extract_tags(text: &str, format: ContentFormat) -> Vec<String> {
+    let skipped = literal_ranges(text, format);
+    let mut tags: Vec<String> = Vec::new();
+    for captures in HASHTAG.captures_iter(text) {
+        let (Some(whole), Some(name)) = (captures.get(0), captures.get(1)) else {
+            continue;
+        };
+        let preceded_by_word = text[..whole.start()]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || "_&#/".contains(c));
+        if preceded_by_word
+            || skipped.iter().any(|range| range.contains(&whole.start()))
+            || name.as_str().chars().all(|c| c.is_ascii_digit())
+            || name.as_str().chars().count() > MAX_TAG_CHARS
+        {
+            continue;
+        }
+        let tag = normalize_tag(name.as_str());
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+        if tags.len() == MAX_TAGS {
+            break;
+        }
+    }
+    tags
+}
+
+/// 去掉包含敏感词的话题
+///
+/// 话题公开列在话题列表中，因此命中词表中任何处理方式的词都不记录，
+/// 帖子本身是否公开仍由审核流水线决定
+pub fn accepted_tags(filter: &SensitiveWordFilter, tags: Vec<String>) -> Vec<String> {
+    tags.into_iter()
+        .filter(|tag| {
+            let refused = !filter.find_matches(tag).is_empty();
+            if refused {
+                tracing::debug!("话题 #{} 包含敏感词，不记录", tag);
+            }
+            !refused
+        })
+        .collect()
+}
+
+/// 只保留经过审核后仍然完整的话题
+///
+/// 话题从原文中提取，但只有在审核后的文本中原样出现的才记录：审核流水线替换或删去了
+/// 其中文字的话题（如包含联系方式或敏感词）不记录，替换后剩下的部分也不作为话题。
+/// 只转人工审核或拒绝、不替换文字的敏感词由 [`accepted_tags`] 去掉
+///
+/// # Arguments
+/// * `raw` - 从原文中提取的话题
+/// * `moderated` - 从审核后的文本中提取的话题
+pub fn moderated_tags(
+    filter: &SensitiveWordFilter,
+    raw: Vec<String>,
+    moderated: &[String],
+) -> Vec<String> {
+    let intact = raw
+        .into_iter()
+        .filter(|tag| {
+            let intact = moderated.contains(tag);
+            if !intact {
+                tracing::debug!("话题 #{} 被审核流水线修改，不记录", tag);
+            }
+            intact
+        })
+        .collect();
+    accepted_tags(filter, intact)
+}
+
+/// 找出帖子的话题并保存，失败时只记录日志，不影响发布
+///
+/// # Arguments
+/// * `raw` - 用户提交的原文
+/// * `moderated` - 审核流水线处理后的文本，见 [`moderated_tags`]
+///
+/// # Returns
+/// * 帖子的话题，与查询时一样按名称排序；保存失败时为空
+pub async fn record_tags(
+    pool: &PgPool,
+    post_id: Uuid,
+    raw: &str,
+    moderated: &str,
+    format: ContentFormat,
+) -> Vec<String> {
+    let mut tags = moderated_tags(
+        &current_filter(),
+        extract_tags(raw, format),
+        &extract_tags(moderated, format),
+    );
+    if let Err(e) = store::set_post_tags(pool, post_id, &tags).await {
+        tracing::warn!("保存帖子 {} 的话题失败: {}", post_id, e);
+        return Vec::new();
+    }
+    tags.sort();
+    tags
+}
+
+/// 为还没有提取过话题的帖子提取话题
+///
+/// 启动时调用，处理话题功能上线之前发布的帖子。使用保存的原文，只保留在显示内容中
+/// 原样出现的话题；保存原文之前创建的帖子使用显示内容，按 HTML 格式处理
+///
+/// # Returns
+/// * 处理的帖子数量，或者数据库错误
+pub async fn tag_existing_posts(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let filter = current_filter();
+    let mut tagged = 0;
+    let mut last: Option<Uuid> = None;
+    loop {
+        let rows: Vec<(Uuid, Option<String>, String, String)> = sqlx::query_as(
+            r#"
+            SELECT id, raw_content, content, content_format
+            FROM posts
+            WHERE NOT tagged AND ($1::uuid IS NULL OR id > $1)
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+        .bind(last)
+        .bind(BACKFILL_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+        let Some((id, _, _, _)) = rows.last() else {
+            break;
+        };
+        last = Some(*id);
+
+        for (id, raw, content, format) in &rows {
+            let shown = extract_tags(content, ContentFormat::Html);
+            let tags = match raw {
+                Some(raw) => {
+                    let submitted = extract_tags(raw, ContentFormat::from_db(format));
+                    moderated_tags(&filter, submitted, &shown)
+                }
+                None => accepted_tags(&filter, shown),
+            };
+            store::set_post_tags(pool, *id, &tags).await?;
+        }
+        tagged += rows.len() as u64;
+        tracing::debug!("为已有帖子提取话题，累计 {} 个", tagged);
+    }
+    Ok(tagged)
+}
+
+/// 查询一批帖子的话题，查询失败时返回空结果
+pub async fn tags_for(pool: &PgPool, post_ids: &[Uuid]) -> HashMap<Uuid, Vec<String>> {
+    store::load_post_tags(pool, post_ids)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("无法查询帖子的话题: {}", e);
+            HashMap::new()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::filter::FilterAction;
+    use crate::utils::moderation::{KeywordClassifier, ModerationPipeline, PiiClassifier, PiiKind};
+    use std::sync::Arc;
+
+    fn tags(text: &str) -> Vec<String> {
+        extract_tags(text, ContentFormat::Markdown)
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        assert_eq!(
+            tags("#话题 今天天气不错，#Rust_lang 和 #rust_LANG。（#話題）\n#第二行"),
+            vec!["话题", "rust_lang", "第二行"]
+        );
+        assert!(tags("a#b https://example.com/#top &#39; >>#3 #123 ## # 文字#紧跟").is_empty());
+        assert!(tags("`#代码` [#链接](https://example.com/#a)\n\n```\n#代码块\n```").is_empty());
+        assert_eq!(tags(&format!("#{} #短", "长".repeat(MAX_TAG_CHARS + 1))), vec!["短"]);
+        assert_eq!(
+            extract_tags(r##"<a href="#锚点">#链接文字</a> <b>#粗体</b>"##, ContentFormat::Html),
+            vec!["链接文字", "粗体"]
+        );
+        let many: String = (0..MAX_TAGS + 5).map(|i| format!("#t{} ", i)).collect();
+        assert_eq!(tags(&many).len(), MAX_TAGS);
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("ＲＵＳＴ"), "rust");
+        assert_eq!(normalize_tag("話題"), "话题");
+    }
+
+    #[tokio::test]
+    async fn test_moderated_tags() {
+        let filter = SensitiveWordFilter::new(["犯罪"]);
+        let pipeline = ModerationPipeline::new(vec![
+            Arc::new(PiiClassifier::new(FilterAction::Mask, PiiKind::ALL.to_vec())),
+            Arc::new(KeywordClassifier::with_filter(SensitiveWordFilter::new(["犯罪"]))),
+        ]);
+        let raw = "#新闻 #QQ12345678 #微信号wxid_abc #犯罪现场";
+        let moderated = pipeline.run(raw).await.content;
+        assert_eq!(
+            moderated_tags(&filter, tags(raw), &tags(&moderated)),
+            vec!["新闻"]
+        );
+    }
+
+    #[test]
+    fn test_refused_tags() {
+        let filter = SensitiveWordFilter::new(["犯罪"]);
+        assert_eq!(
+            accepted_tags(&filter, tags("#新闻 #犯罪现场 #犯_罪")),
+            vec!["新闻"]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use sqlx::{PgExecutor, PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 替换一个帖子的话题，并重新统计涉及的话题的帖子数量
+///
+/// 新的话题在这里创建，之后不再删除，帖子数量为 0 的话题不出现在话题列表中
+pub async fn set_post_tags(pool: &PgPool, post_id: Uuid, tags: &[String]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    if !tags.is_empty() {
+        sqlx::query("INSERT INTO tags (name) SELECT unnest($1::text[]) ON CONFLICT (name) DO NOTHING")
+            .bind(tags)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let removed: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        DELETE FROM post_tags
+        WHERE post_id = $1 AND tag_id NOT IN (SELECT id FROM tags WHERE name = ANY($2))
+        RETURNING tag_id
+        "#,
+    )
+    .bind(post_id)
+    .bind(tags)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO post_tags (post_id, tag_id)
+        SELECT $1, id FROM tags WHERE name = ANY($2)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(post_id)
+    .bind(tags)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE posts SET tagged = true WHERE id = $1")
+        .bind(post_id)
+        .execute(&mut *tx)
+        .await?;
+    refresh_tag_counts(&mut *tx, &[post_id], &removed).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 重新统计话题中已公开帖子的数量
+///
+/// 帖子的话题或发布状态改变后调用
+///
+/// # Arguments
+/// * `post_ids` - 状态或话题改变的帖子，统计它们现在的全部话题
+/// * `tag_ids` - 另外需要统计的话题，例如刚从帖子上去掉的话题
+pub async fn refresh_tag_counts<'e, E: PgExecutor<'e>>(
+    executor: E,
+    post_ids: &[Uuid],
+    tag_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE tags t SET post_count = (
+            SELECT COUNT(*) FROM post_tags pt
+            JOIN posts p ON p.id = pt.post_id
+            WHERE pt.tag_id = t.id AND p.status = 'published'
+        )
+        WHERE t.id IN (SELECT tag_id FROM post_tags WHERE post_id = ANY($1))
+            OR t.id = ANY($2)
+        "#,
+    )
+    .bind(post_ids)
+    .bind(tag_ids)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// 查询一批帖子的话题，按话题名称排序
+pub async fn load_post_tags(
+    pool: &PgPool,
+    post_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error> {
+    if post_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows = sqlx::query(
+        r#"
+        SELECT pt.post_id, t.name
+        FROM post_tags pt
+        JOIN tags t ON t.id = pt.tag_id
+        WHERE pt.post_id = ANY($1)
+        ORDER BY t.name COLLATE "C"
+        "#,
+    )
+    .bind(post_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for row in rows {
+        tags.entry(row.get("post_id")).or_default().push(row.get("name"));
+    }
+    Ok(tags)
+}
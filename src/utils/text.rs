@@ -0,0 +1,149 @@
+/// 块级标签前后各空一行
+const BLOCK_TAGS: [&str; 12] = [
+    "p", "pre", "blockquote", "div", "table", "h1", "h2", "h3", "h4", "h5", "h6", "hr",
+];
+
+/// 行级标签前后各换一行
+const LINE_TAGS: [&str; 4] = ["li", "ul", "ol", "tr"];
+
+/// 把净化后的 HTML 转换为纯文本
+///
+/// 去掉所有标签并解码实体；`<br>` 转为换行，段落、引用、代码块等块之间空一行，列表项各占一行。
+/// 代码块中的空白原样保留，其他地方 HTML 源码中的换行按空格处理（紧跟在换行之后的除外）。
+/// 输入应当是净化后的内容：文字中的 `<` 已经转义，属性值带引号
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut pre_depth = 0usize;
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        push_text(&mut text, &rest[..start], pre_depth > 0);
+        let (tag, remaining) = match tag_end(&rest[start..]) {
+            Some(end) => (&rest[start + 1..start + end - 1], &rest[start + end..]),
+            None => (&rest[start + 1..], ""),
+        };
+        rest = remaining;
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match name.as_str() {
+            "br" => text.push('\n'),
+            "pre" => {
+                if closing {
+                    pre_depth = pre_depth.saturating_sub(1);
+                } else {
+                    pre_depth += 1;
+                }
+                ensure_newlines(&mut text, 2);
+            }
+            name if BLOCK_TAGS.contains(&name) => ensure_newlines(&mut text, 2),
+            name if LINE_TAGS.contains(&name) => ensure_newlines(&mut text, 1),
+            _ => {}
+        }
+    }
+    push_text(&mut text, rest, pre_depth > 0);
+
+    text.trim_matches('\n').to_string()
+}
+
+/// 标签结束位置（`>` 之后），跳过引号中的 `>`
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 追加标签之间的文字
+fn push_text(text: &mut String, raw: &str, preformatted: bool) {
+    if raw.is_empty() {
+        return;
+    }
+    let decoded = html_escape::decode_html_entities(raw);
+    if preformatted {
+        text.push_str(&decoded);
+        return;
+    }
+    for c in decoded.chars() {
+        if c == '\n' || c == '\r' {
+            if !text.is_empty() && !text.ends_with(['\n', ' ']) {
+                text.push(' ');
+            }
+        } else {
+            text.push(c);
+        }
+    }
+}
+
+/// 保证文字末尾至少有指定数量的换行，开头不加换行
+fn ensure_newlines(text: &mut String, count: usize) {
+    if text.is_empty() {
+        return;
+    }
+    text.truncate(text.trim_end_matches(' ').len());
+    let existing = text.len() - text.trim_end_matches('\n').len();
+    for _ in existing..count {
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::format::ContentFormat;
+    use crate::utils::render::render_html;
+    use crate::utils::sanitize::SanitizerPolicy;
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(html_to_text("第一行<br>第二行"), "第一行\n第二行");
+        assert_eq!(
+            html_to_text("a &lt;b&gt; &amp;&amp; c &quot;d&quot; &#39;e&#39;"),
+            "a <b> && c \"d\" 'e'"
+        );
+        assert_eq!(
+            html_to_text(r#"<a href="https://example.com/?a=1&amp;b=2" title="x > y" rel="nofollow">链接</a>"#),
+            "链接"
+        );
+        assert_eq!(html_to_text(""), "");
+    }
+
+    #[test]
+    fn test_rendered_markdown_to_text() {
+        let policy = SanitizerPolicy::default();
+        let html = render_html(
+            "**粗体** 和 `a < b`\n第二行\n\n- 一\n- 二\n\n> 引用\n\n```\nfn main() {\n    1 < 2;\n}\n```",
+            ContentFormat::Markdown,
+            &policy,
+            true,
+        );
+        assert_eq!(
+            html_to_text(&html),
+            "粗体 和 a < b\n第二行\n\n一\n二\n\n引用\n\nfn main() {\n    1 < 2;\n}"
+        );
+    }
+
+    #[test]
+    fn test_rendered_html_to_text() {
+        let policy = SanitizerPolicy::default();
+        let html = render_html(
+            "<p>段落</p>第一行\n<b>第二行</b> 5 > 3",
+            ContentFormat::Html,
+            &policy,
+            true,
+        );
+        assert_eq!(html_to_text(&html), "段落\n\n第一行\n第二行 5 > 3");
+    }
+}
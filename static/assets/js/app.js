@@ -134,6 +134,7 @@
       const postElement = document.createElement("div");
       postElement.className = "card mb-4";
       postElement.dataset.postId = post.id;
+      postElement.id = `post-${post.id}`;
 
       // 确保内容安全
       const content = post.content || "(无内容)";
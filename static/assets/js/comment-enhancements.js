@@ -349,6 +349,7 @@
         const commentElement = document.createElement('div');
         commentElement.className = 'comment';
         commentElement.dataset.commentId = comment.id;
+        commentElement.id = `comment-${comment.id}`;
         
         // 添加评论内容
         const content = document.createElement('div');
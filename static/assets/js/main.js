@@ -135,6 +135,7 @@ function createPostElement(post) {
     const postElement = document.createElement('div');
     postElement.className = 'card post-card';
     postElement.dataset.postId = post.id;
+    postElement.id = `post-${post.id}`;
 
     // 创建帖子内容
     const postContent = document.createElement('div');
@@ -385,6 +386,7 @@ function createCommentElement(comment) {
     const commentElement = document.createElement('div');
     commentElement.className = 'comment';
     commentElement.dataset.commentId = comment.id;
+    commentElement.id = `comment-${comment.id}`;
 
     // 添加评论内容
     const content = document.createElement('div');